@@ -0,0 +1,192 @@
+use cc_matching_engine::workload::*;
+use cc_matching_engine::*;
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use uuid::Uuid;
+
+const QUEUE_DEPTHS: [usize; 4] = [1, 10, 100, 1_000];
+const SWEPT_LEVELS: [u64; 4] = [1, 10, 100, 1_000];
+const LEVEL_PRICE: OrderPrice = 100_000;
+const LEVEL_VOLUME: OrderVolume = 10;
+
+fn resting_order(order_id: u128) -> OrderbookOrder {
+    OrderbookOrder {
+        id: Uuid::from_u128(order_id),
+        remaining_volume: LEVEL_VOLUME,
+        account: None,
+        session_id: None,
+    }
+}
+
+// A single price level holding `queue_depth` orders.
+fn queued_orderbook(queue_depth: usize) -> Orderbook {
+    let mut orderbook = Orderbook::default();
+
+    for order_id in 0..queue_depth as u128 {
+        orderbook.insert(LEVEL_PRICE, resting_order(order_id));
+    }
+
+    orderbook
+}
+
+// `levels` ask levels of one order each, the best at LEVEL_PRICE.
+fn laddered_orderbooks(levels: u64) -> Orderbooks {
+    let mut the_orderbooks = Orderbooks::default();
+
+    for level in 0..levels {
+        the_orderbooks.execute_order(&OrderMessage {
+            side: OrderSide::Ask,
+            r#type: OrderType::Limit,
+            volume: Some(LEVEL_VOLUME),
+            price: Some(LEVEL_PRICE + level),
+            ..Default::default()
+        });
+    }
+
+    the_orderbooks
+}
+
+fn bench_orderbook_insert(c: &mut Criterion) {
+    let mut group = c.benchmark_group("orderbook_insert");
+
+    for queue_depth in QUEUE_DEPTHS.iter() {
+        let orderbook = queued_orderbook(*queue_depth);
+
+        group.bench_with_input(BenchmarkId::from_parameter(queue_depth), queue_depth, |b, _| {
+            b.iter_batched_ref(
+                || orderbook.clone(),
+                |orderbook| orderbook.insert(LEVEL_PRICE, resting_order(u128::MAX)),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+// Removes the last order of the queue, the longest scan for its position.
+fn bench_orderbook_remove(c: &mut Criterion) {
+    let mut group = c.benchmark_group("orderbook_remove");
+
+    for queue_depth in QUEUE_DEPTHS.iter() {
+        let orderbook = queued_orderbook(*queue_depth);
+        let last_id = Uuid::from_u128(*queue_depth as u128 - 1);
+
+        group.bench_with_input(BenchmarkId::from_parameter(queue_depth), queue_depth, |b, _| {
+            b.iter_batched_ref(
+                || orderbook.clone(),
+                |orderbook| orderbook.remove(&last_id),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_limit_sweep(c: &mut Criterion) {
+    let mut group = c.benchmark_group("limit_bid_sweeping_levels");
+
+    for levels in SWEPT_LEVELS.iter() {
+        let the_orderbooks = laddered_orderbooks(*levels);
+        let limit_bid = OrderMessage {
+            side: OrderSide::Bid,
+            r#type: OrderType::Limit,
+            volume: Some(LEVEL_VOLUME * levels),
+            price: Some(LEVEL_PRICE + levels - 1),
+            ..Default::default()
+        };
+
+        group.bench_with_input(BenchmarkId::from_parameter(levels), levels, |b, _| {
+            b.iter_batched_ref(
+                || the_orderbooks.clone(),
+                |the_orderbooks| the_orderbooks.execute_order(&limit_bid),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+// The max quote runs out exactly at the last level, with volume to spare.
+fn bench_market_bid_max_quote(c: &mut Criterion) {
+    let mut group = c.benchmark_group("market_bid_max_quote");
+
+    for levels in SWEPT_LEVELS.iter() {
+        let the_orderbooks = laddered_orderbooks(*levels);
+        let max_quote = (0..*levels).map(|level| (LEVEL_PRICE + level) * LEVEL_VOLUME).sum();
+        let market_bid = OrderMessage {
+            side: OrderSide::Bid,
+            r#type: OrderType::Market,
+            volume: Some(LEVEL_VOLUME * levels * 2),
+            max_quote: Some(max_quote),
+            ..Default::default()
+        };
+
+        group.bench_with_input(BenchmarkId::from_parameter(levels), levels, |b, _| {
+            b.iter_batched_ref(
+                || the_orderbooks.clone(),
+                |the_orderbooks| the_orderbooks.execute_order(&market_bid),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_cancel_heavy(c: &mut Criterion) {
+    const ORDER_COUNT: usize = 1_000;
+    let mut group = c.benchmark_group("cancel_heavy_workload");
+    group.throughput(Throughput::Elements(ORDER_COUNT as u64));
+
+    for depth in [10, 1_000].iter() {
+        let config = WorkloadConfig {
+            limit_ratio: 25,
+            market_ratio: 5,
+            cancel_ratio: 70,
+            depth: *depth,
+            ..Default::default()
+        };
+        let mut generator = WorkloadGenerator::new(config);
+        let mut the_orderbooks = Orderbooks::default();
+
+        for order in generator.prefill() {
+            the_orderbooks.execute_order(&order);
+        }
+
+        // Generated against a scratch copy, so every cancel finds its target.
+        let mut scratch_orderbooks = the_orderbooks.clone();
+        let orders: Vec<OrderMessage> = (0..ORDER_COUNT)
+            .map(|_| {
+                let order = generator.next_order(&scratch_orderbooks);
+                scratch_orderbooks.execute_order(&order);
+                order
+            })
+            .collect();
+
+        group.bench_with_input(BenchmarkId::new("depth", depth), depth, |b, _| {
+            b.iter_batched_ref(
+                || the_orderbooks.clone(),
+                |the_orderbooks| {
+                    for order in orders.iter() {
+                        the_orderbooks.execute_order(order);
+                    }
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_orderbook_insert,
+    bench_orderbook_remove,
+    bench_limit_sweep,
+    bench_market_bid_max_quote,
+    bench_cancel_heavy
+);
+criterion_main!(benches);
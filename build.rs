@@ -0,0 +1,25 @@
+use std::env;
+use std::path::Path;
+
+// Regenerates the C header for the `ffi` module when `CCME_GENERATE_HEADER` is
+// set, so plain builds leave the source tree alone.
+fn main() {
+    println!("cargo:rerun-if-env-changed=CCME_GENERATE_HEADER");
+
+    if env::var_os("CCME_GENERATE_HEADER").is_none() {
+        return;
+    }
+
+    let crate_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+    let ffi_source = Path::new(&crate_dir).join("src/ffi/mod.rs");
+    println!("cargo:rerun-if-changed={}", ffi_source.display());
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+
+    let config = cbindgen::Config::from_file(Path::new(&crate_dir).join("cbindgen.toml")).unwrap();
+    cbindgen::Builder::new()
+        .with_config(config)
+        .with_src(ffi_source)
+        .generate()
+        .expect("Unable to generate the C bindings")
+        .write_to_file(Path::new(&crate_dir).join("include/cc_matching_engine.h"));
+}
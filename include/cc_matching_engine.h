@@ -0,0 +1,247 @@
+#ifndef CC_MATCHING_ENGINE_H
+#define CC_MATCHING_ENGINE_H
+
+/* Generated by cbindgen from src/ffi/mod.rs, do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+#define CCME_OK 0
+
+#define CCME_ERROR_NULL_POINTER -1
+
+#define CCME_ERROR_INVALID_ENUM -2
+
+#define CCME_ERROR_INVALID_SIZE -3
+
+#define CCME_REJECTED_HALTED 1
+
+#define CCME_REJECTED_UNKNOWN_ORDER 2
+
+#define CCME_REJECTED_DUPLICATE_ORDER_ID 3
+
+#define CCME_REJECTED_MISSING_SIDE 4
+
+#define CCME_REJECTED_INVALID_VOLUME 5
+
+#define CCME_REJECTED_INVALID_PRICE 6
+
+#define CCME_REJECTED_MISSING_MAX_QUOTE 7
+
+#define CCME_REJECTED_SIDE_MISMATCH 8
+
+#define CCME_REJECTED_IN_AUCTION 9
+
+#define CCME_REJECTED_PRE_OPEN 10
+
+#define CCME_REJECTED_CLOSED 11
+
+#define CCME_REJECTED_OUTSIDE_PRICE_BAND 12
+
+#define CCME_REJECTED_PRICE_BAND_BREACH 13
+
+#define CCME_REJECTED_INVALID_EXPIRY 14
+
+#define CCME_REJECTED_DUPLICATE_CLIENT_ORDER_ID 15
+
+#define CCME_REJECTED_INSUFFICIENT_FUNDS 16
+
+#define CCME_REJECTED_OPENING_AUCTION 17
+
+#define CCME_REJECTED_CLOSING_AUCTION 18
+
+#define CCME_REJECTED_VOLATILITY_AUCTION 19
+
+#define CCME_REJECTED_CONTINUOUS 20
+
+#define CCME_ORDER_MESSAGE_MIN_SIZE 80
+
+#define CCME_SIDE_NONE 0
+
+#define CCME_SIDE_BID 1
+
+#define CCME_SIDE_ASK 2
+
+#define CCME_TYPE_CANCEL 0
+
+#define CCME_TYPE_MARKET 1
+
+#define CCME_TYPE_LIMIT 2
+
+#define CCME_TYPE_REPLACE 3
+
+#define CCME_TIF_GOOD_TILL_CANCEL 0
+
+#define CCME_TIF_GOOD_TILL_DATE 1
+
+#define CCME_TIF_DAY 2
+
+#define CCME_EVENT_NO_MATCH 0
+
+#define CCME_EVENT_HAS_MATCH 1
+
+#define CCME_EVENT_OPEN 2
+
+#define CCME_EVENT_CLOSED 3
+
+#define CCME_EVENT_CANCELLED 4
+
+#define CCME_EVENT_REPLACED 5
+
+#define CCME_EVENT_EXPIRED 6
+
+#define CCME_EVENT_MASS_CANCELLED 7
+
+#define CCME_EVENT_REJECTED 8
+
+/**
+ * Opaque handle to one `Orderbooks`.
+ */
+typedef struct CcmeOrderbooks CcmeOrderbooks;
+
+/**
+ * Order ids are UUIDs in their 16-byte big-endian form, all zero means none.
+ */
+typedef struct {
+  uint8_t bytes[16];
+} CcmeOrderId;
+
+/**
+ * Mirrors `OrderMessage` without the client order ids. A zero volume, price,
+ * max quote, `expires_at` or session id means none, a zero id is replaced by
+ * a generated one and a zero `created_at` by now. `account` is only read when
+ * `has_account` is set, so account 0 can be given.
+ *
+ * `struct_size` must be `sizeof(CcmeOrderMessage)`. New fields are only ever
+ * appended, those past the `struct_size` of a caller built against an older
+ * header read as zero.
+ */
+typedef struct {
+  size_t struct_size;
+  CcmeOrderId id;
+  CcmeOrderId target_id;
+  int64_t created_at;
+  uint8_t side;
+  uint8_t order_type;
+  uint64_t volume;
+  uint64_t price;
+  uint64_t max_quote;
+  /**
+   * Fields from here on are past `CCME_ORDER_MESSAGE_MIN_SIZE`.
+   */
+  uint8_t time_in_force;
+  int64_t expires_at;
+  uint64_t account;
+  uint64_t session_id;
+  bool has_account;
+} CcmeOrderMessage;
+
+/**
+ * Mirrors `OrderEvent`, tagged with the order it belongs to. A zero traded
+ * price or crossed id means none.
+ */
+typedef struct {
+  CcmeOrderId order_id;
+  int64_t timestamp;
+  uint8_t event_type;
+  bool has_remaining_volume;
+  uint64_t remaining_volume;
+  uint64_t traded_price;
+  CcmeOrderId crossed_id;
+} CcmeOrderEvent;
+
+typedef void (*CcmeEventCallback)(const CcmeOrderEvent *event, void *user_data);
+
+typedef struct {
+  uint64_t price;
+  uint64_t volume;
+} CcmeDepthLevel;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+CcmeOrderbooks *ccme_orderbooks_new(void);
+
+/**
+ * # Safety
+ *
+ * `book` must come from `ccme_orderbooks_new` and not be used afterwards.
+ */
+void ccme_orderbooks_free(CcmeOrderbooks *book);
+
+/**
+ * Validates and executes `order`, writing the id used back into it. Events
+ * are passed to `callback` one at a time, those of `order` first, and only
+ * live for the duration of the call. Returns `CCME_OK`, a `CCME_REJECTED_*`
+ * code or a `CCME_ERROR_*` code.
+ *
+ * # Safety
+ *
+ * `book` must come from `ccme_orderbooks_new` and `order` must point to a
+ * `CcmeOrderMessage` of at least `struct_size` bytes.
+ */
+int32_t ccme_orderbooks_submit(CcmeOrderbooks *book,
+                               CcmeOrderMessage *order,
+                               CcmeEventCallback callback,
+                               void *user_data);
+
+/**
+ * Expires the good till date orders due at or before `now` and passes their
+ * `CCME_EVENT_EXPIRED` events to `callback`. Returns how many expired.
+ *
+ * # Safety
+ *
+ * `book` must come from `ccme_orderbooks_new`.
+ */
+size_t ccme_orderbooks_expire_until(CcmeOrderbooks *book,
+                                    int64_t now,
+                                    CcmeEventCallback callback,
+                                    void *user_data);
+
+/**
+ * Cancels the resting orders of `session_id` unless it opted out of cancel on
+ * disconnect, and passes their `CCME_EVENT_CANCELLED` events to `callback`.
+ * Returns how many were cancelled.
+ *
+ * # Safety
+ *
+ * `book` must come from `ccme_orderbooks_new`.
+ */
+size_t ccme_orderbooks_disconnect_session(CcmeOrderbooks *book,
+                                          uint64_t session_id,
+                                          CcmeEventCallback callback,
+                                          void *user_data);
+
+/**
+ * Copies up to `capacity` aggregated levels of one side, best first, and
+ * returns how many were written.
+ *
+ * # Safety
+ *
+ * `book` must come from `ccme_orderbooks_new` and `levels` must have room for
+ * `capacity` entries.
+ */
+size_t ccme_orderbooks_depth(const CcmeOrderbooks *book,
+                             uint8_t side,
+                             CcmeDepthLevel *levels,
+                             size_t capacity);
+
+/**
+ * Returns the number of resting orders on one side, or on both for
+ * `CCME_SIDE_NONE`.
+ *
+ * # Safety
+ *
+ * `book` must come from `ccme_orderbooks_new`.
+ */
+size_t ccme_orderbooks_count(const CcmeOrderbooks *book, uint8_t side);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* CC_MATCHING_ENGINE_H */
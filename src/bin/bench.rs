@@ -0,0 +1,112 @@
+use cc_matching_engine::workload::*;
+use cc_matching_engine::*;
+use hdrhistogram::Histogram;
+use std::env;
+use std::mem::{align_of, size_of};
+use std::process;
+use std::time::Instant;
+
+macro_rules! show_size {
+    ($t:ty) => {
+        println!(
+            "- {}:\n  * Size {} bytes\n  * Aligment {} bytes",
+            stringify!($t),
+            size_of::<$t>(),
+            align_of::<$t>()
+        );
+    };
+}
+
+fn print_info_headers() {
+    println!();
+    println!("CC Matching Engine (Rust) - 1.0.0-beta.0");
+    println!("========================================");
+    println!();
+}
+
+fn print_structure_info() {
+    println!("[Data Structure Alignment]");
+    show_size!(OrderEvent);
+    show_size!(OrderMessage);
+    show_size!(OrderbookOrder);
+    show_size!(DepthUpdate);
+    show_size!(OrderFeedMessage);
+    show_size!(Trade);
+    show_size!(Orderbook);
+    show_size!(Orderbooks);
+}
+
+fn print_latency(name: &str, histogram: &Histogram<u64>) {
+    println!(
+        "- {}: {} orders, p50 {} ns, p99 {} ns, p99.9 {} ns, max {} ns",
+        name,
+        histogram.len(),
+        histogram.value_at_quantile(0.5),
+        histogram.value_at_quantile(0.99),
+        histogram.value_at_quantile(0.999),
+        histogram.max()
+    );
+}
+
+fn bench_workload(config: WorkloadConfig) {
+    println!(
+        "\n[Benchmark: {} Orders, Mix {}:{}:{}, {:?} Prices {} +/- {}, Depth {}, Seed {}]",
+        config.orders,
+        config.limit_ratio,
+        config.market_ratio,
+        config.cancel_ratio,
+        config.distribution,
+        config.mid_price,
+        config.price_range,
+        config.depth,
+        config.seed
+    );
+    print!("- Populating Orderbooks...");
+    let mut the_orderbooks = Orderbooks::default();
+    let mut generator = WorkloadGenerator::new(config.clone());
+    for order in generator.prefill() {
+        the_orderbooks.execute_order(&order);
+    }
+    println!("DONE");
+    print!("- Matching...");
+    // Latencies are recorded per order type, in nanoseconds up to one second.
+    let new_histogram = || Histogram::<u64>::new_with_bounds(1, 1_000_000_000, 3).unwrap();
+    let mut histograms = [new_histogram(), new_histogram(), new_histogram(), new_histogram()];
+    let mut total_latency = new_histogram();
+    let mut exec_span_nano = 0;
+    for _ in 0..config.orders {
+        let order = generator.next_order(&the_orderbooks);
+        let timestamp_start = Instant::now();
+        the_orderbooks.execute_order(&order);
+        let latency = timestamp_start.elapsed().as_nanos() as u64;
+        exec_span_nano += latency;
+        histograms[order.r#type as usize].saturating_record(latency.max(1));
+        total_latency.saturating_record(latency.max(1));
+    }
+    println!("DONE");
+    let ops = (config.orders as u128 * 1_000_000_000) / (exec_span_nano as u128).max(1);
+    println!("- Took {} ns to complete", exec_span_nano);
+    println!("- {} Orders per second", ops);
+    print_latency("All", &total_latency);
+    for r#type in [OrderType::Limit, OrderType::Market, OrderType::Cancel].iter() {
+        if !histograms[*r#type as usize].is_empty() {
+            print_latency(&format!("{:?}", r#type), &histograms[*r#type as usize]);
+        }
+    }
+    let (bids_count, asks_count, _) = the_orderbooks.count();
+    println!("- Orderbook Bids count {} orders", bids_count);
+    println!("- Orderbook Asks count {} orders", asks_count);
+}
+
+fn main() {
+    let config = match WorkloadConfig::from_args(env::args().skip(1)) {
+        Ok(config) => config,
+        Err(error) => {
+            eprintln!("{}\n\n{}", error, WORKLOAD_USAGE);
+            process::exit(2);
+        }
+    };
+    print_info_headers();
+    print_structure_info();
+    bench_workload(config);
+}
@@ -0,0 +1,62 @@
+use cc_matching_engine::fix::*;
+use std::env;
+use std::process;
+
+fn print_usage() {
+    eprintln!("Usage: fix_gateway [--listen ADDR] [--sender-comp-id ID] [--max-heartbeat SECONDS] --symbol SYMBOL...");
+}
+
+fn main() {
+    let mut listen_addr = "127.0.0.1:9878".to_string();
+    let mut config = FixSessionConfig {
+        sender_comp_id: "CCME".to_string(),
+        max_heartbeat_interval: 300,
+    };
+    let mut symbols = Vec::new();
+    let mut args = env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        let value = args.next();
+
+        match (arg.as_str(), value) {
+            ("--listen", Some(value)) => listen_addr = value,
+            ("--sender-comp-id", Some(value)) => config.sender_comp_id = value,
+            ("--max-heartbeat", Some(value)) => match value.parse() {
+                Ok(value) => config.max_heartbeat_interval = value,
+                Err(_) => {
+                    print_usage();
+                    process::exit(2);
+                }
+            },
+            ("--symbol", Some(value)) => symbols.push(value),
+            _ => {
+                print_usage();
+                process::exit(2);
+            }
+        }
+    }
+
+    if symbols.is_empty() {
+        print_usage();
+        process::exit(2);
+    }
+
+    let acceptor = match FixAcceptor::bind(&listen_addr, config, &symbols) {
+        Ok(acceptor) => acceptor,
+        Err(error) => {
+            eprintln!("Failed to listen on {}: {}", listen_addr, error);
+            process::exit(1);
+        }
+    };
+
+    println!(
+        "FIX 4.4 gateway listening on {} for {}",
+        acceptor.local_addr().unwrap(),
+        symbols.join(", ")
+    );
+
+    if let Err(error) = acceptor.run() {
+        eprintln!("FIX gateway stopped: {}", error);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,120 @@
+use cc_matching_engine::ouch::*;
+use cc_matching_engine::*;
+use std::env;
+use std::io::{BufWriter, Read, Write};
+use std::net::TcpStream;
+use std::process;
+use std::thread;
+use std::time::Instant;
+
+fn print_usage() {
+    eprintln!("Usage: ouch_loadgen [--connect ADDR] [--symbol SYMBOL] [--matches COUNT]");
+}
+
+#[derive(Debug, Default)]
+struct Tally {
+    accepted: u64,
+    rejected: u64,
+    executed: u64,
+    canceled: u64,
+}
+
+// Reads responses until every entered order has been accepted or rejected.
+fn count_responses(mut stream: TcpStream, order_count: u64) -> std::io::Result<Tally> {
+    let mut tally = Tally::default();
+    let mut buffer = Vec::new();
+    let mut chunk = vec![0; 64 * 1024];
+
+    while tally.accepted + tally.rejected < order_count {
+        let read_size = stream.read(&mut chunk)?;
+
+        if read_size == 0 {
+            break;
+        }
+
+        buffer.extend_from_slice(&chunk[..read_size]);
+
+        while let Some(payload) = take_payload(&mut buffer) {
+            match OuchResponse::decode(&payload) {
+                Some(OuchResponse::Accepted { .. }) => tally.accepted += 1,
+                Some(OuchResponse::Rejected { .. }) => tally.rejected += 1,
+                Some(OuchResponse::Executed { .. }) => tally.executed += 1,
+                Some(OuchResponse::Canceled { .. }) => tally.canceled += 1,
+                _ => {}
+            }
+        }
+    }
+
+    Ok(tally)
+}
+
+// Sends the same perfect limit match flow as the in-process benchmark: every
+// bid rests first, then every ask crosses one of them.
+fn run(addr: &str, symbol_name: &str, match_count: u64) -> std::io::Result<()> {
+    let stream = TcpStream::connect(addr)?;
+    stream.set_nodelay(true)?;
+    let order_count = match_count * 2;
+    let reader = stream.try_clone()?;
+    let responses = thread::spawn(move || count_responses(reader, order_count));
+    let mut writer = BufWriter::new(stream);
+    let mut buffer = Vec::new();
+    let timestamp_start = Instant::now();
+
+    for token in 0..order_count {
+        let (side, price) = if token < match_count {
+            (OrderSide::Bid, 100_000 - (token % 1000))
+        } else {
+            (OrderSide::Ask, 90_000 + (token % 1000))
+        };
+        let request = OuchRequest::EnterOrder {
+            token: token + 1,
+            symbol: symbol(symbol_name),
+            side,
+            r#type: OrderType::Limit,
+            volume: 20_000,
+            price,
+            max_quote: 0,
+        };
+        buffer.clear();
+        request.encode(&mut buffer);
+        writer.write_all(&buffer)?;
+    }
+
+    writer.flush()?;
+    let tally = responses.join().unwrap()?;
+    let elapsed = timestamp_start.elapsed();
+
+    println!("[OUCH Load: {} Limit Match ({} Orders)]", match_count, order_count);
+    println!("- Took {} ns to complete", elapsed.as_nanos());
+    println!(
+        "- {} Orders per second",
+        (order_count as f64 / elapsed.as_secs_f64()) as u64
+    );
+    println!("- {} accepted, {} rejected", tally.accepted, tally.rejected);
+    println!("- {} executed, {} canceled", tally.executed, tally.canceled);
+    Ok(())
+}
+
+fn main() {
+    let mut addr = "127.0.0.1:9002".to_string();
+    let mut symbol_name = "BTC-IDR".to_string();
+    let mut match_count = 50_000;
+    let mut args = env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), args.next()) {
+            ("--connect", Some(value)) => addr = value,
+            ("--symbol", Some(value)) => symbol_name = value,
+            ("--matches", Some(value)) if value.parse::<u64>().is_ok() => match_count = value.parse().unwrap(),
+            _ => {
+                print_usage();
+                process::exit(2);
+            }
+        }
+    }
+
+    if let Err(error) = run(&addr, &symbol_name, match_count) {
+        eprintln!("Load generator failed against {}: {}", addr, error);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,85 @@
+use cc_matching_engine::repl::*;
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::process;
+
+fn print_usage() {
+    eprintln!("Usage: repl [SCRIPT]");
+}
+
+// Returns false once the session should end.
+fn run_line(session: &mut ReplSession, line: &str) -> bool {
+    let result = ReplCommand::parse(line).and_then(|command| {
+        if command == ReplCommand::Quit {
+            return Ok(None);
+        }
+
+        session.run(command).map(Some)
+    });
+
+    match result {
+        Ok(None) => return false,
+        Ok(Some(output)) if !output.is_empty() => println!("{}", output),
+        Ok(Some(_)) => {}
+        Err(error) => println!("error: {}", error),
+    }
+
+    true
+}
+
+// Every line is echoed, so the output of a script reads like the session it replays.
+fn run_script(session: &mut ReplSession, path: &str) {
+    let script = match fs::read_to_string(path) {
+        Ok(script) => script,
+        Err(error) => {
+            eprintln!("Failed to read {}: {}", path, error);
+            process::exit(1);
+        }
+    };
+
+    for line in script.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        println!("> {}", line.trim());
+
+        if !run_line(session, line) {
+            break;
+        }
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let mut session = ReplSession::default();
+
+    match args.as_slice() {
+        [] => {}
+        [path] if !path.starts_with('-') => return run_script(&mut session, path),
+        _ => {
+            print_usage();
+            process::exit(2);
+        }
+    }
+
+    println!("CC Matching Engine REPL, type `help` for the commands");
+    let stdin = io::stdin();
+
+    loop {
+        print!("> ");
+        io::stdout().flush().unwrap();
+        let mut line = String::new();
+
+        match stdin.lock().read_line(&mut line) {
+            Ok(0) => break,
+            Ok(_) if run_line(&mut session, &line) => {}
+            Ok(_) => break,
+            Err(error) => {
+                eprintln!("Failed to read input: {}", error);
+                process::exit(1);
+            }
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use cc_matching_engine::replay::*;
+use std::env;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::process;
+
+fn print_usage() {
+    eprintln!("Usage: replay INPUT [--format csv|ndjson] [--output FILE] [--strict]");
+}
+
+fn main() {
+    let mut input_path = None;
+    let mut format = None;
+    let mut output_path = None;
+    let mut strict = false;
+    let mut args = env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => match args.next().as_deref().and_then(OrderLogFormat::from_name) {
+                Some(value) => format = Some(value),
+                None => {
+                    print_usage();
+                    process::exit(2);
+                }
+            },
+            "--output" => output_path = args.next(),
+            "--strict" => strict = true,
+            _ if input_path.is_none() && !arg.starts_with('-') => input_path = Some(arg),
+            _ => {
+                print_usage();
+                process::exit(2);
+            }
+        }
+    }
+
+    let input_path = match input_path {
+        Some(input_path) => input_path,
+        None => {
+            print_usage();
+            process::exit(2);
+        }
+    };
+    let format = match format.or_else(|| OrderLogFormat::from_path(Path::new(&input_path))) {
+        Some(format) => format,
+        None => {
+            eprintln!("Cannot tell the format of {}, pass --format", input_path);
+            process::exit(2);
+        }
+    };
+    let input = match File::open(&input_path) {
+        Ok(input) => input,
+        Err(error) => {
+            eprintln!("Failed to open {}: {}", input_path, error);
+            process::exit(1);
+        }
+    };
+    let output: Box<dyn Write> = match &output_path {
+        Some(output_path) => match File::create(output_path) {
+            Ok(output) => Box::new(BufWriter::new(output)),
+            Err(error) => {
+                eprintln!("Failed to create {}: {}", output_path, error);
+                process::exit(1);
+            }
+        },
+        None => Box::new(BufWriter::new(io::stdout())),
+    };
+
+    let mut replayer = Replayer {
+        strict,
+        ..Default::default()
+    };
+
+    match replayer.replay_log(input, format, output) {
+        Ok(summary) => {
+            for invalid_record in summary.invalid_records.iter() {
+                eprintln!("Skipped {}", invalid_record);
+            }
+
+            eprintln!(
+                "Replayed {} orders: {} rejected, {} trades, {} invalid records skipped",
+                summary.orders,
+                summary.rejected,
+                summary.trades,
+                summary.invalid_records.len()
+            );
+        }
+        Err(error) => {
+            eprintln!("Replay of {} failed: {}", input_path, error);
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use cc_matching_engine::rest::*;
+use std::env;
+use std::process;
+
+fn print_usage() {
+    eprintln!("Usage: rest_gateway [--listen ADDR] --symbol SYMBOL...");
+}
+
+fn main() {
+    let mut listen_addr = "127.0.0.1:8080".to_string();
+    let mut symbols = Vec::new();
+    let mut args = env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), args.next()) {
+            ("--listen", Some(value)) => listen_addr = value,
+            ("--symbol", Some(value)) => symbols.push(value),
+            _ => {
+                print_usage();
+                process::exit(2);
+            }
+        }
+    }
+
+    if symbols.is_empty() {
+        print_usage();
+        process::exit(2);
+    }
+
+    let server = match RestServer::bind(&listen_addr, &symbols) {
+        Ok(server) => server,
+        Err(error) => {
+            eprintln!("Failed to listen on {}: {}", listen_addr, error);
+            process::exit(1);
+        }
+    };
+
+    println!(
+        "HTTP gateway listening on http://{} for {}",
+        server.local_addr().unwrap(),
+        symbols.join(", ")
+    );
+
+    if let Err(error) = server.run() {
+        eprintln!("HTTP gateway stopped: {}", error);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,608 @@
+use crate::orderbooks::*;
+use std::collections::HashMap;
+use std::mem;
+use std::os::raw::c_void;
+use std::ptr;
+use std::slice;
+use uuid::Uuid;
+
+pub const CCME_OK: i32 = 0;
+pub const CCME_ERROR_NULL_POINTER: i32 = -1;
+pub const CCME_ERROR_INVALID_ENUM: i32 = -2;
+pub const CCME_ERROR_INVALID_SIZE: i32 = -3;
+
+pub const CCME_REJECTED_HALTED: i32 = 1;
+pub const CCME_REJECTED_UNKNOWN_ORDER: i32 = 2;
+pub const CCME_REJECTED_DUPLICATE_ORDER_ID: i32 = 3;
+pub const CCME_REJECTED_MISSING_SIDE: i32 = 4;
+pub const CCME_REJECTED_INVALID_VOLUME: i32 = 5;
+pub const CCME_REJECTED_INVALID_PRICE: i32 = 6;
+pub const CCME_REJECTED_MISSING_MAX_QUOTE: i32 = 7;
+pub const CCME_REJECTED_SIDE_MISMATCH: i32 = 8;
+pub const CCME_REJECTED_IN_AUCTION: i32 = 9;
+pub const CCME_REJECTED_PRE_OPEN: i32 = 10;
+pub const CCME_REJECTED_CLOSED: i32 = 11;
+pub const CCME_REJECTED_OUTSIDE_PRICE_BAND: i32 = 12;
+pub const CCME_REJECTED_PRICE_BAND_BREACH: i32 = 13;
+pub const CCME_REJECTED_INVALID_EXPIRY: i32 = 14;
+pub const CCME_REJECTED_DUPLICATE_CLIENT_ORDER_ID: i32 = 15;
+pub const CCME_REJECTED_INSUFFICIENT_FUNDS: i32 = 16;
+pub const CCME_REJECTED_OPENING_AUCTION: i32 = 17;
+pub const CCME_REJECTED_CLOSING_AUCTION: i32 = 18;
+pub const CCME_REJECTED_VOLATILITY_AUCTION: i32 = 19;
+pub const CCME_REJECTED_CONTINUOUS: i32 = 20;
+
+// `CcmeOrderMessage` up to and including `max_quote`, the fields every version has.
+pub const CCME_ORDER_MESSAGE_MIN_SIZE: usize = 80;
+
+pub const CCME_SIDE_NONE: u8 = 0;
+pub const CCME_SIDE_BID: u8 = 1;
+pub const CCME_SIDE_ASK: u8 = 2;
+
+pub const CCME_TYPE_CANCEL: u8 = 0;
+pub const CCME_TYPE_MARKET: u8 = 1;
+pub const CCME_TYPE_LIMIT: u8 = 2;
+pub const CCME_TYPE_REPLACE: u8 = 3;
+
+pub const CCME_TIF_GOOD_TILL_CANCEL: u8 = 0;
+pub const CCME_TIF_GOOD_TILL_DATE: u8 = 1;
+pub const CCME_TIF_DAY: u8 = 2;
+
+pub const CCME_EVENT_NO_MATCH: u8 = 0;
+pub const CCME_EVENT_HAS_MATCH: u8 = 1;
+pub const CCME_EVENT_OPEN: u8 = 2;
+pub const CCME_EVENT_CLOSED: u8 = 3;
+pub const CCME_EVENT_CANCELLED: u8 = 4;
+pub const CCME_EVENT_REPLACED: u8 = 5;
+pub const CCME_EVENT_EXPIRED: u8 = 6;
+pub const CCME_EVENT_MASS_CANCELLED: u8 = 7;
+pub const CCME_EVENT_REJECTED: u8 = 8;
+
+/// Opaque handle to one `Orderbooks`.
+pub struct CcmeOrderbooks {
+    orderbooks: Orderbooks,
+}
+
+/// Order ids are UUIDs in their 16-byte big-endian form, all zero means none.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct CcmeOrderId {
+    pub bytes: [u8; 16],
+}
+
+/// Mirrors `OrderMessage` without the client order ids. A zero volume, price,
+/// max quote, `expires_at` or session id means none, a zero id is replaced by
+/// a generated one and a zero `created_at` by now. `account` is only read when
+/// `has_account` is set, so account 0 can be given.
+///
+/// `struct_size` must be `sizeof(CcmeOrderMessage)`. New fields are only ever
+/// appended, those past the `struct_size` of a caller built against an older
+/// header read as zero.
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct CcmeOrderMessage {
+    pub struct_size: usize,
+    pub id: CcmeOrderId,
+    pub target_id: CcmeOrderId,
+    pub created_at: i64,
+    pub side: u8,
+    pub order_type: u8,
+    pub volume: u64,
+    pub price: u64,
+    pub max_quote: u64,
+    /// Fields from here on are past `CCME_ORDER_MESSAGE_MIN_SIZE`.
+    pub time_in_force: u8,
+    pub expires_at: i64,
+    pub account: u64,
+    pub session_id: u64,
+    pub has_account: bool,
+}
+
+/// Mirrors `OrderEvent`, tagged with the order it belongs to. A zero traded
+/// price or crossed id means none.
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct CcmeOrderEvent {
+    pub order_id: CcmeOrderId,
+    pub timestamp: i64,
+    pub event_type: u8,
+    pub has_remaining_volume: bool,
+    pub remaining_volume: u64,
+    pub traded_price: u64,
+    pub crossed_id: CcmeOrderId,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct CcmeDepthLevel {
+    pub price: u64,
+    pub volume: u64,
+}
+
+pub type CcmeEventCallback = Option<extern "C" fn(event: *const CcmeOrderEvent, user_data: *mut c_void)>;
+
+impl From<Uuid> for CcmeOrderId {
+    fn from(uuid: Uuid) -> CcmeOrderId {
+        CcmeOrderId {
+            bytes: *uuid.as_bytes(),
+        }
+    }
+}
+
+impl CcmeOrderId {
+    fn to_uuid(self) -> Option<Uuid> {
+        Some(Uuid::from_bytes(self.bytes)).filter(|uuid| !uuid.is_nil())
+    }
+}
+
+fn side_from_c(side: u8) -> Option<OrderSide> {
+    match side {
+        CCME_SIDE_NONE => Some(OrderSide::NoSide),
+        CCME_SIDE_BID => Some(OrderSide::Bid),
+        CCME_SIDE_ASK => Some(OrderSide::Ask),
+        _ => None,
+    }
+}
+
+fn type_from_c(order_type: u8) -> Option<OrderType> {
+    match order_type {
+        CCME_TYPE_CANCEL => Some(OrderType::Cancel),
+        CCME_TYPE_MARKET => Some(OrderType::Market),
+        CCME_TYPE_LIMIT => Some(OrderType::Limit),
+        CCME_TYPE_REPLACE => Some(OrderType::Replace),
+        _ => None,
+    }
+}
+
+fn time_in_force_from_c(time_in_force: u8) -> Option<TimeInForce> {
+    match time_in_force {
+        CCME_TIF_GOOD_TILL_CANCEL => Some(TimeInForce::GoodTillCancel),
+        CCME_TIF_GOOD_TILL_DATE => Some(TimeInForce::GoodTillDate),
+        CCME_TIF_DAY => Some(TimeInForce::Day),
+        _ => None,
+    }
+}
+
+fn rejection_code(rejection: OrderRejection) -> i32 {
+    match rejection {
+        OrderRejection::UnknownOrder => CCME_REJECTED_UNKNOWN_ORDER,
+        OrderRejection::DuplicateOrderId => CCME_REJECTED_DUPLICATE_ORDER_ID,
+        OrderRejection::MissingSide => CCME_REJECTED_MISSING_SIDE,
+        OrderRejection::InvalidVolume => CCME_REJECTED_INVALID_VOLUME,
+        OrderRejection::InvalidPrice => CCME_REJECTED_INVALID_PRICE,
+        OrderRejection::MissingMaxQuote => CCME_REJECTED_MISSING_MAX_QUOTE,
+        OrderRejection::SideMismatch => CCME_REJECTED_SIDE_MISMATCH,
+        OrderRejection::InAuction => CCME_REJECTED_IN_AUCTION,
+        // Unused while continuous trading accepts every order type.
+        OrderRejection::Session(SessionState::Continuous) => CCME_REJECTED_CONTINUOUS,
+        OrderRejection::Session(SessionState::PreOpen) => CCME_REJECTED_PRE_OPEN,
+        OrderRejection::Session(SessionState::OpeningAuction) => CCME_REJECTED_OPENING_AUCTION,
+        OrderRejection::Session(SessionState::ClosingAuction) => CCME_REJECTED_CLOSING_AUCTION,
+        OrderRejection::Session(SessionState::VolatilityAuction) => CCME_REJECTED_VOLATILITY_AUCTION,
+        OrderRejection::Session(SessionState::Halted) => CCME_REJECTED_HALTED,
+        OrderRejection::Session(SessionState::Closed) => CCME_REJECTED_CLOSED,
+        OrderRejection::OutsidePriceBand => CCME_REJECTED_OUTSIDE_PRICE_BAND,
+        OrderRejection::PriceBandBreach => CCME_REJECTED_PRICE_BAND_BREACH,
+        OrderRejection::InvalidExpiry => CCME_REJECTED_INVALID_EXPIRY,
+        OrderRejection::DuplicateClientOrderId => CCME_REJECTED_DUPLICATE_CLIENT_ORDER_ID,
+        OrderRejection::InsufficientFunds => CCME_REJECTED_INSUFFICIENT_FUNDS,
+    }
+}
+
+impl CcmeOrderMessage {
+    // Copies the `struct_size` bytes the caller has, the rest stays zero.
+    unsafe fn read(order: *const CcmeOrderMessage) -> Option<CcmeOrderMessage> {
+        let struct_size = (*order).struct_size;
+
+        if struct_size < CCME_ORDER_MESSAGE_MIN_SIZE {
+            return None;
+        }
+
+        let mut c_order: CcmeOrderMessage = mem::zeroed();
+        ptr::copy_nonoverlapping(
+            order as *const u8,
+            &mut c_order as *mut CcmeOrderMessage as *mut u8,
+            struct_size.min(mem::size_of::<CcmeOrderMessage>()),
+        );
+
+        Some(c_order)
+    }
+
+    fn to_order_message(self) -> Option<OrderMessage> {
+        let mut order_message = OrderMessage {
+            target_id: self.target_id.to_uuid(),
+            side: side_from_c(self.side)?,
+            r#type: type_from_c(self.order_type)?,
+            volume: Some(self.volume).filter(|volume| *volume > 0),
+            price: Some(self.price).filter(|price| *price > 0),
+            max_quote: Some(self.max_quote).filter(|max_quote| *max_quote > 0),
+            time_in_force: time_in_force_from_c(self.time_in_force)?,
+            expires_at: Some(self.expires_at).filter(|expires_at| *expires_at != 0),
+            account: Some(self.account).filter(|_| self.has_account),
+            session_id: Some(self.session_id).filter(|session_id| *session_id > 0),
+            ..Default::default()
+        };
+
+        if let Some(id) = self.id.to_uuid() {
+            order_message.id = id;
+        }
+
+        if self.created_at != 0 {
+            order_message.created_at = self.created_at;
+        }
+
+        Some(order_message)
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn ccme_orderbooks_new() -> *mut CcmeOrderbooks {
+    Box::into_raw(Box::new(CcmeOrderbooks {
+        orderbooks: Orderbooks::default(),
+    }))
+}
+
+/// # Safety
+///
+/// `book` must come from `ccme_orderbooks_new` and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn ccme_orderbooks_free(book: *mut CcmeOrderbooks) {
+    if !book.is_null() {
+        drop(Box::from_raw(book));
+    }
+}
+
+/// Validates and executes `order`, writing the id used back into it. Events
+/// are passed to `callback` one at a time, those of `order` first, and only
+/// live for the duration of the call. Returns `CCME_OK`, a `CCME_REJECTED_*`
+/// code or a `CCME_ERROR_*` code.
+///
+/// # Safety
+///
+/// `book` must come from `ccme_orderbooks_new` and `order` must point to a
+/// `CcmeOrderMessage` of at least `struct_size` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn ccme_orderbooks_submit(
+    book: *mut CcmeOrderbooks,
+    order: *mut CcmeOrderMessage,
+    callback: CcmeEventCallback,
+    user_data: *mut c_void,
+) -> i32 {
+    if book.is_null() || order.is_null() {
+        return CCME_ERROR_NULL_POINTER;
+    }
+
+    let c_order = match CcmeOrderMessage::read(order) {
+        Some(c_order) => c_order,
+        None => return CCME_ERROR_INVALID_SIZE,
+    };
+    let order_message = match c_order.to_order_message() {
+        Some(order_message) => order_message,
+        None => return CCME_ERROR_INVALID_ENUM,
+    };
+    (*order).id = order_message.id.into();
+
+    let order_events = match (*book).orderbooks.try_execute_order(&order_message) {
+        Ok(order_events) => order_events,
+        Err(rejection) => return rejection_code(rejection),
+    };
+
+    deliver_events(order_events, Some(order_message.id), callback, user_data);
+
+    CCME_OK
+}
+
+/// Expires the good till date orders due at or before `now` and passes their
+/// `CCME_EVENT_EXPIRED` events to `callback`. Returns how many expired.
+///
+/// # Safety
+///
+/// `book` must come from `ccme_orderbooks_new`.
+#[no_mangle]
+pub unsafe extern "C" fn ccme_orderbooks_expire_until(
+    book: *mut CcmeOrderbooks,
+    now: i64,
+    callback: CcmeEventCallback,
+    user_data: *mut c_void,
+) -> usize {
+    if book.is_null() {
+        return 0;
+    }
+
+    let order_events = (*book).orderbooks.expire_until(now);
+    let expired_count = order_events.len();
+    deliver_events(order_events, None, callback, user_data);
+
+    expired_count
+}
+
+/// Cancels the resting orders of `session_id` unless it opted out of cancel on
+/// disconnect, and passes their `CCME_EVENT_CANCELLED` events to `callback`.
+/// Returns how many were cancelled.
+///
+/// # Safety
+///
+/// `book` must come from `ccme_orderbooks_new`.
+#[no_mangle]
+pub unsafe extern "C" fn ccme_orderbooks_disconnect_session(
+    book: *mut CcmeOrderbooks,
+    session_id: u64,
+    callback: CcmeEventCallback,
+    user_data: *mut c_void,
+) -> usize {
+    if book.is_null() {
+        return 0;
+    }
+
+    let order_events = (*book).orderbooks.disconnect_session(session_id);
+    let cancelled_count = order_events.len();
+    deliver_events(order_events, None, callback, user_data);
+
+    cancelled_count
+}
+
+// Events of `first_id` come first, the rest in order id order.
+fn deliver_events(
+    order_events: HashMap<Uuid, Vec<OrderEvent>>,
+    first_id: Option<Uuid>,
+    callback: CcmeEventCallback,
+    user_data: *mut c_void,
+) {
+    let callback = match callback {
+        Some(callback) => callback,
+        None => return,
+    };
+    let mut order_events: Vec<_> = order_events.into_iter().collect();
+    order_events.sort_by_key(|(order_id, _)| (Some(*order_id) != first_id, *order_id));
+
+    for (order_id, events) in order_events {
+        for event in events {
+            let c_event = CcmeOrderEvent {
+                order_id: order_id.into(),
+                timestamp: event.timestamp,
+                event_type: event.r#type as u8,
+                has_remaining_volume: event.remaining_volume.is_some(),
+                remaining_volume: event.remaining_volume.unwrap_or(0),
+                traded_price: event.traded_price.unwrap_or(0),
+                crossed_id: event.crossed_id.map(CcmeOrderId::from).unwrap_or_default(),
+            };
+            callback(&c_event, user_data);
+        }
+    }
+}
+
+/// Copies up to `capacity` aggregated levels of one side, best first, and
+/// returns how many were written.
+///
+/// # Safety
+///
+/// `book` must come from `ccme_orderbooks_new` and `levels` must have room for
+/// `capacity` entries.
+#[no_mangle]
+pub unsafe extern "C" fn ccme_orderbooks_depth(
+    book: *const CcmeOrderbooks,
+    side: u8,
+    levels: *mut CcmeDepthLevel,
+    capacity: usize,
+) -> usize {
+    if book.is_null() || (levels.is_null() && capacity > 0) {
+        return 0;
+    }
+
+    let snapshot = (*book).orderbooks.depth_snapshot(capacity);
+    let side_levels = match side_from_c(side) {
+        Some(OrderSide::Bid) => snapshot.bids,
+        Some(OrderSide::Ask) => snapshot.asks,
+        _ => return 0,
+    };
+    let levels = if capacity > 0 {
+        slice::from_raw_parts_mut(levels, capacity)
+    } else {
+        &mut []
+    };
+
+    for (level, (price, volume)) in levels.iter_mut().zip(side_levels.iter()) {
+        *level = CcmeDepthLevel {
+            price: *price,
+            volume: *volume,
+        };
+    }
+
+    side_levels.len()
+}
+
+/// Returns the number of resting orders on one side, or on both for
+/// `CCME_SIDE_NONE`.
+///
+/// # Safety
+///
+/// `book` must come from `ccme_orderbooks_new`.
+#[no_mangle]
+pub unsafe extern "C" fn ccme_orderbooks_count(book: *const CcmeOrderbooks, side: u8) -> usize {
+    if book.is_null() {
+        return 0;
+    }
+
+    let (bids_count, asks_count, total_count) = (*book).orderbooks.count();
+
+    match side_from_c(side) {
+        Some(OrderSide::Bid) => bids_count,
+        Some(OrderSide::Ask) => asks_count,
+        Some(OrderSide::NoSide) => total_count,
+        None => 0,
+    }
+}
+
+#[cfg(test)]
+mod unit_test {
+    use super::*;
+    use std::ptr;
+
+    extern "C" fn collect_event(event: *const CcmeOrderEvent, user_data: *mut c_void) {
+        unsafe { (*(user_data as *mut Vec<CcmeOrderEvent>)).push(*event) };
+    }
+
+    #[test]
+    fn test_submit_through_the_c_abi() {
+        let book = ccme_orderbooks_new();
+        let mut events: Vec<CcmeOrderEvent> = Vec::new();
+        let user_data = &mut events as *mut Vec<CcmeOrderEvent> as *mut c_void;
+        let mut ask = CcmeOrderMessage {
+            struct_size: mem::size_of::<CcmeOrderMessage>(),
+            id: CcmeOrderId::default(),
+            target_id: CcmeOrderId::default(),
+            created_at: 0,
+            side: CCME_SIDE_ASK,
+            order_type: CCME_TYPE_LIMIT,
+            volume: 10,
+            price: 100,
+            max_quote: 0,
+            time_in_force: CCME_TIF_GOOD_TILL_CANCEL,
+            expires_at: 0,
+            account: 0,
+            session_id: 0,
+            has_account: false,
+        };
+        let mut bid = CcmeOrderMessage {
+            side: CCME_SIDE_BID,
+            volume: 4,
+            ..ask
+        };
+
+        unsafe {
+            assert_eq!(ccme_orderbooks_submit(book, &mut ask, None, ptr::null_mut()), CCME_OK);
+            assert_ne!(ask.id, CcmeOrderId::default());
+            assert_eq!(
+                ccme_orderbooks_submit(book, &mut bid, Some(collect_event), user_data),
+                CCME_OK
+            );
+            assert_eq!(events[0].order_id, bid.id);
+            assert_eq!(events[0].event_type, CCME_EVENT_HAS_MATCH);
+            assert_eq!(events[0].crossed_id, ask.id);
+            assert_eq!(events.last().unwrap().order_id, ask.id);
+            assert_eq!(events.last().unwrap().remaining_volume, 6);
+
+            let mut levels = [CcmeDepthLevel::default(); 4];
+
+            assert_eq!(ccme_orderbooks_depth(book, CCME_SIDE_ASK, levels.as_mut_ptr(), 4), 1);
+            assert_eq!((levels[0].price, levels[0].volume), (100, 6));
+            assert_eq!(ccme_orderbooks_count(book, CCME_SIDE_NONE), 1);
+            assert_eq!(
+                ccme_orderbooks_submit(book, &mut ask, None, ptr::null_mut()),
+                CCME_REJECTED_DUPLICATE_ORDER_ID
+            );
+
+            ask.side = 9;
+
+            assert_eq!(
+                ccme_orderbooks_submit(book, &mut ask, None, ptr::null_mut()),
+                CCME_ERROR_INVALID_ENUM
+            );
+            assert_eq!(
+                ccme_orderbooks_submit(ptr::null_mut(), &mut ask, None, ptr::null_mut()),
+                CCME_ERROR_NULL_POINTER
+            );
+
+            ask.side = CCME_SIDE_ASK;
+            ask.struct_size = CCME_ORDER_MESSAGE_MIN_SIZE - 1;
+
+            assert_eq!(
+                ccme_orderbooks_submit(book, &mut ask, None, ptr::null_mut()),
+                CCME_ERROR_INVALID_SIZE
+            );
+            ccme_orderbooks_free(book);
+        }
+    }
+
+    #[test]
+    fn test_fields_past_struct_size_read_as_zero() {
+        let book = ccme_orderbooks_new();
+        let mut ask = CcmeOrderMessage {
+            struct_size: CCME_ORDER_MESSAGE_MIN_SIZE,
+            id: CcmeOrderId::default(),
+            target_id: CcmeOrderId::default(),
+            created_at: 0,
+            side: CCME_SIDE_ASK,
+            order_type: CCME_TYPE_LIMIT,
+            volume: 10,
+            price: 100,
+            max_quote: 0,
+            time_in_force: 42,
+            expires_at: -1,
+            account: 7,
+            session_id: 7,
+            has_account: true,
+        };
+
+        assert_eq!(
+            mem::offset_of!(CcmeOrderMessage, max_quote) + mem::size_of::<u64>(),
+            CCME_ORDER_MESSAGE_MIN_SIZE
+        );
+
+        unsafe {
+            assert_eq!(ccme_orderbooks_submit(book, &mut ask, None, ptr::null_mut()), CCME_OK);
+
+            let order_message = CcmeOrderMessage::read(&ask).unwrap().to_order_message().unwrap();
+
+            assert_eq!(order_message.time_in_force, TimeInForce::GoodTillCancel);
+            assert_eq!((order_message.expires_at, order_message.account), (None, None));
+            assert_eq!(order_message.session_id, None);
+
+            ask.struct_size = mem::size_of::<CcmeOrderMessage>();
+            ask.time_in_force = CCME_TIF_GOOD_TILL_CANCEL;
+            ask.account = 0;
+
+            let order_message = CcmeOrderMessage::read(&ask).unwrap().to_order_message().unwrap();
+
+            assert_eq!((order_message.account, order_message.session_id), (Some(0), Some(7)));
+            ccme_orderbooks_free(book);
+        }
+    }
+
+    #[test]
+    fn test_every_rejection_has_its_own_code() {
+        let rejections = [
+            (OrderRejection::Session(SessionState::Halted), CCME_REJECTED_HALTED),
+            (OrderRejection::UnknownOrder, CCME_REJECTED_UNKNOWN_ORDER),
+            (OrderRejection::DuplicateOrderId, CCME_REJECTED_DUPLICATE_ORDER_ID),
+            (OrderRejection::MissingSide, CCME_REJECTED_MISSING_SIDE),
+            (OrderRejection::InvalidVolume, CCME_REJECTED_INVALID_VOLUME),
+            (OrderRejection::InvalidPrice, CCME_REJECTED_INVALID_PRICE),
+            (OrderRejection::MissingMaxQuote, CCME_REJECTED_MISSING_MAX_QUOTE),
+            (OrderRejection::SideMismatch, CCME_REJECTED_SIDE_MISMATCH),
+            (OrderRejection::InAuction, CCME_REJECTED_IN_AUCTION),
+            (OrderRejection::Session(SessionState::PreOpen), CCME_REJECTED_PRE_OPEN),
+            (OrderRejection::Session(SessionState::Closed), CCME_REJECTED_CLOSED),
+            (OrderRejection::OutsidePriceBand, CCME_REJECTED_OUTSIDE_PRICE_BAND),
+            (OrderRejection::PriceBandBreach, CCME_REJECTED_PRICE_BAND_BREACH),
+            (OrderRejection::InvalidExpiry, CCME_REJECTED_INVALID_EXPIRY),
+            (
+                OrderRejection::DuplicateClientOrderId,
+                CCME_REJECTED_DUPLICATE_CLIENT_ORDER_ID,
+            ),
+            (OrderRejection::InsufficientFunds, CCME_REJECTED_INSUFFICIENT_FUNDS),
+            (
+                OrderRejection::Session(SessionState::OpeningAuction),
+                CCME_REJECTED_OPENING_AUCTION,
+            ),
+            (
+                OrderRejection::Session(SessionState::ClosingAuction),
+                CCME_REJECTED_CLOSING_AUCTION,
+            ),
+            (
+                OrderRejection::Session(SessionState::VolatilityAuction),
+                CCME_REJECTED_VOLATILITY_AUCTION,
+            ),
+            (
+                OrderRejection::Session(SessionState::Continuous),
+                CCME_REJECTED_CONTINUOUS,
+            ),
+        ];
+
+        // Codes are never reused, the list runs in code order.
+        for (index, (rejection, code)) in rejections.iter().enumerate() {
+            assert_eq!(rejection_code(*rejection), *code, "{:?}", rejection);
+            assert_eq!(*code, index as i32 + 1);
+        }
+    }
+}
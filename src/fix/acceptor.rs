@@ -0,0 +1,172 @@
+use super::gateway::FixGateway;
+use super::message::*;
+use super::session::*;
+use super::tags;
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+pub struct FixAcceptor {
+    listener: TcpListener,
+    config: FixSessionConfig,
+    gateway: Arc<Mutex<FixGateway>>,
+}
+
+struct FixConnection {
+    stream: TcpStream,
+    session: FixSession,
+    gateway: Arc<Mutex<FixGateway>>,
+    outbox: Sender<FixMessage>,
+    inbox: Receiver<FixMessage>,
+    comp_id: Option<String>,
+}
+
+impl FixAcceptor {
+    pub fn bind<A: ToSocketAddrs>(addr: A, config: FixSessionConfig, symbols: &[String]) -> io::Result<FixAcceptor> {
+        Ok(FixAcceptor {
+            listener: TcpListener::bind(addr)?,
+            config,
+            gateway: Arc::new(Mutex::new(FixGateway::new(symbols))),
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    pub fn gateway(&self) -> Arc<Mutex<FixGateway>> {
+        self.gateway.clone()
+    }
+
+    pub fn run(&self) -> io::Result<()> {
+        for stream in self.listener.incoming() {
+            let stream = stream?;
+            let config = self.config.clone();
+            let gateway = self.gateway.clone();
+
+            thread::spawn(move || {
+                let peer_addr = stream.peer_addr();
+
+                if let Err(error) =
+                    FixConnection::new(stream, &config, gateway).and_then(|mut connection| connection.run())
+                {
+                    eprintln!("FIX connection {:?} closed: {}", peer_addr, error);
+                }
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl FixConnection {
+    fn new(stream: TcpStream, config: &FixSessionConfig, gateway: Arc<Mutex<FixGateway>>) -> io::Result<FixConnection> {
+        let (outbox, inbox) = mpsc::channel();
+        stream.set_read_timeout(Some(POLL_INTERVAL))?;
+        stream.set_nodelay(true)?;
+
+        Ok(FixConnection {
+            stream,
+            session: FixSession::new(config, Instant::now()),
+            gateway,
+            outbox,
+            inbox,
+            comp_id: None,
+        })
+    }
+
+    fn run(&mut self) -> io::Result<()> {
+        let result = self.poll_loop();
+
+        if let Some(comp_id) = self.comp_id.take() {
+            self.gateway.lock().unwrap().unregister_session(&comp_id);
+        }
+
+        result
+    }
+
+    fn poll_loop(&mut self) -> io::Result<()> {
+        let mut buffer = Vec::new();
+        let mut read_buffer = [0u8; 4096];
+
+        while !self.session.disconnected {
+            match self.stream.read(&mut read_buffer) {
+                Ok(0) => return Ok(()),
+                Ok(read_length) => buffer.extend_from_slice(&read_buffer[..read_length]),
+                Err(error) if error.kind() == ErrorKind::WouldBlock || error.kind() == ErrorKind::TimedOut => {}
+                Err(error) => return Err(error),
+            }
+
+            let now = Instant::now();
+
+            while let Some(message) = take_message(&mut buffer) {
+                // Garbled messages are dropped, the sequence gap they leave is recovered by a resend.
+                if let Ok(message) = message {
+                    self.on_message(message, now);
+                }
+
+                if self.session.disconnected {
+                    break;
+                }
+            }
+
+            while let Ok(message) = self.inbox.try_recv() {
+                self.session.send(message, now);
+            }
+
+            self.session.on_timer(now);
+            self.stream.write_all(&self.session.drain_outbound())?;
+        }
+
+        Ok(())
+    }
+
+    fn on_message(&mut self, message: FixMessage, now: Instant) {
+        let mut cancel_on_disconnect = None;
+
+        if !self.session.logged_on && message.msg_type() == tags::MSG_TYPE_LOGON {
+            let comp_id = message.get(tags::SENDER_COMP_ID).unwrap_or("").to_string();
+
+            // Only one connection per counterparty, a second logon is dropped without a reply.
+            if !self
+                .gateway
+                .lock()
+                .unwrap()
+                .register_session(&comp_id, self.outbox.clone())
+            {
+                self.session.disconnected = true;
+                return;
+            }
+
+            self.comp_id = Some(comp_id);
+            cancel_on_disconnect = Some(message.get(tags::CANCEL_ORDERS_ON_DISCONNECT) != Some("N"));
+        }
+
+        let application_messages = self.session.on_message(message, now);
+
+        if !self.session.logged_on {
+            if let Some(comp_id) = self.comp_id.take() {
+                self.gateway.lock().unwrap().unregister_session(&comp_id);
+            }
+
+            return;
+        }
+
+        if let Some(comp_id) = self.comp_id.as_ref() {
+            let mut gateway = self.gateway.lock().unwrap();
+
+            if let Some(enabled) = cancel_on_disconnect {
+                gateway.set_cancel_on_disconnect(comp_id, enabled);
+            }
+
+            for message in application_messages.iter() {
+                gateway.handle(comp_id, message);
+            }
+        }
+    }
+}
@@ -0,0 +1,593 @@
+use super::message::*;
+use super::tags;
+use crate::orderbooks::*;
+use crate::risk::*;
+use chrono::prelude::*;
+use std::collections::HashMap;
+use std::sync::mpsc::Sender;
+
+pub const ORD_REJ_REASON_UNKNOWN_SYMBOL: u32 = 1;
+pub const ORD_REJ_REASON_EXCHANGE_CLOSED: u32 = 2;
+pub const ORD_REJ_REASON_ORDER_EXCEEDS_LIMIT: u32 = 3;
+pub const ORD_REJ_REASON_UNKNOWN_ORDER: u32 = 5;
+pub const ORD_REJ_REASON_DUPLICATE_ORDER: u32 = 6;
+pub const ORD_REJ_REASON_UNSUPPORTED_ORDER: u32 = 11;
+pub const ORD_REJ_REASON_INCORRECT_QUANTITY: u32 = 13;
+pub const ORD_REJ_REASON_OTHER: u32 = 99;
+pub const CXL_REJ_REASON_TOO_LATE_TO_CANCEL: u32 = 0;
+pub const CXL_REJ_REASON_UNKNOWN_ORDER: u32 = 1;
+pub const CXL_REJ_REASON_DUPLICATE_CL_ORD_ID: u32 = 6;
+pub const CXL_REJ_REASON_OTHER: u32 = 99;
+
+const EXEC_TYPE_NEW: char = '0';
+const EXEC_TYPE_CANCELED: char = '4';
+const EXEC_TYPE_REPLACED: char = '5';
+const EXEC_TYPE_REJECTED: char = '8';
+const EXEC_TYPE_TRADE: char = 'F';
+const ORD_STATUS_NEW: char = '0';
+const ORD_STATUS_PARTIALLY_FILLED: char = '1';
+const ORD_STATUS_FILLED: char = '2';
+const ORD_STATUS_CANCELED: char = '4';
+const ORD_STATUS_REJECTED: char = '8';
+const CXL_REJ_RESPONSE_TO_CANCEL: char = '1';
+const CXL_REJ_RESPONSE_TO_REPLACE: char = '2';
+
+// Order as seen by the FIX client. The engine hands out a fresh id on every
+// replace, while OrderID stays the one assigned on entry.
+#[derive(Clone, Debug)]
+pub struct FixOrder {
+    pub session: String,
+    pub order_id: OrderId,
+    pub cl_ord_id: String,
+    pub account: Option<AccountId>,
+    pub symbol: String,
+    pub side: OrderSide,
+    pub ord_type: OrderType,
+    pub price: Option<OrderPrice>,
+    pub order_qty: OrderVolume,
+    pub cum_qty: OrderVolume,
+    pub cum_quote: OrderQuote,
+}
+
+// Every order goes through the instrument's risk engine before the book.
+#[derive(Debug, Default)]
+pub struct FixGateway {
+    pub instruments: HashMap<String, Orderbooks>,
+    pub risk_engines: HashMap<String, RiskEngine>,
+    pub orders: HashMap<OrderId, FixOrder>,
+    pub cl_ord_ids: HashMap<(String, String), OrderId>,
+    sessions: HashMap<String, Sender<FixMessage>>,
+    session_ids: HashMap<String, SessionId>,
+    last_exec_id: u64,
+}
+
+fn fix_side(side: OrderSide) -> &'static str {
+    match side {
+        OrderSide::Bid => "1",
+        OrderSide::Ask => "2",
+        OrderSide::NoSide => "",
+    }
+}
+
+fn order_side(message: &FixMessage) -> Option<OrderSide> {
+    match message.get(tags::SIDE) {
+        Some("1") => Some(OrderSide::Bid),
+        Some("2") => Some(OrderSide::Ask),
+        _ => None,
+    }
+}
+
+fn ord_rej_reason(rejection: &PreTradeRejection) -> u32 {
+    match rejection {
+        PreTradeRejection::Risk(_) => ORD_REJ_REASON_ORDER_EXCEEDS_LIMIT,
+        PreTradeRejection::Order(OrderRejection::Session(SessionState::Halted))
+        | PreTradeRejection::Order(OrderRejection::Session(SessionState::Closed)) => ORD_REJ_REASON_EXCHANGE_CLOSED,
+        PreTradeRejection::Order(OrderRejection::UnknownOrder) => ORD_REJ_REASON_UNKNOWN_ORDER,
+        PreTradeRejection::Order(OrderRejection::DuplicateOrderId)
+        | PreTradeRejection::Order(OrderRejection::DuplicateClientOrderId) => ORD_REJ_REASON_DUPLICATE_ORDER,
+        PreTradeRejection::Order(OrderRejection::InvalidVolume) => ORD_REJ_REASON_INCORRECT_QUANTITY,
+        PreTradeRejection::Order(_) => ORD_REJ_REASON_OTHER,
+    }
+}
+
+fn transact_time(timestamp: UTCNanoSeconds) -> String {
+    Utc.timestamp_nanos(timestamp).format("%Y%m%d-%H:%M:%S%.3f").to_string()
+}
+
+impl FixOrder {
+    pub fn leaves_qty(&self) -> OrderVolume {
+        self.order_qty - self.cum_qty
+    }
+
+    pub fn ord_status(&self) -> char {
+        if self.cum_qty == 0 {
+            ORD_STATUS_NEW
+        } else if self.cum_qty < self.order_qty {
+            ORD_STATUS_PARTIALLY_FILLED
+        } else {
+            ORD_STATUS_FILLED
+        }
+    }
+
+    pub fn execution_report(&self, exec_id: String, exec_type: char, ord_status: char) -> FixMessage {
+        let mut report = FixMessage::new(tags::MSG_TYPE_EXECUTION_REPORT);
+        report
+            .set(tags::ORDER_ID, self.order_id)
+            .set(tags::CL_ORD_ID, &self.cl_ord_id)
+            .set(tags::EXEC_ID, exec_id)
+            .set(tags::EXEC_TYPE, exec_type)
+            .set(tags::ORD_STATUS, ord_status)
+            .set(tags::SYMBOL, &self.symbol)
+            .set(tags::SIDE, fix_side(self.side))
+            .set(tags::ORDER_QTY, self.order_qty)
+            .set(
+                tags::ORD_TYPE,
+                if self.ord_type == OrderType::Market { "1" } else { "2" },
+            );
+
+        if let Some(price) = self.price {
+            report.set(tags::PRICE, price);
+        }
+
+        if let Some(account) = self.account {
+            report.set(tags::ACCOUNT, account);
+        }
+
+        let leaves_qty = if ord_status == ORD_STATUS_CANCELED {
+            0
+        } else {
+            self.leaves_qty()
+        };
+        report
+            .set(tags::LEAVES_QTY, leaves_qty)
+            .set(tags::CUM_QTY, self.cum_qty)
+            .set(tags::AVG_PX, self.cum_quote.checked_div(self.cum_qty).unwrap_or(0));
+        report
+    }
+}
+
+impl FixGateway {
+    pub fn new(symbols: &[String]) -> FixGateway {
+        FixGateway {
+            instruments: symbols
+                .iter()
+                .map(|symbol| (symbol.clone(), Orderbooks::default()))
+                .collect(),
+            risk_engines: symbols
+                .iter()
+                .map(|symbol| (symbol.clone(), RiskEngine::default()))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    pub fn register_session(&mut self, comp_id: &str, outbox: Sender<FixMessage>) -> bool {
+        if self.sessions.contains_key(comp_id) {
+            return false;
+        }
+
+        self.sessions.insert(comp_id.to_string(), outbox);
+        true
+    }
+
+    // Cancels the session's resting orders unless it opted out. Nobody is left
+    // to report the cancels to. ClOrdIDs of closed orders are forgotten, so
+    // they only need to be unique for as long as the connection lasts.
+    pub fn unregister_session(&mut self, comp_id: &str) {
+        self.sessions.remove(comp_id);
+        let session_id = self.session_id(comp_id);
+
+        for orderbooks in self.instruments.values_mut() {
+            for order_id in orderbooks.disconnect_session(session_id).keys() {
+                self.orders.remove(order_id);
+            }
+        }
+
+        let orders = &self.orders;
+        self.cl_ord_ids
+            .retain(|(session, _), order_id| session != comp_id || orders.contains_key(order_id));
+    }
+
+    pub fn set_cancel_on_disconnect(&mut self, comp_id: &str, enabled: bool) {
+        let session_id = self.session_id(comp_id);
+
+        for orderbooks in self.instruments.values_mut() {
+            orderbooks.set_cancel_on_disconnect(session_id, enabled);
+        }
+    }
+
+    // CompIDs keep their engine session across reconnects.
+    fn session_id(&mut self, comp_id: &str) -> SessionId {
+        let next_session_id = self.session_ids.len() as SessionId + 1;
+
+        *self.session_ids.entry(comp_id.to_string()).or_insert(next_session_id)
+    }
+
+    fn next_exec_id(&mut self) -> String {
+        self.last_exec_id += 1;
+        self.last_exec_id.to_string()
+    }
+
+    fn send(&self, comp_id: &str, message: FixMessage) {
+        // Reports for a disconnected session are dropped.
+        if let Some(outbox) = self.sessions.get(comp_id) {
+            let _ = outbox.send(message);
+        }
+    }
+
+    fn report(&mut self, order: &FixOrder, exec_type: char, ord_status: char, timestamp: UTCNanoSeconds) {
+        let exec_id = self.next_exec_id();
+        let mut report = order.execution_report(exec_id, exec_type, ord_status);
+        report.set(tags::TRANSACT_TIME, transact_time(timestamp));
+        self.send(&order.session, report);
+    }
+
+    pub fn handle(&mut self, comp_id: &str, message: &FixMessage) {
+        match message.msg_type() {
+            tags::MSG_TYPE_NEW_ORDER_SINGLE => self.new_order_single(comp_id, message),
+            tags::MSG_TYPE_ORDER_CANCEL_REQUEST => self.cancel_order(comp_id, message),
+            tags::MSG_TYPE_ORDER_CANCEL_REPLACE_REQUEST => self.replace_order(comp_id, message),
+            msg_type => {
+                let mut reject = FixMessage::new(tags::MSG_TYPE_REJECT);
+                reject
+                    .set(tags::REF_SEQ_NUM, message.seq_num().unwrap_or(0))
+                    .set(tags::REF_MSG_TYPE, msg_type)
+                    .set(tags::SESSION_REJECT_REASON, 11)
+                    .set(tags::TEXT, "Invalid MsgType");
+                self.send(comp_id, reject);
+            }
+        }
+    }
+
+    fn reject_order(&mut self, comp_id: &str, message: &FixMessage, ord_rej_reason: u32, text: &str) {
+        let exec_id = self.next_exec_id();
+        let mut report = FixMessage::new(tags::MSG_TYPE_EXECUTION_REPORT);
+        report
+            .set(tags::ORDER_ID, "NONE")
+            .set(tags::CL_ORD_ID, message.get(tags::CL_ORD_ID).unwrap_or(""))
+            .set(tags::EXEC_ID, exec_id)
+            .set(tags::EXEC_TYPE, EXEC_TYPE_REJECTED)
+            .set(tags::ORD_STATUS, ORD_STATUS_REJECTED)
+            .set(tags::SYMBOL, message.get(tags::SYMBOL).unwrap_or(""))
+            .set(tags::SIDE, message.get(tags::SIDE).unwrap_or(""))
+            .set(tags::LEAVES_QTY, 0)
+            .set(tags::CUM_QTY, 0)
+            .set(tags::AVG_PX, 0)
+            .set(tags::ORD_REJ_REASON, ord_rej_reason)
+            .set(tags::TEXT, text);
+        self.send(comp_id, report);
+    }
+
+    fn reject_cancel(
+        &mut self,
+        comp_id: &str,
+        message: &FixMessage,
+        response_to: char,
+        cxl_rej_reason: u32,
+        text: &str,
+    ) {
+        let known_order = self
+            .cl_ord_ids
+            .get(&(
+                comp_id.to_string(),
+                message.get(tags::ORIG_CL_ORD_ID).unwrap_or("").to_string(),
+            ))
+            .and_then(|order_id| self.orders.get(order_id));
+        let (order_id, ord_status) = match known_order {
+            Some(order) => (order.order_id.to_string(), order.ord_status()),
+            None => ("NONE".to_string(), ORD_STATUS_REJECTED),
+        };
+        let mut reject = FixMessage::new(tags::MSG_TYPE_ORDER_CANCEL_REJECT);
+        reject
+            .set(tags::ORDER_ID, order_id)
+            .set(tags::CL_ORD_ID, message.get(tags::CL_ORD_ID).unwrap_or(""))
+            .set(tags::ORIG_CL_ORD_ID, message.get(tags::ORIG_CL_ORD_ID).unwrap_or(""))
+            .set(tags::ORD_STATUS, ord_status)
+            .set(tags::CXL_REJ_RESPONSE_TO, response_to)
+            .set(tags::CXL_REJ_REASON, cxl_rej_reason)
+            .set(tags::TEXT, text);
+        self.send(comp_id, reject);
+    }
+
+    fn new_order_single(&mut self, comp_id: &str, message: &FixMessage) {
+        let cl_ord_id = message.get(tags::CL_ORD_ID).unwrap_or("").to_string();
+        let symbol = message.get(tags::SYMBOL).unwrap_or("").to_string();
+        let order_qty = message.parse::<OrderVolume>(tags::ORDER_QTY).unwrap_or(0);
+        let price = message.parse::<OrderPrice>(tags::PRICE);
+        let account = message.parse::<AccountId>(tags::ACCOUNT);
+        let session_cl_ord_id = (comp_id.to_string(), cl_ord_id.clone());
+        let session_id = Some(self.session_id(comp_id));
+
+        if cl_ord_id.is_empty() {
+            return self.reject_order(comp_id, message, ORD_REJ_REASON_OTHER, "ClOrdID missing");
+        }
+
+        if self.cl_ord_ids.contains_key(&session_cl_ord_id) {
+            return self.reject_order(comp_id, message, ORD_REJ_REASON_DUPLICATE_ORDER, "Duplicate ClOrdID");
+        }
+
+        if !self.instruments.contains_key(&symbol) {
+            return self.reject_order(comp_id, message, ORD_REJ_REASON_UNKNOWN_SYMBOL, "Unknown symbol");
+        }
+
+        let side = match order_side(message) {
+            Some(side) => side,
+            None => return self.reject_order(comp_id, message, ORD_REJ_REASON_UNSUPPORTED_ORDER, "Unsupported side"),
+        };
+
+        if order_qty == 0 {
+            return self.reject_order(comp_id, message, ORD_REJ_REASON_INCORRECT_QUANTITY, "Invalid OrderQty");
+        }
+
+        let max_quote = message.parse::<OrderQuote>(tags::CASH_ORDER_QTY);
+        let order_message = match (message.get(tags::ORD_TYPE), price) {
+            (Some("1"), _) if side == OrderSide::Bid && max_quote.is_none() => {
+                return self.reject_order(
+                    comp_id,
+                    message,
+                    ORD_REJ_REASON_OTHER,
+                    "Market buy requires CashOrderQty",
+                );
+            }
+            (Some("1"), _) => OrderMessage {
+                side,
+                r#type: OrderType::Market,
+                volume: Some(order_qty),
+                max_quote,
+                account,
+                session_id,
+                ..Default::default()
+            },
+            (Some("2"), Some(price)) if price > 0 => OrderMessage {
+                side,
+                r#type: OrderType::Limit,
+                volume: Some(order_qty),
+                price: Some(price),
+                account,
+                session_id,
+                ..Default::default()
+            },
+            (Some("2"), _) => {
+                return self.reject_order(comp_id, message, ORD_REJ_REASON_OTHER, "Limit order requires a Price");
+            }
+            _ => {
+                return self.reject_order(
+                    comp_id,
+                    message,
+                    ORD_REJ_REASON_UNSUPPORTED_ORDER,
+                    "Unsupported OrdType",
+                )
+            }
+        };
+        let order = FixOrder {
+            session: comp_id.to_string(),
+            order_id: order_message.id,
+            cl_ord_id,
+            account,
+            symbol,
+            side,
+            ord_type: order_message.r#type,
+            price: order_message.price,
+            order_qty,
+            cum_qty: 0,
+            cum_quote: 0,
+        };
+
+        match self.execute(order, &order_message, EXEC_TYPE_NEW, None) {
+            Ok(()) => {
+                self.cl_ord_ids.insert(session_cl_ord_id, order_message.id);
+            }
+            Err(rejection) => self.reject_order(comp_id, message, ord_rej_reason(&rejection), &rejection.to_string()),
+        }
+    }
+
+    fn cancel_order(&mut self, comp_id: &str, message: &FixMessage) {
+        let engine_id = match self.validate_cancel(comp_id, message, CXL_REJ_RESPONSE_TO_CANCEL) {
+            Some(engine_id) => engine_id,
+            None => return,
+        };
+        let cancel_message = OrderMessage {
+            target_id: Some(engine_id),
+            r#type: OrderType::Cancel,
+            account: self.orders[&engine_id].account,
+            session_id: Some(self.session_id(comp_id)),
+            ..Default::default()
+        };
+        let symbol = self.orders[&engine_id].symbol.clone();
+        let orderbooks = self.instruments.get_mut(&symbol).unwrap();
+        let risk = self.risk_engines.get_mut(&symbol).unwrap();
+
+        if let Err(rejection) = risk.try_execute_order(orderbooks, &cancel_message) {
+            let text = rejection.to_string();
+            return self.reject_cancel(
+                comp_id,
+                message,
+                CXL_REJ_RESPONSE_TO_CANCEL,
+                CXL_REJ_REASON_OTHER,
+                &text,
+            );
+        }
+
+        let timestamp = orderbooks.now();
+        let mut order = self.orders.remove(&engine_id).unwrap();
+        let cl_ord_id = message.get(tags::CL_ORD_ID).unwrap().to_string();
+        self.cl_ord_ids
+            .insert((comp_id.to_string(), cl_ord_id.clone()), engine_id);
+        let orig_cl_ord_id = order.cl_ord_id.clone();
+        order.cl_ord_id = cl_ord_id;
+        let exec_id = self.next_exec_id();
+        let mut report = order.execution_report(exec_id, EXEC_TYPE_CANCELED, ORD_STATUS_CANCELED);
+        report
+            .set(tags::ORIG_CL_ORD_ID, orig_cl_ord_id)
+            .set(tags::TRANSACT_TIME, transact_time(timestamp));
+        self.send(comp_id, report);
+    }
+
+    fn replace_order(&mut self, comp_id: &str, message: &FixMessage) {
+        let engine_id = match self.validate_cancel(comp_id, message, CXL_REJ_RESPONSE_TO_REPLACE) {
+            Some(engine_id) => engine_id,
+            None => return,
+        };
+        let order = self.orders.get(&engine_id).unwrap();
+        let order_qty = message.parse::<OrderVolume>(tags::ORDER_QTY).unwrap_or(0);
+        let price = message.parse::<OrderPrice>(tags::PRICE).unwrap_or(0);
+        let text = if order_side(message) != Some(order.side) {
+            "Side cannot be changed"
+        } else if message.get(tags::ORD_TYPE) != Some("2") || price == 0 {
+            "Only limit orders with a Price can be replaced"
+        } else if order_qty <= order.cum_qty {
+            "OrderQty must exceed CumQty"
+        } else {
+            ""
+        };
+
+        if !text.is_empty() {
+            return self.reject_cancel(
+                comp_id,
+                message,
+                CXL_REJ_RESPONSE_TO_REPLACE,
+                CXL_REJ_REASON_OTHER,
+                text,
+            );
+        }
+
+        let mut order = order.clone();
+        let session_id = Some(self.session_id(comp_id));
+        let replace_message = OrderMessage {
+            target_id: Some(engine_id),
+            side: order.side,
+            r#type: OrderType::Replace,
+            volume: Some(order_qty - order.cum_qty),
+            price: Some(price),
+            account: order.account,
+            session_id,
+            ..Default::default()
+        };
+        let cl_ord_id = message.get(tags::CL_ORD_ID).unwrap().to_string();
+        let orig_cl_ord_id = order.cl_ord_id.clone();
+        order.cl_ord_id = cl_ord_id.clone();
+        order.order_qty = order_qty;
+        order.price = Some(price);
+
+        match self.execute(order, &replace_message, EXEC_TYPE_REPLACED, Some(orig_cl_ord_id)) {
+            Ok(()) => {
+                self.orders.remove(&engine_id);
+                self.cl_ord_ids
+                    .insert((comp_id.to_string(), cl_ord_id), replace_message.id);
+            }
+            Err(rejection) => {
+                let text = rejection.to_string();
+                self.reject_cancel(
+                    comp_id,
+                    message,
+                    CXL_REJ_RESPONSE_TO_REPLACE,
+                    CXL_REJ_REASON_OTHER,
+                    &text,
+                );
+            }
+        }
+    }
+
+    // Shared checks for cancel and cancel/replace, so the engine never sees an unknown target.
+    fn validate_cancel(&mut self, comp_id: &str, message: &FixMessage, response_to: char) -> Option<OrderId> {
+        let cl_ord_id = message.get(tags::CL_ORD_ID).unwrap_or("");
+        let orig_cl_ord_id = message.get(tags::ORIG_CL_ORD_ID).unwrap_or("");
+
+        if cl_ord_id.is_empty() {
+            self.reject_cancel(comp_id, message, response_to, CXL_REJ_REASON_OTHER, "ClOrdID missing");
+            return None;
+        }
+
+        if self
+            .cl_ord_ids
+            .contains_key(&(comp_id.to_string(), cl_ord_id.to_string()))
+        {
+            let reason = CXL_REJ_REASON_DUPLICATE_CL_ORD_ID;
+            self.reject_cancel(comp_id, message, response_to, reason, "Duplicate ClOrdID");
+            return None;
+        }
+
+        let engine_id = match self.cl_ord_ids.get(&(comp_id.to_string(), orig_cl_ord_id.to_string())) {
+            Some(engine_id) => *engine_id,
+            None => {
+                self.reject_cancel(
+                    comp_id,
+                    message,
+                    response_to,
+                    CXL_REJ_REASON_UNKNOWN_ORDER,
+                    "Unknown order",
+                );
+                return None;
+            }
+        };
+        let resting = self
+            .orders
+            .get(&engine_id)
+            .is_some_and(|order| self.instruments[&order.symbol].contains(&engine_id));
+
+        if !resting {
+            let reason = CXL_REJ_REASON_TOO_LATE_TO_CANCEL;
+            self.reject_cancel(comp_id, message, response_to, reason, "Order is no longer open");
+            return None;
+        }
+
+        Some(engine_id)
+    }
+
+    // Nothing is reported when the order is rejected, the caller answers with
+    // the reject message that fits the request.
+    fn execute(
+        &mut self,
+        order: FixOrder,
+        order_message: &OrderMessage,
+        exec_type: char,
+        orig_cl_ord_id: Option<String>,
+    ) -> Result<(), PreTradeRejection> {
+        let orderbooks = self.instruments.get_mut(&order.symbol).unwrap();
+        let risk = self.risk_engines.get_mut(&order.symbol).unwrap();
+        risk.try_execute_order(orderbooks, order_message)?;
+        let timestamp = orderbooks.now();
+        let trades = orderbooks.trades().to_vec();
+        let exec_id = self.next_exec_id();
+        let mut report = order.execution_report(exec_id, exec_type, order.ord_status());
+        report.set(tags::TRANSACT_TIME, transact_time(timestamp));
+
+        if let Some(orig_cl_ord_id) = orig_cl_ord_id {
+            report.set(tags::ORIG_CL_ORD_ID, orig_cl_ord_id);
+        }
+
+        self.send(&order.session, report);
+        self.orders.insert(order_message.id, order);
+
+        for trade in trades.iter() {
+            for engine_id in [trade.aggressor_id, trade.resting_id].iter() {
+                let mut order = match self.orders.remove(engine_id) {
+                    Some(order) => order,
+                    None => continue,
+                };
+                order.cum_qty += trade.volume;
+                order.cum_quote += trade.quote();
+                let exec_id = self.next_exec_id();
+                let mut report = order.execution_report(exec_id, EXEC_TYPE_TRADE, order.ord_status());
+                report
+                    .set(tags::LAST_PX, trade.price)
+                    .set(tags::LAST_QTY, trade.volume)
+                    .set(tags::TRANSACT_TIME, transact_time(trade.timestamp));
+                self.send(&order.session, report);
+
+                if order.leaves_qty() > 0 {
+                    self.orders.insert(*engine_id, order);
+                }
+            }
+        }
+
+        // Whatever a market order could not fill is not kept on the books.
+        if order_message.r#type == OrderType::Market {
+            if let Some(order) = self.orders.remove(&order_message.id) {
+                self.report(&order, EXEC_TYPE_CANCELED, ORD_STATUS_CANCELED, timestamp);
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,214 @@
+use super::tags;
+use std::fmt;
+use std::str::{self, FromStr};
+
+pub type FixTag = u32;
+pub type SeqNum = u64;
+
+pub const SOH: u8 = 0x01;
+pub const BEGIN_STRING_FIX44: &str = "FIX.4.4";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FixError {
+    BeginString,
+    BodyLength,
+    CheckSum,
+    Field(String),
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FixMessage {
+    pub fields: Vec<(FixTag, String)>,
+}
+
+impl FixMessage {
+    pub fn new(msg_type: &str) -> FixMessage {
+        let mut message = FixMessage::default();
+        message.set(tags::MSG_TYPE, msg_type);
+        message
+    }
+
+    pub fn msg_type(&self) -> &str {
+        self.get(tags::MSG_TYPE).unwrap_or("")
+    }
+
+    pub fn get(&self, tag: FixTag) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(field_tag, _)| *field_tag == tag)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn parse<T: FromStr>(&self, tag: FixTag) -> Option<T> {
+        self.get(tag).and_then(|value| value.parse().ok())
+    }
+
+    pub fn set<T: ToString>(&mut self, tag: FixTag, value: T) -> &mut FixMessage {
+        let value = value.to_string();
+
+        match self.fields.iter_mut().find(|(field_tag, _)| *field_tag == tag) {
+            Some(field) => field.1 = value,
+            None => self.fields.push((tag, value)),
+        }
+
+        self
+    }
+
+    pub fn remove(&mut self, tag: FixTag) {
+        self.fields.retain(|(field_tag, _)| *field_tag != tag);
+    }
+
+    pub fn seq_num(&self) -> Option<SeqNum> {
+        self.parse(tags::MSG_SEQ_NUM)
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut body = Vec::new();
+
+        // MsgType always leads the body, followed by the rest of the fields in insertion order.
+        for (tag, value) in
+            self.fields
+                .iter()
+                .filter(|(tag, _)| *tag == tags::MSG_TYPE)
+                .chain(self.fields.iter().filter(|(tag, _)| {
+                    *tag != tags::MSG_TYPE
+                        && *tag != tags::BEGIN_STRING
+                        && *tag != tags::BODY_LENGTH
+                        && *tag != tags::CHECK_SUM
+                }))
+        {
+            body.extend_from_slice(format!("{}={}", tag, value).as_bytes());
+            body.push(SOH);
+        }
+
+        let mut encoded = format!("8={}\x019={}\x01", BEGIN_STRING_FIX44, body.len()).into_bytes();
+        encoded.extend_from_slice(&body);
+        let check_sum = checksum(&encoded);
+        encoded.extend_from_slice(format!("10={:03}\x01", check_sum).as_bytes());
+        encoded
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<FixMessage, FixError> {
+        let mut message = FixMessage::default();
+
+        for field in bytes.split(|byte| *byte == SOH).filter(|field| !field.is_empty()) {
+            let field = str::from_utf8(field).map_err(|_| FixError::Field(String::from_utf8_lossy(field).into()))?;
+            let mut tag_value = field.splitn(2, '=');
+            let tag = tag_value.next().and_then(|tag| tag.parse().ok());
+            let value = tag_value.next();
+
+            match (tag, value) {
+                (Some(tag), Some(value)) => message.fields.push((tag, value.to_string())),
+                _ => return Err(FixError::Field(field.to_string())),
+            }
+        }
+
+        if message.get(tags::BEGIN_STRING) != Some(BEGIN_STRING_FIX44) {
+            return Err(FixError::BeginString);
+        }
+
+        let check_sum_position = bytes.len() - 7;
+
+        if message.parse::<u8>(tags::CHECK_SUM) != Some(checksum(&bytes[..check_sum_position])) {
+            return Err(FixError::CheckSum);
+        }
+
+        Ok(message)
+    }
+}
+
+impl fmt::Display for FixMessage {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let fields: Vec<String> = self
+            .fields
+            .iter()
+            .map(|(tag, value)| format!("{}={}", tag, value))
+            .collect();
+        write!(formatter, "{}", fields.join("|"))
+    }
+}
+
+impl fmt::Display for FixError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FixError::BeginString => write!(formatter, "Unsupported BeginString"),
+            FixError::BodyLength => write!(formatter, "Invalid BodyLength"),
+            FixError::CheckSum => write!(formatter, "Invalid CheckSum"),
+            FixError::Field(field) => write!(formatter, "Malformed field: {}", field),
+        }
+    }
+}
+
+pub fn checksum(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0u8, |check_sum, byte| check_sum.wrapping_add(*byte))
+}
+
+// Splits the next complete message off the front of `buffer`, using BodyLength for framing.
+pub fn take_message(buffer: &mut Vec<u8>) -> Option<Result<FixMessage, FixError>> {
+    let begin_string_end = buffer.iter().position(|byte| *byte == SOH)?;
+    let body_length_end = begin_string_end + 1 + buffer[begin_string_end + 1..].iter().position(|byte| *byte == SOH)?;
+    let body_length_field = &buffer[begin_string_end + 1..body_length_end];
+
+    if !body_length_field.starts_with(b"9=") {
+        buffer.clear();
+        return Some(Err(FixError::BodyLength));
+    }
+
+    let body_length = match str::from_utf8(&body_length_field[2..])
+        .ok()
+        .and_then(|value| value.parse::<usize>().ok())
+    {
+        Some(body_length) => body_length,
+        None => {
+            buffer.clear();
+            return Some(Err(FixError::BodyLength));
+        }
+    };
+    let message_length = body_length_end + 1 + body_length + 7;
+
+    if buffer.len() < message_length {
+        return None;
+    }
+
+    let message_bytes: Vec<u8> = buffer.drain(..message_length).collect();
+
+    if !message_bytes.ends_with(&[SOH]) || !message_bytes[message_length - 7..].starts_with(b"10=") {
+        return Some(Err(FixError::BodyLength));
+    }
+
+    Some(FixMessage::decode(&message_bytes))
+}
+
+#[cfg(test)]
+mod unit_test {
+    use super::*;
+
+    #[test]
+    fn test_encode_then_decode() {
+        let mut message = FixMessage::new(tags::MSG_TYPE_LOGON);
+        message
+            .set(tags::SENDER_COMP_ID, "CLIENT")
+            .set(tags::TARGET_COMP_ID, "CCME")
+            .set(tags::MSG_SEQ_NUM, 1)
+            .set(tags::HEART_BT_INT, 30);
+        let mut buffer = message.encode();
+        let encoded = String::from_utf8(buffer.clone()).unwrap().replace('\x01', "|");
+
+        assert_eq!(encoded, "8=FIX.4.4|9=35|35=A|49=CLIENT|56=CCME|34=1|108=30|10=034|");
+        assert!(take_message(&mut buffer[..20].to_vec()).is_none());
+
+        let decoded = take_message(&mut buffer).unwrap().unwrap();
+
+        assert!(buffer.is_empty());
+        assert_eq!(decoded.msg_type(), tags::MSG_TYPE_LOGON);
+        assert_eq!(decoded.seq_num(), Some(1));
+        assert_eq!(decoded.parse::<u64>(tags::HEART_BT_INT), Some(30));
+    }
+
+    #[test]
+    fn test_decode_bad_checksum() {
+        let mut buffer = b"8=FIX.4.4\x019=5\x0135=0\x0110=000\x01".to_vec();
+
+        assert_eq!(take_message(&mut buffer), Some(Err(FixError::CheckSum)));
+    }
+}
@@ -0,0 +1,10 @@
+mod acceptor;
+mod gateway;
+mod message;
+mod session;
+pub mod tags;
+
+pub use acceptor::*;
+pub use gateway::*;
+pub use message::*;
+pub use session::*;
@@ -0,0 +1,490 @@
+use super::message::*;
+use super::tags;
+use chrono::prelude::*;
+use std::collections::BTreeMap;
+use std::mem;
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_HEARTBEAT_INTERVAL: u64 = 30;
+
+#[derive(Clone, Debug)]
+pub struct FixSessionConfig {
+    pub sender_comp_id: String,
+    pub max_heartbeat_interval: u64,
+}
+
+#[derive(Clone, Debug)]
+struct SentMessage {
+    sending_time: String,
+    message: FixMessage,
+}
+
+// Acceptor side of a single FIX 4.4 connection. Sequence numbers start at 1
+// for every connection, so a reconnecting initiator has to log on with
+// ResetSeqNumFlag or with MsgSeqNum=1.
+#[derive(Debug)]
+pub struct FixSession {
+    pub sender_comp_id: String,
+    pub target_comp_id: Option<String>,
+    pub heartbeat_interval: Duration,
+    pub max_heartbeat_interval: u64,
+    pub next_incoming_seq: SeqNum,
+    pub next_outgoing_seq: SeqNum,
+    pub logged_on: bool,
+    pub disconnected: bool,
+    resend_requested_until: Option<SeqNum>,
+    logout_sent: bool,
+    test_request_sent_at: Option<Instant>,
+    last_received_at: Instant,
+    last_sent_at: Instant,
+    sent_messages: BTreeMap<SeqNum, SentMessage>,
+    outbound: Vec<u8>,
+}
+
+pub fn sending_time() -> String {
+    Utc::now().format("%Y%m%d-%H:%M:%S%.3f").to_string()
+}
+
+fn is_admin_message(msg_type: &str) -> bool {
+    matches!(
+        msg_type,
+        tags::MSG_TYPE_HEARTBEAT
+            | tags::MSG_TYPE_TEST_REQUEST
+            | tags::MSG_TYPE_RESEND_REQUEST
+            | tags::MSG_TYPE_SEQUENCE_RESET
+            | tags::MSG_TYPE_LOGOUT
+            | tags::MSG_TYPE_LOGON
+    )
+}
+
+fn is_header_tag(tag: FixTag) -> bool {
+    matches!(
+        tag,
+        tags::BEGIN_STRING
+            | tags::BODY_LENGTH
+            | tags::MSG_TYPE
+            | tags::SENDER_COMP_ID
+            | tags::TARGET_COMP_ID
+            | tags::MSG_SEQ_NUM
+            | tags::SENDING_TIME
+            | tags::POSS_DUP_FLAG
+            | tags::ORIG_SENDING_TIME
+            | tags::CHECK_SUM
+    )
+}
+
+impl FixSession {
+    pub fn new(config: &FixSessionConfig, now: Instant) -> FixSession {
+        FixSession {
+            sender_comp_id: config.sender_comp_id.clone(),
+            target_comp_id: None,
+            heartbeat_interval: Duration::from_secs(DEFAULT_HEARTBEAT_INTERVAL),
+            max_heartbeat_interval: config.max_heartbeat_interval,
+            next_incoming_seq: 1,
+            next_outgoing_seq: 1,
+            logged_on: false,
+            disconnected: false,
+            resend_requested_until: None,
+            logout_sent: false,
+            test_request_sent_at: None,
+            last_received_at: now,
+            last_sent_at: now,
+            sent_messages: BTreeMap::new(),
+            outbound: Vec::new(),
+        }
+    }
+
+    pub fn drain_outbound(&mut self) -> Vec<u8> {
+        mem::take(&mut self.outbound)
+    }
+
+    pub fn send(&mut self, message: FixMessage, now: Instant) {
+        let seq_num = self.next_outgoing_seq;
+        let sending_time = sending_time();
+        self.next_outgoing_seq += 1;
+        self.write(seq_num, &message, &sending_time, None, now);
+        self.sent_messages
+            .insert(seq_num, SentMessage { sending_time, message });
+    }
+
+    fn write(
+        &mut self,
+        seq_num: SeqNum,
+        message: &FixMessage,
+        sending_time: &str,
+        orig_sending_time: Option<&str>,
+        now: Instant,
+    ) {
+        let mut stamped = FixMessage::new(message.msg_type());
+        stamped
+            .set(tags::SENDER_COMP_ID, &self.sender_comp_id)
+            .set(tags::TARGET_COMP_ID, self.target_comp_id.as_deref().unwrap_or(""))
+            .set(tags::MSG_SEQ_NUM, seq_num)
+            .set(tags::SENDING_TIME, sending_time);
+
+        if let Some(orig_sending_time) = orig_sending_time {
+            stamped
+                .set(tags::POSS_DUP_FLAG, "Y")
+                .set(tags::ORIG_SENDING_TIME, orig_sending_time);
+        }
+
+        let body_fields = message.fields.iter().filter(|(tag, _)| !is_header_tag(*tag));
+        stamped.fields.extend(body_fields.cloned());
+        self.outbound.extend_from_slice(&stamped.encode());
+        self.last_sent_at = now;
+    }
+
+    fn logout(&mut self, text: &str, now: Instant) {
+        let mut logout = FixMessage::new(tags::MSG_TYPE_LOGOUT);
+
+        if !text.is_empty() {
+            logout.set(tags::TEXT, text);
+        }
+
+        self.send(logout, now);
+        self.logout_sent = true;
+        self.disconnected = true;
+    }
+
+    fn reject(&mut self, ref_seq_num: SeqNum, text: &str, now: Instant) {
+        let mut reject = FixMessage::new(tags::MSG_TYPE_REJECT);
+        reject.set(tags::REF_SEQ_NUM, ref_seq_num).set(tags::TEXT, text);
+        self.send(reject, now);
+    }
+
+    // Returns the application messages that should be handed to the gateway.
+    pub fn on_message(&mut self, message: FixMessage, now: Instant) -> Vec<FixMessage> {
+        self.last_received_at = now;
+        self.test_request_sent_at = None;
+
+        let msg_type = message.msg_type().to_string();
+        let seq_num = match message.seq_num() {
+            Some(seq_num) => seq_num,
+            None => {
+                self.logout("MsgSeqNum missing", now);
+                return Vec::new();
+            }
+        };
+
+        if !self.logged_on {
+            if msg_type != tags::MSG_TYPE_LOGON {
+                self.disconnected = true;
+                return Vec::new();
+            }
+
+            return self.on_logon(&message, seq_num, now);
+        }
+
+        if message.get(tags::SENDER_COMP_ID) != self.target_comp_id.as_deref()
+            || message.get(tags::TARGET_COMP_ID) != Some(self.sender_comp_id.as_str())
+        {
+            self.reject(seq_num, "CompID problem", now);
+            self.logout("CompID problem", now);
+            return Vec::new();
+        }
+
+        let gap_fill = message.get(tags::GAP_FILL_FLAG) == Some("Y");
+
+        if msg_type == tags::MSG_TYPE_SEQUENCE_RESET && !gap_fill {
+            match message.parse::<SeqNum>(tags::NEW_SEQ_NO) {
+                Some(new_seq_num) if new_seq_num >= self.next_incoming_seq => self.next_incoming_seq = new_seq_num,
+                _ => self.reject(seq_num, "Value is incorrect (out of range) for this tag", now),
+            }
+
+            return Vec::new();
+        }
+
+        if seq_num < self.next_incoming_seq {
+            if message.get(tags::POSS_DUP_FLAG) != Some("Y") {
+                let text = format!(
+                    "MsgSeqNum too low, expecting {} but received {}",
+                    self.next_incoming_seq, seq_num
+                );
+                self.logout(&text, now);
+            }
+
+            return Vec::new();
+        }
+
+        if seq_num > self.next_incoming_seq {
+            if self.resend_requested_until.is_none() {
+                self.request_resend(seq_num, now);
+            }
+
+            // The rest of the gap will be resent, but a resend request and a logout are acted upon straight away.
+            match msg_type.as_str() {
+                tags::MSG_TYPE_RESEND_REQUEST => self.on_resend_request(&message, now),
+                tags::MSG_TYPE_LOGOUT => self.on_logout(now),
+                _ => {}
+            }
+
+            return Vec::new();
+        }
+
+        self.next_incoming_seq += 1;
+
+        if let Some(resend_requested_until) = self.resend_requested_until {
+            if self.next_incoming_seq > resend_requested_until {
+                self.resend_requested_until = None;
+            }
+        }
+
+        match msg_type.as_str() {
+            tags::MSG_TYPE_HEARTBEAT | tags::MSG_TYPE_REJECT => {}
+            tags::MSG_TYPE_TEST_REQUEST => {
+                let mut heartbeat = FixMessage::new(tags::MSG_TYPE_HEARTBEAT);
+
+                if let Some(test_req_id) = message.get(tags::TEST_REQ_ID) {
+                    heartbeat.set(tags::TEST_REQ_ID, test_req_id);
+                }
+
+                self.send(heartbeat, now);
+            }
+            tags::MSG_TYPE_RESEND_REQUEST => self.on_resend_request(&message, now),
+            tags::MSG_TYPE_SEQUENCE_RESET => match message.parse::<SeqNum>(tags::NEW_SEQ_NO) {
+                Some(new_seq_num) if new_seq_num >= self.next_incoming_seq => self.next_incoming_seq = new_seq_num,
+                _ => self.reject(seq_num, "Value is incorrect (out of range) for this tag", now),
+            },
+            tags::MSG_TYPE_LOGOUT => self.on_logout(now),
+            tags::MSG_TYPE_LOGON => self.reject(seq_num, "Already logged on", now),
+            _ => return vec![message],
+        }
+
+        Vec::new()
+    }
+
+    fn on_logon(&mut self, message: &FixMessage, seq_num: SeqNum, now: Instant) -> Vec<FixMessage> {
+        let heartbeat_interval = message.parse::<u64>(tags::HEART_BT_INT);
+        let target_comp_id = message.get(tags::SENDER_COMP_ID).filter(|comp_id| !comp_id.is_empty());
+        self.target_comp_id = target_comp_id.map(String::from);
+
+        if target_comp_id.is_none() || message.get(tags::TARGET_COMP_ID) != Some(self.sender_comp_id.as_str()) {
+            self.logout("CompID problem", now);
+            return Vec::new();
+        }
+
+        let heartbeat_interval = match heartbeat_interval {
+            Some(heartbeat_interval) if heartbeat_interval > 0 && heartbeat_interval <= self.max_heartbeat_interval => {
+                heartbeat_interval
+            }
+            _ => {
+                self.logout("Invalid HeartBtInt", now);
+                return Vec::new();
+            }
+        };
+        let reset_seq_num = message.get(tags::RESET_SEQ_NUM_FLAG) == Some("Y");
+
+        if reset_seq_num {
+            self.next_outgoing_seq = 1;
+            self.sent_messages.clear();
+
+            if seq_num != 1 {
+                self.logout("MsgSeqNum must be 1 when ResetSeqNumFlag is set", now);
+                return Vec::new();
+            }
+        }
+
+        self.heartbeat_interval = Duration::from_secs(heartbeat_interval);
+        self.logged_on = true;
+        let mut logon = FixMessage::new(tags::MSG_TYPE_LOGON);
+        logon
+            .set(tags::ENCRYPT_METHOD, 0)
+            .set(tags::HEART_BT_INT, heartbeat_interval);
+
+        if reset_seq_num {
+            logon.set(tags::RESET_SEQ_NUM_FLAG, "Y");
+        }
+
+        self.send(logon, now);
+
+        if seq_num > self.next_incoming_seq {
+            self.request_resend(seq_num, now);
+        } else {
+            self.next_incoming_seq = seq_num + 1;
+        }
+
+        Vec::new()
+    }
+
+    fn on_logout(&mut self, now: Instant) {
+        if !self.logout_sent {
+            self.logout("", now);
+        }
+
+        self.disconnected = true;
+    }
+
+    fn request_resend(&mut self, received_seq_num: SeqNum, now: Instant) {
+        let mut resend_request = FixMessage::new(tags::MSG_TYPE_RESEND_REQUEST);
+        resend_request
+            .set(tags::BEGIN_SEQ_NO, self.next_incoming_seq)
+            .set(tags::END_SEQ_NO, 0);
+        self.send(resend_request, now);
+        self.resend_requested_until = Some(received_seq_num);
+    }
+
+    fn on_resend_request(&mut self, message: &FixMessage, now: Instant) {
+        let last_sent_seq = self.next_outgoing_seq - 1;
+        let begin_seq_num = message.parse::<SeqNum>(tags::BEGIN_SEQ_NO).unwrap_or(1).max(1);
+        let end_seq_num = match message.parse::<SeqNum>(tags::END_SEQ_NO) {
+            Some(end_seq_num) if end_seq_num != 0 => end_seq_num.min(last_sent_seq),
+            _ => last_sent_seq,
+        };
+        let mut gap_fill_begin = None;
+
+        for seq_num in begin_seq_num..=end_seq_num {
+            let resendable = self
+                .sent_messages
+                .get(&seq_num)
+                .filter(|sent_message| !is_admin_message(sent_message.message.msg_type()))
+                .cloned();
+
+            match resendable {
+                Some(sent_message) => {
+                    if let Some(gap_fill_begin) = gap_fill_begin.take() {
+                        self.gap_fill(gap_fill_begin, seq_num, now);
+                    }
+
+                    let sending_time = sending_time();
+                    let orig_sending_time = Some(sent_message.sending_time.as_str());
+                    self.write(seq_num, &sent_message.message, &sending_time, orig_sending_time, now);
+                }
+                None => {
+                    gap_fill_begin = gap_fill_begin.or(Some(seq_num));
+                }
+            }
+        }
+
+        if let Some(gap_fill_begin) = gap_fill_begin {
+            self.gap_fill(gap_fill_begin, end_seq_num + 1, now);
+        }
+    }
+
+    fn gap_fill(&mut self, begin_seq_num: SeqNum, new_seq_num: SeqNum, now: Instant) {
+        let mut sequence_reset = FixMessage::new(tags::MSG_TYPE_SEQUENCE_RESET);
+        sequence_reset
+            .set(tags::GAP_FILL_FLAG, "Y")
+            .set(tags::NEW_SEQ_NO, new_seq_num);
+        let sending_time = sending_time();
+        self.write(begin_seq_num, &sequence_reset, &sending_time, Some(&sending_time), now);
+    }
+
+    pub fn on_timer(&mut self, now: Instant) {
+        if !self.logged_on || self.disconnected {
+            return;
+        }
+
+        let silence = now.duration_since(self.last_received_at);
+
+        if let Some(test_request_sent_at) = self.test_request_sent_at {
+            if now.duration_since(test_request_sent_at) >= self.heartbeat_interval {
+                self.logout("Test request timed out", now);
+                return;
+            }
+        } else if silence >= self.heartbeat_interval + self.heartbeat_interval / 5 {
+            let mut test_request = FixMessage::new(tags::MSG_TYPE_TEST_REQUEST);
+            test_request.set(tags::TEST_REQ_ID, sending_time());
+            self.send(test_request, now);
+            self.test_request_sent_at = Some(now);
+        }
+
+        if now.duration_since(self.last_sent_at) >= self.heartbeat_interval {
+            self.send(FixMessage::new(tags::MSG_TYPE_HEARTBEAT), now);
+        }
+    }
+}
+
+#[cfg(test)]
+mod unit_test {
+    use super::*;
+
+    fn client_message(msg_type: &str, seq_num: SeqNum) -> FixMessage {
+        let mut message = FixMessage::new(msg_type);
+        message
+            .set(tags::SENDER_COMP_ID, "CLIENT")
+            .set(tags::TARGET_COMP_ID, "CCME")
+            .set(tags::MSG_SEQ_NUM, seq_num);
+        message
+    }
+
+    fn sent_messages(session: &mut FixSession) -> Vec<FixMessage> {
+        let mut outbound = session.drain_outbound();
+        let mut messages = Vec::new();
+
+        while let Some(message) = take_message(&mut outbound) {
+            messages.push(message.unwrap());
+        }
+
+        messages
+    }
+
+    fn logged_on_session(now: Instant) -> FixSession {
+        let config = FixSessionConfig {
+            sender_comp_id: "CCME".to_string(),
+            max_heartbeat_interval: 60,
+        };
+        let mut session = FixSession::new(&config, now);
+        let mut logon = client_message(tags::MSG_TYPE_LOGON, 1);
+        logon.set(tags::HEART_BT_INT, 10);
+        session.on_message(logon, now);
+        session
+    }
+
+    #[test]
+    fn test_logon_and_heartbeat_timers() {
+        let now = Instant::now();
+        let mut session = logged_on_session(now);
+        let messages = sent_messages(&mut session);
+
+        assert!(session.logged_on);
+        assert_eq!(messages[0].msg_type(), tags::MSG_TYPE_LOGON);
+        assert_eq!(messages[0].get(tags::TARGET_COMP_ID), Some("CLIENT"));
+        assert_eq!(messages[0].get(tags::HEART_BT_INT), Some("10"));
+
+        session.on_timer(now + Duration::from_secs(10));
+        let messages = sent_messages(&mut session);
+
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].msg_type(), tags::MSG_TYPE_HEARTBEAT);
+
+        session.on_timer(now + Duration::from_secs(12));
+        let messages = sent_messages(&mut session);
+
+        assert_eq!(messages[0].msg_type(), tags::MSG_TYPE_TEST_REQUEST);
+        assert_eq!(messages[0].seq_num(), Some(3));
+
+        session.on_timer(now + Duration::from_secs(22));
+
+        assert!(session.disconnected);
+        assert_eq!(sent_messages(&mut session)[0].msg_type(), tags::MSG_TYPE_LOGOUT);
+    }
+
+    #[test]
+    fn test_sequence_gap_then_gap_fill() {
+        let now = Instant::now();
+        let mut session = logged_on_session(now);
+        sent_messages(&mut session);
+        let delivered = session.on_message(client_message(tags::MSG_TYPE_NEW_ORDER_SINGLE, 4), now);
+        let messages = sent_messages(&mut session);
+
+        assert!(delivered.is_empty());
+        assert_eq!(messages[0].msg_type(), tags::MSG_TYPE_RESEND_REQUEST);
+        assert_eq!(messages[0].get(tags::BEGIN_SEQ_NO), Some("2"));
+
+        session.on_message(client_message(tags::MSG_TYPE_NEW_ORDER_SINGLE, 5), now);
+
+        assert!(sent_messages(&mut session).is_empty());
+
+        let mut gap_fill = client_message(tags::MSG_TYPE_SEQUENCE_RESET, 2);
+        gap_fill.set(tags::GAP_FILL_FLAG, "Y").set(tags::NEW_SEQ_NO, 4);
+        session.on_message(gap_fill, now);
+        let delivered = session.on_message(client_message(tags::MSG_TYPE_NEW_ORDER_SINGLE, 4), now);
+
+        assert_eq!(delivered.len(), 1);
+        assert_eq!(session.next_incoming_seq, 5);
+
+        session.on_message(client_message(tags::MSG_TYPE_HEARTBEAT, 2), now);
+
+        assert!(session.disconnected);
+    }
+}
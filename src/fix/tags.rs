@@ -0,0 +1,63 @@
+use super::message::FixTag;
+
+pub const BEGIN_STRING: FixTag = 8;
+pub const BODY_LENGTH: FixTag = 9;
+pub const CHECK_SUM: FixTag = 10;
+pub const MSG_TYPE: FixTag = 35;
+pub const SENDER_COMP_ID: FixTag = 49;
+pub const TARGET_COMP_ID: FixTag = 56;
+pub const MSG_SEQ_NUM: FixTag = 34;
+pub const SENDING_TIME: FixTag = 52;
+pub const POSS_DUP_FLAG: FixTag = 43;
+pub const ORIG_SENDING_TIME: FixTag = 122;
+
+pub const BEGIN_SEQ_NO: FixTag = 7;
+pub const END_SEQ_NO: FixTag = 16;
+pub const NEW_SEQ_NO: FixTag = 36;
+pub const GAP_FILL_FLAG: FixTag = 123;
+pub const ENCRYPT_METHOD: FixTag = 98;
+pub const HEART_BT_INT: FixTag = 108;
+pub const TEST_REQ_ID: FixTag = 112;
+pub const RESET_SEQ_NUM_FLAG: FixTag = 141;
+pub const REF_SEQ_NUM: FixTag = 45;
+pub const TEXT: FixTag = 58;
+pub const REF_MSG_TYPE: FixTag = 372;
+pub const SESSION_REJECT_REASON: FixTag = 373;
+
+pub const ACCOUNT: FixTag = 1;
+pub const AVG_PX: FixTag = 6;
+pub const CL_ORD_ID: FixTag = 11;
+pub const CUM_QTY: FixTag = 14;
+pub const EXEC_ID: FixTag = 17;
+pub const LAST_PX: FixTag = 31;
+pub const LAST_QTY: FixTag = 32;
+pub const ORDER_ID: FixTag = 37;
+pub const ORDER_QTY: FixTag = 38;
+pub const ORD_STATUS: FixTag = 39;
+pub const ORD_TYPE: FixTag = 40;
+pub const ORIG_CL_ORD_ID: FixTag = 41;
+pub const PRICE: FixTag = 44;
+pub const SIDE: FixTag = 54;
+pub const SYMBOL: FixTag = 55;
+pub const TRANSACT_TIME: FixTag = 60;
+pub const CXL_REJ_REASON: FixTag = 102;
+pub const ORD_REJ_REASON: FixTag = 103;
+pub const EXEC_TYPE: FixTag = 150;
+pub const LEAVES_QTY: FixTag = 151;
+pub const CASH_ORDER_QTY: FixTag = 152;
+pub const CXL_REJ_RESPONSE_TO: FixTag = 434;
+// User defined, sent on Logon. "N" keeps the session's orders resting after a disconnect.
+pub const CANCEL_ORDERS_ON_DISCONNECT: FixTag = 8013;
+
+pub const MSG_TYPE_HEARTBEAT: &str = "0";
+pub const MSG_TYPE_TEST_REQUEST: &str = "1";
+pub const MSG_TYPE_RESEND_REQUEST: &str = "2";
+pub const MSG_TYPE_REJECT: &str = "3";
+pub const MSG_TYPE_SEQUENCE_RESET: &str = "4";
+pub const MSG_TYPE_LOGOUT: &str = "5";
+pub const MSG_TYPE_EXECUTION_REPORT: &str = "8";
+pub const MSG_TYPE_ORDER_CANCEL_REJECT: &str = "9";
+pub const MSG_TYPE_LOGON: &str = "A";
+pub const MSG_TYPE_NEW_ORDER_SINGLE: &str = "D";
+pub const MSG_TYPE_ORDER_CANCEL_REQUEST: &str = "F";
+pub const MSG_TYPE_ORDER_CANCEL_REPLACE_REQUEST: &str = "G";
@@ -0,0 +1,32 @@
+//! Order matching for a single instrument per `Orderbooks`, plus the gateways
+//! and tools built on it. Book internals stay private to the crate, everything
+//! a caller needs is re-exported here.
+//!
+//! ```compile_fail
+//! let the_orderbooks = cc_matching_engine::Orderbooks::default();
+//! the_orderbooks.orders_location.len();
+//! ```
+
+mod orderbooks;
+mod risk;
+
+pub mod ffi;
+pub mod fix;
+pub mod ouch;
+pub mod repl;
+pub mod replay;
+pub mod rest;
+pub mod workload;
+pub mod ws;
+
+pub use orderbooks::{
+    AccountId, AuctionIndication, Balance, BandBreachAction, Candle, CandleAggregator, CandleInterval, CandleSeries,
+    ClientOrderId, DepthChecksum, DepthSnapshot, DepthUpdate, DepthUpdateType, EngineClock, FifoMatching, Funds,
+    HybridMatching, Ledger, MarketDepth, MassCancel, MatchingPolicy, OrderEvent, OrderEventType, OrderFeedMessage,
+    OrderFeedMessageType, OrderId, OrderMessage, OrderPrice, OrderQuote, OrderRejection, OrderSide, OrderType,
+    OrderVolume, Orderbook, OrderbookOrder, Orderbooks, Position, PriceBands, PriceLevel, ProRataMatching,
+    ProRataRemainder, QuoteVolume, SequenceNumber, SessionId, SessionSchedule, SessionState, SessionTransitionError,
+    Ticker, TimeInForce, Trade, TradeCount, UTCNanoSeconds, BASIS_POINTS, DEPTH_CHECKSUM_LEVELS, NANOSECONDS_PER_DAY,
+    STATISTICS_WINDOW,
+};
+pub use risk::{MessageRate, PreTradeRejection, RiskEngine, RiskLimit, RiskLimits, RiskRejection};
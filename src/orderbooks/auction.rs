@@ -0,0 +1,194 @@
+use super::orders::*;
+use super::{Orderbook, PriceLevel};
+use serde::{Deserialize, Serialize};
+
+// The price and volume the book would uncross at right now. `price` is `None`
+// while the bids and asks do not cross.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AuctionIndication {
+    pub timestamp: UTCNanoSeconds,
+    pub price: Option<OrderPrice>,
+    pub volume: OrderVolume,
+    pub imbalance: OrderVolume,
+    pub imbalance_side: OrderSide,
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Auction {
+    pub reference_price: Option<OrderPrice>,
+    pub indication: Option<AuctionIndication>,
+    pub ends_at: Option<UTCNanoSeconds>,
+}
+
+impl AuctionIndication {
+    pub(crate) fn same_as(&self, other: &AuctionIndication) -> bool {
+        (self.price, self.volume, self.imbalance, self.imbalance_side)
+            == (other.price, other.volume, other.imbalance, other.imbalance_side)
+    }
+}
+
+// Every limit price in the book is a candidate. The equilibrium executes the
+// most volume, then leaves the smallest imbalance, then is closest to the
+// reference price. Without one the middle of the tied prices is used, and the
+// lower price wins an exact tie.
+pub(crate) fn equilibrium(
+    bids: &Orderbook,
+    asks: &Orderbook,
+    reference_price: Option<OrderPrice>,
+    timestamp: UTCNanoSeconds,
+) -> AuctionIndication {
+    let mut prices: Vec<PriceLevel> = bids.orders.keys().chain(asks.orders.keys()).copied().collect();
+    prices.sort_unstable();
+    prices.dedup();
+
+    let mut ask_volumes = Vec::with_capacity(prices.len());
+    let mut bid_volumes = vec![0; prices.len()];
+    let mut cumulative_volume = 0;
+
+    for price in prices.iter() {
+        cumulative_volume += asks.volume(*price);
+        ask_volumes.push(cumulative_volume);
+    }
+
+    cumulative_volume = 0;
+
+    for (index, price) in prices.iter().enumerate().rev() {
+        cumulative_volume += bids.volume(*price);
+        bid_volumes[index] = cumulative_volume;
+    }
+
+    let executable = |index: usize| bid_volumes[index].min(ask_volumes[index]);
+    let imbalance = |index: usize| bid_volumes[index].abs_diff(ask_volumes[index]);
+    let volume = (0..prices.len()).map(executable).max().unwrap_or(0);
+    let mut indication = AuctionIndication {
+        timestamp,
+        price: None,
+        volume: 0,
+        imbalance: 0,
+        imbalance_side: OrderSide::NoSide,
+    };
+
+    if volume == 0 {
+        return indication;
+    }
+
+    let least_imbalance = (0..prices.len())
+        .filter(|index| executable(*index) == volume)
+        .map(imbalance)
+        .min()
+        .unwrap();
+    let tied: Vec<usize> = (0..prices.len())
+        .filter(|index| executable(*index) == volume && imbalance(*index) == least_imbalance)
+        .collect();
+    let reference_price = reference_price.unwrap_or_else(|| (prices[tied[0]] + prices[*tied.last().unwrap()]) / 2);
+    let index = *tied
+        .iter()
+        .min_by_key(|index| (prices[**index].abs_diff(reference_price), prices[**index]))
+        .unwrap();
+
+    indication.price = Some(prices[index]);
+    indication.volume = volume;
+    indication.imbalance = least_imbalance;
+    indication.imbalance_side = match bid_volumes[index].cmp(&ask_volumes[index]) {
+        std::cmp::Ordering::Greater => OrderSide::Bid,
+        std::cmp::Ordering::Less => OrderSide::Ask,
+        std::cmp::Ordering::Equal => OrderSide::NoSide,
+    };
+    indication
+}
+
+#[cfg(test)]
+mod unit_test {
+    use super::super::test_support::*;
+    use super::super::*;
+
+    #[test]
+    fn test_auction_uncrosses_at_the_equilibrium() {
+        let mut the_orderbooks = Orderbooks::default();
+        the_orderbooks.start_auction(None);
+        let orders = [
+            limit_order(OrderSide::Bid, 10, 102),
+            limit_order(OrderSide::Bid, 5, 101),
+            limit_order(OrderSide::Bid, 10, 100),
+            limit_order(OrderSide::Ask, 8, 99),
+            limit_order(OrderSide::Ask, 7, 100),
+            limit_order(OrderSide::Ask, 10, 103),
+        ];
+
+        for order in orders.iter() {
+            let events = the_orderbooks.try_execute_order(order).unwrap();
+
+            assert_eq!(events[&order.id].last().unwrap().r#type, OrderEventType::Open);
+        }
+
+        assert_eq!(
+            the_orderbooks.validate(&OrderMessage {
+                side: OrderSide::Ask,
+                r#type: OrderType::Market,
+                volume: Some(1),
+                ..Default::default()
+            }),
+            Err(OrderRejection::InAuction)
+        );
+
+        // 15 can trade at 100 and 101, but 101 leaves no imbalance.
+        let indications = the_orderbooks.drain_auction_indications();
+        let indication = indications.last().unwrap();
+
+        assert_eq!(indications.first().unwrap().price, None);
+        assert_eq!(
+            (indication.price, indication.volume, indication.imbalance),
+            (Some(101), 15, 0)
+        );
+
+        let events = the_orderbooks.uncross();
+        let trades: Vec<(OrderId, OrderId, OrderVolume, OrderPrice)> = the_orderbooks
+            .trades()
+            .iter()
+            .map(|trade| (trade.aggressor_id, trade.resting_id, trade.volume, trade.price))
+            .collect();
+
+        assert_eq!(
+            trades,
+            vec![
+                (orders[0].id, orders[3].id, 8, 101),
+                (orders[0].id, orders[4].id, 2, 101),
+                (orders[1].id, orders[4].id, 5, 101),
+            ]
+        );
+        assert_eq!(events[&orders[4].id].len(), 3);
+        assert!(!the_orderbooks.in_auction());
+        assert_eq!(the_orderbooks.count(), (1, 1, 2));
+        assert!(the_orderbooks.contains(&orders[2].id) && the_orderbooks.contains(&orders[5].id));
+    }
+
+    #[test]
+    fn test_equilibrium_reference_price_tie_break() {
+        let mut bids = Orderbook::default();
+        let mut asks = Orderbook::default();
+        bids.insert(
+            101,
+            OrderbookOrder {
+                id: OrderId::new_v4(),
+                remaining_volume: 10,
+                account: None,
+                session_id: None,
+            },
+        );
+        asks.insert(
+            99,
+            OrderbookOrder {
+                id: OrderId::new_v4(),
+                remaining_volume: 10,
+                account: None,
+                session_id: None,
+            },
+        );
+
+        assert_eq!(equilibrium(&bids, &asks, Some(101), 0).price, Some(101));
+        assert_eq!(equilibrium(&bids, &asks, Some(50), 0).price, Some(99));
+        assert_eq!(equilibrium(&bids, &asks, Some(100), 0).price, Some(99));
+        assert_eq!(equilibrium(&bids, &asks, None, 0).price, Some(99));
+        assert_eq!(equilibrium(&bids, &Orderbook::default(), None, 0).price, None);
+    }
+}
@@ -0,0 +1,304 @@
+use super::orders::*;
+use super::trades::Trade;
+use std::mem;
+
+pub type TradeCount = u64;
+// Sums of price times volume, wide enough that a busy interval cannot overflow.
+pub type QuoteVolume = u128;
+
+#[derive(Copy, Clone, Debug)]
+#[repr(u8)]
+pub enum CandleInterval {
+    OneSecond = 0,
+    OneMinute = 1,
+    OneHour = 2,
+    OneDay = 3,
+}
+
+#[derive(Clone, Debug)]
+#[repr(align(8))]
+pub struct Candle {
+    pub interval: CandleInterval,
+    pub open_time: UTCNanoSeconds,
+    pub close_time: UTCNanoSeconds,
+    pub open: OrderPrice,
+    pub high: OrderPrice,
+    pub low: OrderPrice,
+    pub close: OrderPrice,
+    pub volume: OrderVolume,
+    pub quote_volume: QuoteVolume,
+    pub trade_count: TradeCount,
+}
+
+#[derive(Clone, Debug)]
+pub struct CandleSeries {
+    pub interval: CandleInterval,
+    pub current: Option<Candle>,
+    pub next_open_time: Option<UTCNanoSeconds>,
+    pub last_close: Option<OrderPrice>,
+    pub closed: Vec<Candle>,
+}
+
+#[derive(Clone, Debug)]
+pub struct CandleAggregator {
+    pub series: Vec<CandleSeries>,
+}
+
+impl CandleInterval {
+    pub fn nanoseconds(self) -> UTCNanoSeconds {
+        match self {
+            CandleInterval::OneSecond => 1_000_000_000,
+            CandleInterval::OneMinute => 60_000_000_000,
+            CandleInterval::OneHour => 3_600_000_000_000,
+            CandleInterval::OneDay => 86_400_000_000_000,
+        }
+    }
+
+    pub fn open_time(self, timestamp: UTCNanoSeconds) -> UTCNanoSeconds {
+        timestamp - timestamp.rem_euclid(self.nanoseconds())
+    }
+}
+
+impl Candle {
+    pub fn new(interval: CandleInterval, open_time: UTCNanoSeconds, price: OrderPrice) -> Candle {
+        Candle {
+            interval,
+            open_time,
+            close_time: open_time + interval.nanoseconds(),
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            volume: 0,
+            quote_volume: 0,
+            trade_count: 0,
+        }
+    }
+
+    pub fn add_trade(&mut self, trade: &Trade) {
+        if self.trade_count == 0 {
+            self.open = trade.price;
+            self.high = trade.price;
+            self.low = trade.price;
+        }
+
+        self.high = self.high.max(trade.price);
+        self.low = self.low.min(trade.price);
+        self.close = trade.price;
+        self.volume += trade.volume;
+        self.quote_volume += trade.price as QuoteVolume * trade.volume as QuoteVolume;
+        self.trade_count += 1;
+    }
+
+    // Rounded to the nearest price, halves up.
+    pub fn vwap(&self) -> Option<OrderPrice> {
+        let volume = self.volume as QuoteVolume;
+
+        if volume == 0 {
+            return None;
+        }
+
+        Some(((self.quote_volume + volume / 2) / volume) as OrderPrice)
+    }
+}
+
+impl CandleSeries {
+    pub fn new(interval: CandleInterval) -> CandleSeries {
+        CandleSeries {
+            interval,
+            current: None,
+            next_open_time: None,
+            last_close: None,
+            closed: Vec::new(),
+        }
+    }
+
+    pub fn advance(&mut self, now: UTCNanoSeconds) {
+        if let Some(current_candle) = self.current.as_ref() {
+            if now < current_candle.close_time {
+                return;
+            }
+
+            let closed_candle = self.current.take().unwrap();
+            self.next_open_time = Some(closed_candle.close_time);
+            self.last_close = Some(closed_candle.close);
+            self.closed.push(closed_candle);
+        }
+
+        if let (Some(mut next_open_time), Some(last_close)) = (self.next_open_time, self.last_close) {
+            let interval_nanoseconds = self.interval.nanoseconds();
+
+            while next_open_time + interval_nanoseconds <= now {
+                self.closed.push(Candle::new(self.interval, next_open_time, last_close));
+                next_open_time += interval_nanoseconds;
+            }
+
+            self.next_open_time = Some(next_open_time);
+        }
+    }
+
+    pub fn add_trade(&mut self, trade: &Trade) {
+        self.advance(trade.timestamp);
+
+        let interval = self.interval;
+        let current_candle = self
+            .current
+            .get_or_insert_with(|| Candle::new(interval, interval.open_time(trade.timestamp), trade.price));
+
+        current_candle.add_trade(trade);
+    }
+}
+
+impl CandleAggregator {
+    pub fn new(intervals: &[CandleInterval]) -> CandleAggregator {
+        CandleAggregator {
+            series: intervals.iter().map(|interval| CandleSeries::new(*interval)).collect(),
+        }
+    }
+
+    pub fn add_trades(&mut self, trades: &[Trade]) {
+        for trade in trades {
+            for candle_series in self.series.iter_mut() {
+                candle_series.add_trade(trade);
+            }
+        }
+    }
+
+    pub fn advance(&mut self, now: UTCNanoSeconds) {
+        for candle_series in self.series.iter_mut() {
+            candle_series.advance(now);
+        }
+    }
+
+    pub fn current(&self, interval: CandleInterval) -> Option<&Candle> {
+        self.series
+            .iter()
+            .find(|candle_series| candle_series.interval == interval)
+            .and_then(|candle_series| candle_series.current.as_ref())
+    }
+
+    pub fn drain_closed(&mut self) -> Vec<Candle> {
+        let mut closed_candles = Vec::new();
+
+        for candle_series in self.series.iter_mut() {
+            closed_candles.append(&mut mem::take(&mut candle_series.closed));
+        }
+
+        closed_candles
+    }
+}
+
+impl PartialEq for CandleInterval {
+    fn eq(&self, other: &Self) -> bool {
+        mem::discriminant(self) == mem::discriminant(other)
+    }
+}
+
+impl Eq for CandleInterval {}
+
+#[cfg(test)]
+mod unit_test {
+    use super::super::test_support::*;
+    use super::super::*;
+
+    const ONE_SECOND: UTCNanoSeconds = 1_000_000_000;
+
+    fn execute_at(the_orderbooks: &mut Orderbooks, timestamp: UTCNanoSeconds, order_message: &OrderMessage) {
+        the_orderbooks.clock.set(timestamp);
+        the_orderbooks.execute_order(order_message);
+    }
+
+    #[test]
+    fn test_candle_ohlcv_and_vwap() {
+        let mut the_orderbooks = Orderbooks::default();
+        the_orderbooks.attach_candles(&[CandleInterval::OneSecond, CandleInterval::OneMinute]);
+        let start = 60 * ONE_SECOND;
+        let timed_orders = [
+            (start, limit_order(OrderSide::Ask, 10, 100)),
+            (start, limit_order(OrderSide::Ask, 10, 110)),
+            (start + 1, limit_order(OrderSide::Bid, 15, 110)),
+            (start + 2, limit_order(OrderSide::Bid, 5, 105)),
+            (start + 3, limit_order(OrderSide::Ask, 5, 105)),
+        ];
+
+        for (timestamp, order_message) in timed_orders.iter() {
+            execute_at(&mut the_orderbooks, *timestamp, order_message);
+        }
+
+        let current_candle = the_orderbooks
+            .candles()
+            .unwrap()
+            .current(CandleInterval::OneSecond)
+            .unwrap();
+
+        assert_eq!(current_candle.open_time, start);
+        assert_eq!(current_candle.open, 100);
+        assert_eq!(current_candle.high, 110);
+        assert_eq!(current_candle.low, 100);
+        assert_eq!(current_candle.close, 105);
+        assert_eq!(current_candle.volume, 20);
+        assert_eq!(current_candle.quote_volume, 1000 + 550 + 525);
+        assert_eq!(current_candle.vwap(), Some(104));
+        assert_eq!(current_candle.trade_count, 3);
+        assert!(the_orderbooks.drain_closed_candles().is_empty());
+    }
+
+    #[test]
+    fn test_candle_gap_fill_on_engine_clock() {
+        let mut the_orderbooks = Orderbooks::default();
+        the_orderbooks.attach_candles(&[CandleInterval::OneSecond]);
+        let ask_order = limit_order(OrderSide::Ask, 10, 100);
+        let bid_order_0 = limit_order(OrderSide::Bid, 4, 100);
+        let bid_order_1 = limit_order(OrderSide::Bid, 2, 100);
+        execute_at(&mut the_orderbooks, 0, &ask_order);
+        execute_at(&mut the_orderbooks, 500, &bid_order_0);
+        the_orderbooks.clock.set(ONE_SECOND - 1);
+
+        assert!(the_orderbooks.drain_closed_candles().is_empty());
+
+        execute_at(&mut the_orderbooks, 3 * ONE_SECOND + 7, &bid_order_1);
+        let closed_candles = the_orderbooks.drain_closed_candles();
+
+        assert_eq!(closed_candles.len(), 3);
+        assert_eq!(closed_candles[0].trade_count, 1);
+        assert_eq!(closed_candles[0].volume, 4);
+        assert_eq!(closed_candles[1].open_time, ONE_SECOND);
+        assert_eq!(closed_candles[1].trade_count, 0);
+        assert_eq!(closed_candles[1].open, 100);
+        assert_eq!(closed_candles[1].close, 100);
+        assert_eq!(closed_candles[1].vwap(), None);
+        assert_eq!(closed_candles[2].open_time, 2 * ONE_SECOND);
+
+        the_orderbooks.clock.set(the_orderbooks.now() + 2 * ONE_SECOND);
+        let closed_candles = the_orderbooks.drain_closed_candles();
+
+        assert_eq!(closed_candles.len(), 2);
+        assert_eq!(closed_candles[0].open_time, 3 * ONE_SECOND);
+        assert_eq!(closed_candles[0].volume, 2);
+        assert_eq!(closed_candles[1].open_time, 4 * ONE_SECOND);
+        assert_eq!(closed_candles[1].volume, 0);
+    }
+
+    #[test]
+    fn test_candle_quote_volume_does_not_overflow() {
+        let price = OrderPrice::MAX / 2;
+        let mut candle = Candle::new(CandleInterval::OneMinute, 0, price);
+        let trade = Trade {
+            timestamp: 0,
+            price,
+            volume: 4,
+            aggressor_id: OrderId::nil(),
+            aggressor_side: OrderSide::Bid,
+            resting_id: OrderId::nil(),
+            resting_remaining_volume: 0,
+            aggressor_account: None,
+            resting_account: None,
+        };
+        candle.add_trade(&trade);
+        candle.add_trade(&trade);
+
+        assert_eq!(candle.quote_volume, price as QuoteVolume * 8);
+        assert_eq!(candle.vwap(), Some(price));
+        assert_eq!(trade.quote(), OrderQuote::MAX);
+    }
+}
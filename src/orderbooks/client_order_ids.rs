@@ -0,0 +1,168 @@
+use super::orders::*;
+use super::session::NANOSECONDS_PER_DAY;
+use std::collections::{HashMap, VecDeque};
+
+// Client order ids are unique per account, orders without one share a scope.
+pub type ClientOrderKey = (Option<AccountId>, ClientOrderId);
+
+// Maps client order ids to the orders that used them last. An id is taken while
+// its order rests and for `window` nanoseconds after it was used. Entries are
+// checked against the book when read, so orders leaving it need no bookkeeping.
+#[derive(Clone, Debug)]
+pub struct ClientOrderIds {
+    pub(crate) window: UTCNanoSeconds,
+    pub(crate) orders: HashMap<ClientOrderKey, (OrderId, UTCNanoSeconds)>,
+    pub(crate) used_at: VecDeque<(UTCNanoSeconds, ClientOrderKey)>,
+}
+
+impl Default for ClientOrderIds {
+    fn default() -> ClientOrderIds {
+        ClientOrderIds {
+            window: NANOSECONDS_PER_DAY,
+            orders: HashMap::new(),
+            used_at: VecDeque::new(),
+        }
+    }
+}
+
+impl ClientOrderIds {
+    pub(crate) fn get(&self, key: &ClientOrderKey) -> Option<OrderId> {
+        self.orders.get(key).map(|(order_id, _)| *order_id)
+    }
+
+    pub(crate) fn is_taken<F>(&self, key: &ClientOrderKey, now: UTCNanoSeconds, is_resting: F) -> bool
+    where
+        F: Fn(&OrderId) -> bool,
+    {
+        self.orders
+            .get(key)
+            .is_some_and(|(order_id, used_at)| now < used_at.saturating_add(self.window) || is_resting(order_id))
+    }
+
+    pub(crate) fn record(&mut self, key: ClientOrderKey, order_id: OrderId, now: UTCNanoSeconds) {
+        self.used_at.push_back((now, key.clone()));
+        self.orders.insert(key, (order_id, now));
+    }
+
+    // Drops the ids that left the window, unless they were used again since or
+    // their order still rests.
+    pub(crate) fn prune<F>(&mut self, now: UTCNanoSeconds, is_resting: F)
+    where
+        F: Fn(&OrderId) -> bool,
+    {
+        while let Some((used_at, _)) = self.used_at.front() {
+            if now < used_at.saturating_add(self.window) {
+                break;
+            }
+
+            let (used_at, key) = self.used_at.pop_front().unwrap();
+
+            if let Some((order_id, last_used_at)) = self.orders.get(&key) {
+                if *last_used_at == used_at && !is_resting(order_id) {
+                    self.orders.remove(&key);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod unit_test {
+    use super::super::test_support::*;
+    use super::super::*;
+
+    #[test]
+    fn test_client_order_ids() {
+        let mut the_orderbooks = Orderbooks::default();
+        the_orderbooks.set_clock(EngineClock::Manual(1_000));
+        the_orderbooks.set_client_order_id_window(500);
+        let client_order = |client_order_id: &str, account: AccountId, volume: OrderVolume| OrderMessage {
+            account: Some(account),
+            client_order_id: Some(client_order_id.to_string()),
+            ..limit_order(OrderSide::Bid, volume, 100)
+        };
+        let bid_order = client_order("a", 1, 10);
+
+        the_orderbooks.try_execute_order(&bid_order).unwrap();
+
+        assert_eq!(
+            the_orderbooks.try_execute_order(&client_order("a", 1, 10)).unwrap_err(),
+            OrderRejection::DuplicateClientOrderId
+        );
+        assert!(the_orderbooks.validate(&client_order("a", 2, 10)).is_ok());
+        assert_eq!(the_orderbooks.lookup_client_order_id(Some(1), "a"), Some(bid_order.id));
+
+        let replace_order = OrderMessage {
+            r#type: OrderType::Replace,
+            target_client_order_id: Some("a".to_string()),
+            ..client_order("b", 1, 5)
+        };
+        the_orderbooks.try_execute_order(&replace_order).unwrap();
+
+        assert_eq!(the_orderbooks.lookup_client_order_id(Some(1), "a"), None);
+        assert_eq!(
+            the_orderbooks.lookup_client_order_id(Some(1), "b"),
+            Some(replace_order.id)
+        );
+
+        // Past the window "a" is free again, "b" stays taken while it rests.
+        the_orderbooks.set_clock(EngineClock::Manual(1_500));
+
+        assert!(the_orderbooks.validate(&client_order("a", 1, 10)).is_ok());
+        assert_eq!(
+            the_orderbooks.validate(&client_order("b", 1, 10)),
+            Err(OrderRejection::DuplicateClientOrderId)
+        );
+
+        let cancel_order = OrderMessage {
+            r#type: OrderType::Cancel,
+            account: Some(1),
+            target_client_order_id: Some("b".to_string()),
+            ..Default::default()
+        };
+        let events = the_orderbooks.try_execute_order(&cancel_order).unwrap();
+
+        assert_eq!(events[&replace_order.id][0].r#type, OrderEventType::Cancelled);
+        assert!(the_orderbooks.validate(&client_order("b", 1, 10)).is_ok());
+
+        the_orderbooks.set_clock(EngineClock::Manual(2_000));
+        the_orderbooks.try_execute_order(&client_order("c", 1, 10)).unwrap();
+
+        assert_eq!(the_orderbooks.client_order_ids.orders.len(), 1);
+    }
+
+    #[test]
+    fn test_only_executed_orders_take_their_client_order_id() {
+        let mut the_orderbooks = Orderbooks::default();
+        the_orderbooks.set_clock(EngineClock::Manual(1_000));
+        the_orderbooks.set_price_bands(PriceBands {
+            reference_price: Some(100),
+            static_band: Some(100),
+            ..Default::default()
+        });
+        the_orderbooks.execute_order(&limit_order(OrderSide::Ask, 5, 100));
+        the_orderbooks.execute_order(&limit_order(OrderSide::Ask, 5, 105));
+        let market_bid = |volume: OrderVolume| OrderMessage {
+            client_order_id: Some("a".to_string()),
+            ..market_order(OrderSide::Bid, volume)
+        };
+        let breaching_bid = market_bid(10);
+        let events = the_orderbooks.execute_order(&breaching_bid);
+
+        assert_eq!(events[&breaching_bid.id][0].r#type, OrderEventType::Rejected);
+        assert!(the_orderbooks.validate(&market_bid(5)).is_ok());
+
+        the_orderbooks.try_execute_order(&market_bid(5)).unwrap();
+
+        // Filled orders keep their id for a day by default.
+        assert_eq!(
+            the_orderbooks.validate(&market_bid(5)),
+            Err(OrderRejection::DuplicateClientOrderId)
+        );
+
+        the_orderbooks.set_clock(EngineClock::Manual(1_000 + NANOSECONDS_PER_DAY));
+        the_orderbooks.execute_order(&limit_order(OrderSide::Bid, 1, 90));
+
+        assert!(the_orderbooks.client_order_ids.orders.is_empty());
+    }
+}
@@ -0,0 +1,22 @@
+use super::orders::UTCNanoSeconds;
+use chrono::Utc;
+
+#[derive(Copy, Clone, Debug, Default)]
+pub enum EngineClock {
+    #[default]
+    System,
+    Manual(UTCNanoSeconds),
+}
+
+impl EngineClock {
+    pub fn now(&self) -> UTCNanoSeconds {
+        match self {
+            EngineClock::System => Utc::now().timestamp_nanos_opt().unwrap(),
+            EngineClock::Manual(timestamp) => *timestamp,
+        }
+    }
+
+    pub fn set(&mut self, timestamp: UTCNanoSeconds) {
+        *self = EngineClock::Manual(timestamp);
+    }
+}
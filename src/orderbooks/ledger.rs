@@ -0,0 +1,362 @@
+use super::orders::*;
+use super::trades::Trade;
+use super::OrderRejection;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Funds {
+    pub total: u64,
+    pub reserved: u64,
+}
+
+// Base is the traded asset, counted in volume. Quote is what it is paid with,
+// counted in price times volume.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Balance {
+    pub base: Funds,
+    pub quote: Funds,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct Reservation {
+    pub account: AccountId,
+    pub side: OrderSide,
+    pub price: OrderPrice,
+    pub amount: u64,
+}
+
+// Balances of the accounts trading one instrument. Resting bids reserve
+// `price * volume` of quote and resting asks their volume of base, until they
+// trade, are cancelled or expire. Orders without an account are not funded.
+// The ledger lives inside its `Orderbooks` and knows no other instrument: the
+// quote of an account must not be deposited into the ledgers of two books, as
+// each would let it be spent in full.
+#[derive(Clone, Debug, Default)]
+pub struct Ledger {
+    pub(crate) balances: HashMap<AccountId, Balance>,
+    pub(crate) reservations: HashMap<Uuid, Reservation>,
+}
+
+impl Funds {
+    pub fn available(&self) -> u64 {
+        self.total.saturating_sub(self.reserved)
+    }
+}
+
+impl Balance {
+    fn funds_mut(&mut self, side: OrderSide) -> &mut Funds {
+        match side {
+            OrderSide::Bid => &mut self.quote,
+            _ => &mut self.base,
+        }
+    }
+}
+
+pub(crate) fn required_funds(side: OrderSide, price: OrderPrice, volume: OrderVolume) -> u64 {
+    match side {
+        OrderSide::Bid => price.saturating_mul(volume),
+        _ => volume,
+    }
+}
+
+impl Ledger {
+    pub fn balance(&self, account: AccountId) -> Balance {
+        self.balances.get(&account).copied().unwrap_or_default()
+    }
+
+    pub fn deposit(&mut self, account: AccountId, base: u64, quote: u64) {
+        let balance = self.balances.entry(account).or_default();
+        balance.base.total = balance.base.total.saturating_add(base);
+        balance.quote.total = balance.quote.total.saturating_add(quote);
+    }
+
+    // Reserved funds cannot be withdrawn.
+    pub fn withdraw(&mut self, account: AccountId, base: u64, quote: u64) -> Result<(), OrderRejection> {
+        let balance = self.balances.entry(account).or_default();
+
+        if balance.base.available() < base || balance.quote.available() < quote {
+            return Err(OrderRejection::InsufficientFunds);
+        }
+
+        balance.base.total -= base;
+        balance.quote.total -= quote;
+        Ok(())
+    }
+
+    pub(crate) fn reserved_for(&self, order_id: &Uuid) -> Option<&Reservation> {
+        self.reservations.get(order_id)
+    }
+
+    // `released` is what a replaced order frees on the same side. Market bids
+    // only need some quote, they are capped by what is available.
+    pub(crate) fn check(
+        &self,
+        account: AccountId,
+        order_message: &OrderMessage,
+        released: u64,
+    ) -> Result<(), OrderRejection> {
+        let balance = self.balance(account);
+        let volume = order_message.volume.unwrap_or(0);
+        let (available, required) = match (order_message.r#type, order_message.side) {
+            (OrderType::Cancel, _) => return Ok(()),
+            (OrderType::Market, OrderSide::Bid) => (balance.quote.available(), 1),
+            (OrderType::Market, _) => (balance.base.available(), volume),
+            (_, side) => {
+                let funds = if side == OrderSide::Bid {
+                    balance.quote
+                } else {
+                    balance.base
+                };
+                let required = required_funds(side, order_message.price.unwrap_or(0), volume);
+                (funds.available().saturating_add(released), required)
+            }
+        };
+
+        if available < required {
+            return Err(OrderRejection::InsufficientFunds);
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn reserve(
+        &mut self,
+        order_id: Uuid,
+        account: AccountId,
+        side: OrderSide,
+        price: OrderPrice,
+        volume: OrderVolume,
+    ) {
+        let amount = required_funds(side, price, volume);
+        let funds = self.balances.entry(account).or_default().funds_mut(side);
+        funds.reserved = funds.reserved.saturating_add(amount);
+        self.reservations.insert(
+            order_id,
+            Reservation {
+                account,
+                side,
+                price,
+                amount,
+            },
+        );
+    }
+
+    pub(crate) fn release(&mut self, order_id: &Uuid) {
+        if let Some(reservation) = self.reservations.remove(order_id) {
+            let funds = self
+                .balances
+                .entry(reservation.account)
+                .or_default()
+                .funds_mut(reservation.side);
+            funds.reserved = funds.reserved.saturating_sub(reservation.amount);
+        }
+    }
+
+    // Moves base to the buyer and quote to the seller at the traded price, and
+    // shrinks the reservations of the orders that traded at their own price.
+    // Auction trades report the bid as the aggressor.
+    pub(crate) fn settle(&mut self, trades: &[Trade], aggressor_account: Option<AccountId>) {
+        for trade in trades {
+            let account = |ledger: &Ledger, order_id: &Uuid| {
+                ledger.reservations.get(order_id).map(|reservation| reservation.account)
+            };
+            let aggressor_account = account(self, &trade.aggressor_id).or(aggressor_account);
+            let resting_account = account(self, &trade.resting_id);
+            let (buyer, seller) = match trade.aggressor_side {
+                OrderSide::Ask => (resting_account, aggressor_account),
+                _ => (aggressor_account, resting_account),
+            };
+            let quote = trade.price.saturating_mul(trade.volume);
+
+            if let Some(buyer) = buyer {
+                let balance = self.balances.entry(buyer).or_default();
+                balance.quote.total = balance.quote.total.saturating_sub(quote);
+                balance.base.total = balance.base.total.saturating_add(trade.volume);
+            }
+
+            if let Some(seller) = seller {
+                let balance = self.balances.entry(seller).or_default();
+                balance.base.total = balance.base.total.saturating_sub(trade.volume);
+                balance.quote.total = balance.quote.total.saturating_add(quote);
+            }
+
+            for order_id in [trade.aggressor_id, trade.resting_id].iter() {
+                self.consume(order_id, trade.volume);
+            }
+        }
+    }
+
+    fn consume(&mut self, order_id: &Uuid, volume: OrderVolume) {
+        let reservation = match self.reservations.get_mut(order_id) {
+            Some(reservation) => reservation,
+            None => return,
+        };
+        let amount = required_funds(reservation.side, reservation.price, volume).min(reservation.amount);
+        reservation.amount -= amount;
+        let (account, side, exhausted) = (reservation.account, reservation.side, reservation.amount == 0);
+        let funds = self.balances.entry(account).or_default().funds_mut(side);
+        funds.reserved = funds.reserved.saturating_sub(amount);
+
+        if exhausted {
+            self.reservations.remove(order_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod unit_test {
+    use super::super::test_support::*;
+    use super::super::*;
+
+    fn funded(account: AccountId, order_message: OrderMessage) -> OrderMessage {
+        OrderMessage {
+            account: Some(account),
+            ..order_message
+        }
+    }
+
+    fn funded_orderbooks() -> Orderbooks {
+        let mut the_orderbooks = Orderbooks::default();
+        let mut ledger = Ledger::default();
+        ledger.deposit(1, 0, 1_000);
+        ledger.deposit(2, 20, 0);
+        the_orderbooks.set_ledger(ledger);
+        the_orderbooks
+    }
+
+    #[test]
+    fn test_ledger_rejects_unfunded_orders() {
+        let the_orderbooks = funded_orderbooks();
+
+        assert_eq!(
+            the_orderbooks.validate(&funded(1, limit_order(OrderSide::Bid, 11, 100))),
+            Err(OrderRejection::InsufficientFunds)
+        );
+        assert_eq!(
+            the_orderbooks.validate(&funded(2, market_order(OrderSide::Ask, 21))),
+            Err(OrderRejection::InsufficientFunds)
+        );
+        assert_eq!(
+            the_orderbooks.validate(&funded(2, market_order(OrderSide::Bid, 1))),
+            Err(OrderRejection::InsufficientFunds)
+        );
+        assert!(the_orderbooks
+            .validate(&funded(1, limit_order(OrderSide::Bid, 10, 100)))
+            .is_ok());
+        assert!(the_orderbooks.validate(&limit_order(OrderSide::Bid, 11, 100)).is_ok());
+    }
+
+    #[test]
+    fn test_execute_order_rejects_unfunded_orders() {
+        let mut the_orderbooks = funded_orderbooks();
+        let bid_order = funded(1, limit_order(OrderSide::Bid, 11, 100));
+        let events = the_orderbooks.execute_order(&bid_order);
+
+        assert_eq!(events[&bid_order.id].len(), 1);
+        assert_eq!(events[&bid_order.id][0].r#type, OrderEventType::Rejected);
+        assert!(!the_orderbooks.contains(&bid_order.id));
+        assert_eq!(the_orderbooks.ledger().unwrap().balance(1).quote.reserved, 0);
+    }
+
+    #[test]
+    fn test_ledger_reserves_resting_orders() {
+        let mut the_orderbooks = funded_orderbooks();
+
+        the_orderbooks
+            .try_execute_order(&funded(1, limit_order(OrderSide::Bid, 10, 100)))
+            .unwrap();
+        the_orderbooks
+            .try_execute_order(&funded(2, limit_order(OrderSide::Ask, 5, 110)))
+            .unwrap();
+        let ledger = the_orderbooks.ledger_mut().unwrap();
+
+        assert_eq!(
+            ledger.balance(1).quote,
+            Funds {
+                total: 1_000,
+                reserved: 1_000
+            }
+        );
+        assert_eq!(ledger.balance(2).base, Funds { total: 20, reserved: 5 });
+        assert_eq!(ledger.withdraw(1, 0, 1), Err(OrderRejection::InsufficientFunds));
+        assert_eq!(ledger.withdraw(2, 15, 0), Ok(()));
+        assert_eq!(ledger.balance(2).base, Funds { total: 5, reserved: 5 });
+    }
+
+    #[test]
+    fn test_ledger_releases_replaced_and_cancelled_orders() {
+        let mut the_orderbooks = funded_orderbooks();
+        let bid_order = funded(1, limit_order(OrderSide::Bid, 10, 100));
+        let replace_order = OrderMessage {
+            r#type: OrderType::Replace,
+            target_id: Some(bid_order.id),
+            ..funded(1, limit_order(OrderSide::Bid, 6, 50))
+        };
+        let cancel_order = OrderMessage {
+            r#type: OrderType::Cancel,
+            target_id: Some(replace_order.id),
+            account: Some(1),
+            ..Default::default()
+        };
+
+        the_orderbooks.try_execute_order(&bid_order).unwrap();
+        the_orderbooks.try_execute_order(&replace_order).unwrap();
+
+        assert_eq!(the_orderbooks.ledger().unwrap().balance(1).quote.reserved, 300);
+
+        the_orderbooks.try_execute_order(&cancel_order).unwrap();
+
+        assert_eq!(
+            the_orderbooks.ledger().unwrap().balance(1).quote,
+            Funds {
+                total: 1_000,
+                reserved: 0
+            }
+        );
+    }
+
+    #[test]
+    fn test_ledger_settles_trades_at_the_traded_price() {
+        let mut the_orderbooks = funded_orderbooks();
+
+        the_orderbooks
+            .try_execute_order(&funded(1, limit_order(OrderSide::Bid, 10, 100)))
+            .unwrap();
+        the_orderbooks
+            .try_execute_order(&funded(2, limit_order(OrderSide::Ask, 4, 90)))
+            .unwrap();
+        let ledger = the_orderbooks.ledger().unwrap();
+
+        assert_eq!(
+            ledger.balance(1).quote,
+            Funds {
+                total: 600,
+                reserved: 600
+            }
+        );
+        assert_eq!(ledger.balance(1).base.total, 4);
+        assert_eq!(ledger.balance(2).base, Funds { total: 16, reserved: 0 });
+        assert_eq!(ledger.balance(2).quote.total, 400);
+    }
+
+    #[test]
+    fn test_ledger_caps_market_bids_by_available_quote() {
+        let mut the_orderbooks = funded_orderbooks();
+        the_orderbooks.ledger_mut().unwrap().withdraw(1, 0, 400).unwrap();
+        let market_bid = funded(1, market_order(OrderSide::Bid, 10));
+
+        the_orderbooks
+            .try_execute_order(&funded(2, limit_order(OrderSide::Ask, 10, 100)))
+            .unwrap();
+        let events = the_orderbooks.try_execute_order(&market_bid).unwrap();
+        let ledger = the_orderbooks.ledger().unwrap();
+
+        assert_eq!(events[&market_bid.id].last().unwrap().remaining_volume, Some(4));
+        assert_eq!(ledger.balance(1).quote, Funds { total: 0, reserved: 0 });
+        assert_eq!(ledger.balance(1).base.total, 6);
+        assert_eq!(ledger.balance(2).base, Funds { total: 14, reserved: 4 });
+        assert_eq!(ledger.balance(2).quote.total, 600);
+    }
+}
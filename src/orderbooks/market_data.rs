@@ -0,0 +1,340 @@
+use super::orders::*;
+use super::{Orderbook, PriceLevel};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::mem;
+
+pub type SequenceNumber = u64;
+pub type DepthChecksum = u32;
+
+pub const DEPTH_CHECKSUM_LEVELS: usize = 25;
+
+// Updates wait for `drain_updates`. A publisher nobody drains drops the oldest
+// half past this many, so late readers see a sequence gap and resync from a
+// snapshot instead of the buffer growing without bound.
+pub const MAX_PENDING_DEPTH_UPDATES: usize = 65_536;
+
+#[derive(Copy, Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[repr(u8)]
+pub enum DepthUpdateType {
+    New = 0,
+    Changed = 1,
+    Deleted = 2,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[repr(align(8))]
+pub struct DepthUpdate {
+    pub sequence: SequenceNumber,  // 8 bytes
+    pub timestamp: UTCNanoSeconds, // 8 bytes
+    pub side: OrderSide,           // 1 byte
+    pub r#type: DepthUpdateType,   // 1 byte
+    pub price: PriceLevel,         // 8 bytes
+    pub volume: OrderVolume,       // 8 bytes
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct DepthSnapshot {
+    pub sequence: SequenceNumber,
+    pub checksum: DepthChecksum,
+    pub bids: Vec<(PriceLevel, OrderVolume)>,
+    pub asks: Vec<(PriceLevel, OrderVolume)>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct MarketDepth {
+    pub sequence: SequenceNumber,
+    pub checksum: DepthChecksum,
+    pub bids: BTreeMap<PriceLevel, OrderVolume>,
+    pub asks: BTreeMap<PriceLevel, OrderVolume>,
+    pub updates: Vec<DepthUpdate>,
+}
+
+impl MarketDepth {
+    pub fn from_orderbooks(bids: &Orderbook, asks: &Orderbook) -> MarketDepth {
+        let mut market_depth = MarketDepth::default();
+
+        for price_level in bids.orders.keys() {
+            market_depth.bids.insert(*price_level, bids.volume(*price_level));
+        }
+
+        for price_level in asks.orders.keys() {
+            market_depth.asks.insert(*price_level, asks.volume(*price_level));
+        }
+
+        market_depth.update_checksum();
+        market_depth
+    }
+
+    pub fn from_snapshot(snapshot: &DepthSnapshot) -> MarketDepth {
+        MarketDepth {
+            sequence: snapshot.sequence,
+            checksum: snapshot.checksum,
+            bids: snapshot.bids.iter().cloned().collect(),
+            asks: snapshot.asks.iter().cloned().collect(),
+            updates: Vec::new(),
+        }
+    }
+
+    pub fn publish(&mut self, side: OrderSide, price: PriceLevel, volume: OrderVolume, timestamp: UTCNanoSeconds) {
+        let levels = match side {
+            OrderSide::Bid => &mut self.bids,
+            OrderSide::Ask => &mut self.asks,
+            OrderSide::NoSide => panic!("Attempt to publish depth without side: {}", price),
+        };
+        let update_type = match (levels.get(&price), volume) {
+            (None, 0) => return,
+            (None, _) => DepthUpdateType::New,
+            (Some(_), 0) => DepthUpdateType::Deleted,
+            (Some(previous_volume), _) if *previous_volume == volume => return,
+            (Some(_), _) => DepthUpdateType::Changed,
+        };
+
+        if volume == 0 {
+            levels.remove(&price);
+        } else {
+            levels.insert(price, volume);
+        }
+
+        if self.updates.len() >= MAX_PENDING_DEPTH_UPDATES {
+            self.updates.drain(..MAX_PENDING_DEPTH_UPDATES / 2);
+        }
+
+        self.sequence += 1;
+        self.updates.push(DepthUpdate {
+            sequence: self.sequence,
+            timestamp,
+            side,
+            r#type: update_type,
+            price,
+            volume,
+        });
+    }
+
+    pub fn apply(&mut self, update: &DepthUpdate) -> bool {
+        if update.sequence <= self.sequence {
+            return true;
+        }
+
+        if update.sequence != self.sequence + 1 {
+            return false;
+        }
+
+        let levels = match update.side {
+            OrderSide::Bid => &mut self.bids,
+            OrderSide::Ask => &mut self.asks,
+            OrderSide::NoSide => return false,
+        };
+
+        if update.r#type == DepthUpdateType::Deleted {
+            levels.remove(&update.price);
+        } else {
+            levels.insert(update.price, update.volume);
+        }
+
+        self.sequence = update.sequence;
+
+        true
+    }
+
+    // Interleaves the best levels as "bid_price:bid_volume:ask_price:ask_volume:...",
+    // skipping the side that has run out of levels.
+    pub fn checksum_payload(&self, max_levels: usize) -> String {
+        let mut bid_levels = self.bids.iter().rev();
+        let mut ask_levels = self.asks.iter();
+        let mut payload = Vec::new();
+
+        for _ in 0..max_levels {
+            let bid_level = bid_levels.next();
+            let ask_level = ask_levels.next();
+
+            if bid_level.is_none() && ask_level.is_none() {
+                break;
+            }
+
+            for (price, volume) in bid_level.into_iter().chain(ask_level) {
+                payload.push(price.to_string());
+                payload.push(volume.to_string());
+            }
+        }
+
+        payload.join(":")
+    }
+
+    pub fn compute_checksum(&self, max_levels: usize) -> DepthChecksum {
+        crc32fast::hash(self.checksum_payload(max_levels).as_bytes())
+    }
+
+    pub fn update_checksum(&mut self) {
+        self.checksum = self.compute_checksum(DEPTH_CHECKSUM_LEVELS);
+    }
+
+    pub fn drain_updates(&mut self) -> Vec<DepthUpdate> {
+        mem::take(&mut self.updates)
+    }
+
+    pub fn snapshot(&self, max_levels: usize) -> DepthSnapshot {
+        DepthSnapshot {
+            sequence: self.sequence,
+            checksum: self.checksum,
+            bids: self.bids.iter().rev().take(max_levels).map(|(p, v)| (*p, *v)).collect(),
+            asks: self.asks.iter().take(max_levels).map(|(p, v)| (*p, *v)).collect(),
+        }
+    }
+}
+
+impl PartialEq for DepthUpdateType {
+    fn eq(&self, other: &Self) -> bool {
+        mem::discriminant(self) == mem::discriminant(other)
+    }
+}
+
+impl Eq for DepthUpdateType {}
+
+#[cfg(test)]
+mod unit_test {
+    use super::super::test_support::*;
+    use super::super::*;
+
+    #[test]
+    fn test_depth_updates_new_changed_deleted() {
+        let mut the_orderbooks = Orderbooks::default();
+        the_orderbooks.attach_depth_publisher();
+        the_orderbooks.execute_order(&limit_order(OrderSide::Ask, 10, 100));
+        the_orderbooks.execute_order(&limit_order(OrderSide::Ask, 5, 100));
+        the_orderbooks.execute_order(&limit_order(OrderSide::Bid, 15, 100));
+        let updates = the_orderbooks.drain_depth_updates();
+
+        assert_eq!(updates.len(), 3);
+        assert_eq!(updates[0].r#type, DepthUpdateType::New);
+        assert_eq!(updates[0].volume, 10);
+        assert_eq!(updates[1].r#type, DepthUpdateType::Changed);
+        assert_eq!(updates[1].volume, 15);
+        assert_eq!(updates[2].r#type, DepthUpdateType::Deleted);
+        assert_eq!(updates[2].volume, 0);
+        assert_eq!(updates[2].sequence, 3);
+        assert!(the_orderbooks.drain_depth_updates().is_empty());
+    }
+
+    #[test]
+    fn test_rebuild_depth_from_snapshot_and_updates() {
+        let mut the_orderbooks = Orderbooks::default();
+        the_orderbooks.execute_order(&limit_order(OrderSide::Bid, 10, 90));
+        the_orderbooks.execute_order(&limit_order(OrderSide::Ask, 10, 110));
+        the_orderbooks.attach_depth_publisher();
+        let mut client_depth = MarketDepth::from_snapshot(&the_orderbooks.depth_snapshot(usize::MAX));
+        the_orderbooks.execute_order(&limit_order(OrderSide::Bid, 7, 95));
+        the_orderbooks.execute_order(&limit_order(OrderSide::Ask, 12, 90));
+        the_orderbooks.execute_order(&OrderMessage {
+            side: OrderSide::Ask,
+            r#type: OrderType::Market,
+            volume: Some(3),
+            ..Default::default()
+        });
+
+        for update in the_orderbooks.drain_depth_updates() {
+            assert!(client_depth.apply(&update));
+        }
+
+        let engine_snapshot = the_orderbooks.depth_snapshot(usize::MAX);
+        let client_snapshot = client_depth.snapshot(usize::MAX);
+
+        assert_eq!(client_snapshot.sequence, engine_snapshot.sequence);
+        assert_eq!(client_snapshot.bids, engine_snapshot.bids);
+        assert_eq!(client_snapshot.asks, engine_snapshot.asks);
+        assert_eq!(client_snapshot.bids, vec![(90, 2)]);
+        assert_eq!(client_snapshot.asks, vec![(110, 10)]);
+    }
+
+    #[test]
+    fn test_depth_update_gap_detection() {
+        let mut the_orderbooks = Orderbooks::default();
+        the_orderbooks.attach_depth_publisher();
+        let mut client_depth = MarketDepth::from_snapshot(&the_orderbooks.depth_snapshot(10));
+        the_orderbooks.execute_order(&limit_order(OrderSide::Bid, 10, 90));
+        the_orderbooks.execute_order(&limit_order(OrderSide::Bid, 10, 91));
+        let updates = the_orderbooks.drain_depth_updates();
+
+        assert!(!client_depth.apply(&updates[1]));
+        assert!(client_depth.apply(&updates[0]));
+        assert!(client_depth.apply(&updates[1]));
+        assert_eq!(client_depth.sequence, 2);
+    }
+
+    #[test]
+    fn test_undrained_depth_updates_are_capped() {
+        let mut market_depth = MarketDepth::default();
+
+        for price in 1..=MAX_PENDING_DEPTH_UPDATES as PriceLevel + 1 {
+            market_depth.publish(OrderSide::Bid, price, 1, 0);
+        }
+
+        let updates = market_depth.drain_updates();
+        let mut client_depth = MarketDepth::default();
+
+        assert_eq!(updates.len(), MAX_PENDING_DEPTH_UPDATES / 2 + 1);
+        assert_eq!(
+            updates.last().unwrap().sequence,
+            MAX_PENDING_DEPTH_UPDATES as SequenceNumber + 1
+        );
+        assert!(!client_depth.apply(&updates[0]));
+    }
+
+    fn parse_levels(levels: &str) -> Vec<(PriceLevel, OrderVolume)> {
+        if levels == "-" {
+            return Vec::new();
+        }
+
+        levels
+            .split(',')
+            .map(|level| {
+                let mut price_volume = level.split(':').map(|value| value.parse().unwrap());
+                (price_volume.next().unwrap(), price_volume.next().unwrap())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_depth_checksum_vectors() {
+        let test_vectors = include_str!("../../test_vectors/depth_checksum.tsv");
+
+        for test_vector in test_vectors.lines().filter(|line| !line.starts_with('#')) {
+            let columns: Vec<&str> = test_vector.split('\t').collect();
+            let market_depth = MarketDepth::from_snapshot(&DepthSnapshot {
+                bids: parse_levels(columns[0]),
+                asks: parse_levels(columns[1]),
+                ..Default::default()
+            });
+
+            let payload = match columns[2] {
+                "-" => "",
+                payload => payload,
+            };
+
+            assert_eq!(market_depth.checksum_payload(DEPTH_CHECKSUM_LEVELS), payload);
+            assert_eq!(
+                market_depth.compute_checksum(DEPTH_CHECKSUM_LEVELS),
+                columns[3].parse::<DepthChecksum>().unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_depth_checksum_after_execute_order() {
+        let mut the_orderbooks = Orderbooks::default();
+        the_orderbooks.attach_depth_publisher();
+        let mut client_depth = MarketDepth::from_snapshot(&the_orderbooks.depth_snapshot(DEPTH_CHECKSUM_LEVELS));
+        the_orderbooks.execute_order(&limit_order(OrderSide::Bid, 5, 100));
+        the_orderbooks.execute_order(&limit_order(OrderSide::Ask, 7, 101));
+
+        for update in the_orderbooks.drain_depth_updates() {
+            assert!(client_depth.apply(&update));
+        }
+
+        let snapshot = the_orderbooks.depth_snapshot(1);
+
+        assert_eq!(snapshot.checksum, 1_539_382_039);
+        assert_eq!(client_depth.compute_checksum(DEPTH_CHECKSUM_LEVELS), snapshot.checksum);
+    }
+}
@@ -0,0 +1,112 @@
+use super::orders::*;
+use super::PriceLevel;
+use serde::{Deserialize, Serialize};
+use std::ops::RangeInclusive;
+
+// Picks the resting orders `Orderbooks::mass_cancel` removes. Every filter left
+// out matches everything, so the default cancels the whole book.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct MassCancel {
+    pub side: Option<OrderSide>,
+    pub min_price: Option<OrderPrice>,
+    pub max_price: Option<OrderPrice>,
+    pub account: Option<AccountId>,
+    pub session_id: Option<SessionId>,
+}
+
+impl MassCancel {
+    pub fn all() -> MassCancel {
+        MassCancel::default()
+    }
+
+    pub(crate) fn covers_side(&self, side: OrderSide) -> bool {
+        self.side
+            .is_none_or(|filter_side| filter_side == side || filter_side == OrderSide::NoSide)
+    }
+
+    // `None` for an empty range, which `BTreeMap::range` would panic on.
+    pub(crate) fn price_range(&self) -> Option<RangeInclusive<PriceLevel>> {
+        let min_price = self.min_price.unwrap_or(0);
+        let max_price = self.max_price.unwrap_or(PriceLevel::MAX);
+
+        Some(min_price..=max_price).filter(|_| min_price <= max_price)
+    }
+
+    pub(crate) fn takes_whole_levels(&self) -> bool {
+        self.account.is_none() && self.session_id.is_none()
+    }
+
+    pub(crate) fn covers_order(&self, order: &OrderbookOrder) -> bool {
+        self.account.is_none_or(|account| order.account == Some(account))
+            && self
+                .session_id
+                .is_none_or(|session_id| order.session_id == Some(session_id))
+    }
+}
+
+#[cfg(test)]
+mod unit_test {
+    use super::super::*;
+
+    #[test]
+    fn test_mass_cancel_filters() {
+        let mut the_orderbooks = Orderbooks::default();
+        let orders: Vec<OrderMessage> = [
+            (OrderSide::Bid, 98, 1),
+            (OrderSide::Bid, 99, 2),
+            (OrderSide::Bid, 99, 1),
+            (OrderSide::Ask, 101, 1),
+            (OrderSide::Ask, 102, 2),
+        ]
+        .iter()
+        .map(|(side, price, account)| OrderMessage {
+            side: *side,
+            r#type: OrderType::Limit,
+            volume: Some(10),
+            price: Some(*price),
+            account: Some(*account),
+            ..Default::default()
+        })
+        .collect();
+
+        for order in orders.iter() {
+            the_orderbooks.try_execute_order(order).unwrap();
+        }
+
+        let request_id = OrderId::new_v4();
+        let events = the_orderbooks.mass_cancel(
+            request_id,
+            &MassCancel {
+                side: Some(OrderSide::Bid),
+                min_price: Some(99),
+                account: Some(1),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[&orders[2].id][0].r#type, OrderEventType::Cancelled);
+        assert_eq!(events[&orders[2].id][0].crossed_id, Some(request_id));
+        assert_eq!(events[&request_id][0].r#type, OrderEventType::MassCancelled);
+        assert_eq!(events[&request_id][0].remaining_volume, Some(10));
+        assert_eq!(the_orderbooks.bids.volume(99), 10);
+
+        let events = the_orderbooks.mass_cancel(
+            request_id,
+            &MassCancel {
+                min_price: Some(99),
+                max_price: Some(101),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(events.len(), 3);
+        assert_eq!(the_orderbooks.count(), (1, 1, 2));
+
+        the_orderbooks.mass_cancel(request_id, &MassCancel::all());
+
+        assert_eq!(the_orderbooks.count(), (0, 0, 0));
+        assert!(the_orderbooks.bids.orders.is_empty() && the_orderbooks.asks.orders.is_empty());
+    }
+}
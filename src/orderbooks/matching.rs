@@ -0,0 +1,246 @@
+use super::orders::*;
+use std::fmt::Debug;
+
+// Splits the volume an incoming order trades at one price level among the
+// resting orders of that level, which are in time priority. The allocations
+// follow the level's order, never exceed an order's remaining volume and add
+// up to the smaller of `volume` and the level's volume. Missing trailing
+// entries mean zero.
+pub trait MatchingPolicy: Debug + Send + Sync {
+    fn allocate(&self, level_orders: &[OrderbookOrder], volume: OrderVolume) -> Vec<OrderVolume>;
+}
+
+// Price-time priority: the oldest order fills first.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct FifoMatching;
+
+// How the lots left over after rounding every pro-rata share down are given
+// out, one lot per order at most.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ProRataRemainder {
+    #[default]
+    TimePriority,
+    LargestOrder,
+    LargestFraction,
+}
+
+// Every order gets its share of the volume in proportion to its size.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct ProRataMatching {
+    pub remainder: ProRataRemainder,
+}
+
+// The first order of the level fills up to `top_order_volume` (0 disables the
+// top order priority), then `fifo_percentage` of what is left is matched FIFO
+// and the rest pro-rata.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct HybridMatching {
+    pub top_order_volume: OrderVolume,
+    pub fifo_percentage: u8,
+    pub remainder: ProRataRemainder,
+}
+
+fn allocate_fifo(volumes: &[OrderVolume], mut volume: OrderVolume) -> Vec<OrderVolume> {
+    let mut allocations = Vec::new();
+
+    for order_volume in volumes.iter() {
+        if volume == 0 {
+            break;
+        }
+
+        let allocated = volume.min(*order_volume);
+        allocations.push(allocated);
+        volume -= allocated;
+    }
+
+    allocations
+}
+
+fn allocate_pro_rata(volumes: &[OrderVolume], volume: OrderVolume, remainder: ProRataRemainder) -> Vec<OrderVolume> {
+    let level_volume: u128 = volumes.iter().map(|order_volume| *order_volume as u128).sum();
+
+    if volume as u128 >= level_volume {
+        return volumes.to_vec();
+    }
+
+    let shares: Vec<u128> = volumes
+        .iter()
+        .map(|order_volume| volume as u128 * *order_volume as u128)
+        .collect();
+    let mut allocations: Vec<OrderVolume> = shares
+        .iter()
+        .map(|share| (share / level_volume) as OrderVolume)
+        .collect();
+    let leftover = volume - allocations.iter().sum::<OrderVolume>();
+    let mut candidates: Vec<usize> = (0..volumes.len())
+        .filter(|index| allocations[*index] < volumes[*index])
+        .collect();
+
+    match remainder {
+        ProRataRemainder::TimePriority => {}
+        ProRataRemainder::LargestOrder => candidates.sort_by_key(|index| (std::cmp::Reverse(volumes[*index]), *index)),
+        ProRataRemainder::LargestFraction => {
+            candidates.sort_by_key(|index| (std::cmp::Reverse(shares[*index] % level_volume), *index))
+        }
+    }
+
+    // The fractions add up to the leftover and each is below one lot, so one
+    // pass over the orders that still have room is enough.
+    for index in candidates.into_iter().take(leftover as usize) {
+        allocations[index] += 1;
+    }
+
+    allocations
+}
+
+impl MatchingPolicy for FifoMatching {
+    fn allocate(&self, level_orders: &[OrderbookOrder], volume: OrderVolume) -> Vec<OrderVolume> {
+        let volumes: Vec<OrderVolume> = level_orders.iter().map(|order| order.remaining_volume).collect();
+
+        allocate_fifo(&volumes, volume)
+    }
+}
+
+impl MatchingPolicy for ProRataMatching {
+    fn allocate(&self, level_orders: &[OrderbookOrder], volume: OrderVolume) -> Vec<OrderVolume> {
+        let volumes: Vec<OrderVolume> = level_orders.iter().map(|order| order.remaining_volume).collect();
+
+        allocate_pro_rata(&volumes, volume, self.remainder)
+    }
+}
+
+impl MatchingPolicy for HybridMatching {
+    fn allocate(&self, level_orders: &[OrderbookOrder], mut volume: OrderVolume) -> Vec<OrderVolume> {
+        let mut volumes: Vec<OrderVolume> = level_orders.iter().map(|order| order.remaining_volume).collect();
+        let mut allocations = vec![0; volumes.len()];
+
+        if let Some(top_order_volume) = volumes.first_mut() {
+            let allocated = volume.min(*top_order_volume).min(self.top_order_volume);
+            allocations[0] = allocated;
+            *top_order_volume -= allocated;
+            volume -= allocated;
+        }
+
+        let fifo_volume = (volume as u128 * self.fifo_percentage.min(100) as u128 / 100) as OrderVolume;
+
+        for (index, allocated) in allocate_fifo(&volumes, fifo_volume).into_iter().enumerate() {
+            allocations[index] += allocated;
+            volumes[index] -= allocated;
+            volume -= allocated;
+        }
+
+        for (index, allocated) in allocate_pro_rata(&volumes, volume, self.remainder)
+            .into_iter()
+            .enumerate()
+        {
+            allocations[index] += allocated;
+        }
+
+        allocations
+    }
+}
+
+#[cfg(test)]
+mod unit_test {
+    use super::*;
+    use uuid::Uuid;
+
+    fn level(volumes: &[OrderVolume]) -> Vec<OrderbookOrder> {
+        volumes
+            .iter()
+            .map(|remaining_volume| OrderbookOrder {
+                id: Uuid::new_v4(),
+                remaining_volume: *remaining_volume,
+                account: None,
+                session_id: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_fifo_fills_the_oldest_order_first() {
+        let orders = level(&[10, 30, 60]);
+
+        assert_eq!(FifoMatching.allocate(&orders, 25), vec![10, 15]);
+        assert_eq!(FifoMatching.allocate(&orders, 500), vec![10, 30, 60]);
+    }
+
+    #[test]
+    fn test_pro_rata_allocates_by_size() {
+        let orders = level(&[10, 30, 60]);
+        let pro_rata = |remainder| ProRataMatching { remainder };
+
+        // 7 lots: shares 0.7, 2.1 and 4.2 round down to 0, 2 and 4.
+        assert_eq!(
+            pro_rata(ProRataRemainder::TimePriority).allocate(&orders, 7),
+            vec![1, 2, 4]
+        );
+        assert_eq!(
+            pro_rata(ProRataRemainder::LargestOrder).allocate(&orders, 7),
+            vec![0, 2, 5]
+        );
+        assert_eq!(
+            pro_rata(ProRataRemainder::LargestFraction).allocate(&orders, 7),
+            vec![1, 2, 4]
+        );
+        // 8 lots: shares 0.8, 2.4 and 4.8 leave two lots over.
+        assert_eq!(
+            pro_rata(ProRataRemainder::TimePriority).allocate(&orders, 8),
+            vec![1, 3, 4]
+        );
+        assert_eq!(
+            pro_rata(ProRataRemainder::LargestOrder).allocate(&orders, 8),
+            vec![0, 3, 5]
+        );
+        assert_eq!(
+            pro_rata(ProRataRemainder::LargestFraction).allocate(&orders, 8),
+            vec![1, 2, 5]
+        );
+        // Ties go to the older order.
+        assert_eq!(
+            pro_rata(ProRataRemainder::LargestOrder).allocate(&level(&[1, 1, 1]), 2),
+            vec![1, 1, 0]
+        );
+        assert_eq!(
+            pro_rata(ProRataRemainder::TimePriority).allocate(&orders, 150),
+            vec![10, 30, 60]
+        );
+    }
+
+    #[test]
+    fn test_hybrid_fills_the_top_order_then_fifo_then_pro_rata() {
+        let orders = level(&[10, 30, 60]);
+        let hybrid = HybridMatching {
+            top_order_volume: 5,
+            fifo_percentage: 40,
+            remainder: ProRataRemainder::TimePriority,
+        };
+
+        // 5 to the top order, 40% of the other 45 FIFO (5 more to the first
+        // order, 13 to the second) and 27 pro-rata over 0, 17 and 60.
+        assert_eq!(hybrid.allocate(&orders, 50), vec![10, 19, 21]);
+        assert_eq!(hybrid.allocate(&orders, 3), vec![3, 0, 0]);
+        assert_eq!(hybrid.allocate(&orders, 100), vec![10, 30, 60]);
+    }
+
+    #[test]
+    fn test_hybrid_gives_out_the_pro_rata_remainder() {
+        let orders = level(&[10, 30, 60]);
+        let hybrid = |remainder| HybridMatching {
+            top_order_volume: 5,
+            fifo_percentage: 40,
+            remainder,
+        };
+
+        // The 27 pro-rata lots over 0, 17 and 60 are shares of 5.96 and 21.04,
+        // which leaves one lot over.
+        assert_eq!(
+            hybrid(ProRataRemainder::LargestOrder).allocate(&orders, 50),
+            vec![10, 18, 22]
+        );
+        assert_eq!(
+            hybrid(ProRataRemainder::LargestFraction).allocate(&orders, 50),
+            vec![10, 19, 21]
+        );
+    }
+}
@@ -1,27 +1,83 @@
+mod auction;
+mod candles;
+mod client_order_ids;
+mod clock;
+mod ledger;
+mod market_data;
+mod mass_cancel;
+mod matching;
+mod order_feed;
 mod orders;
-
+mod positions;
+mod price_bands;
+mod rejections;
+mod session;
+mod stats;
+#[cfg(test)]
+pub(crate) mod test_support;
+mod trades;
+
+pub use auction::*;
+pub use candles::*;
+pub use client_order_ids::*;
+pub use clock::*;
+pub use ledger::*;
+pub use market_data::*;
+pub use mass_cancel::*;
+pub use matching::*;
+pub use order_feed::*;
 pub use orders::*;
-
-use chrono::prelude::*;
-use std::collections::{BTreeMap, HashMap};
+pub use positions::*;
+pub use price_bands::*;
+pub use rejections::*;
+pub use session::*;
+pub use stats::*;
+pub use trades::*;
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet};
+use std::mem;
+use std::sync::Arc;
+
+const MIN_EXPIRIES_COMPACTION: usize = 1_024;
 use uuid::Uuid;
 
 pub type PriceLevel = u64;
-pub type Index = usize;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 #[repr(align(8))]
 pub struct Orderbook {
-    pub orders: BTreeMap<PriceLevel, Vec<OrderbookOrder>>,
-    pub orders_location: HashMap<Uuid, PriceLevel>,
+    pub(crate) orders: BTreeMap<PriceLevel, Vec<OrderbookOrder>>,
+    pub(crate) orders_location: HashMap<Uuid, PriceLevel>,
+    pub(crate) touched_levels: BTreeSet<PriceLevel>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 #[repr(align(8))]
 pub struct Orderbooks {
-    pub bids: Orderbook,
-    pub asks: Orderbook,
-    pub orders_location: HashMap<Uuid, OrderSide>,
+    pub(crate) bids: Orderbook,
+    pub(crate) asks: Orderbook,
+    pub(crate) orders_location: HashMap<Uuid, OrderSide>,
+    pub(crate) depth: Option<MarketDepth>,
+    pub(crate) order_feed: Option<OrderFeed>,
+    pub(crate) candles: Option<CandleAggregator>,
+    pub(crate) trades: Vec<Trade>,
+    pub(crate) stats: MarketStatistics,
+    pub(crate) positions: Positions,
+    pub(crate) clock: EngineClock,
+    pub(crate) session: SessionState,
+    pub(crate) resume_session: SessionState,
+    pub(crate) session_schedule: Option<SessionSchedule>,
+    pub(crate) matching_policy: Option<Arc<dyn MatchingPolicy>>,
+    pub(crate) auction: Option<Auction>,
+    pub(crate) auction_indications: Vec<AuctionIndication>,
+    pub(crate) price_bands: Option<PriceBands>,
+    pub(crate) expiries: BinaryHeap<Reverse<(UTCNanoSeconds, Uuid)>>,
+    pub(crate) order_expiries: HashMap<Uuid, UTCNanoSeconds>,
+    pub(crate) day_orders: HashSet<Uuid>,
+    pub(crate) cancel_on_disconnect_opt_outs: HashSet<SessionId>,
+    pub(crate) client_order_ids: ClientOrderIds,
+    pub(crate) ledger: Option<Ledger>,
 }
 
 impl Orderbook {
@@ -29,6 +85,13 @@ impl Orderbook {
         self.orders_location.len()
     }
 
+    pub fn volume(&self, price_level: PriceLevel) -> OrderVolume {
+        match self.orders.get(&price_level) {
+            Some(price_level_orders) => price_level_orders.iter().map(|order| order.remaining_volume).sum(),
+            None => 0,
+        }
+    }
+
     pub fn remove(&mut self, order_id: &Uuid) -> Option<OrderbookOrder> {
         if !self.orders_location.contains_key(order_id) {
             return None;
@@ -36,6 +99,7 @@ impl Orderbook {
 
         let mut retval = None;
         let (_, price_level) = self.orders_location.remove_entry(order_id).unwrap();
+        self.touched_levels.insert(price_level);
 
         if let Some(price_level_orders) = self.orders.get_mut(&price_level) {
             let index = price_level_orders
@@ -66,15 +130,7 @@ impl Orderbook {
         }
 
         self.orders_location.insert(order_id, price_level);
-    }
-}
-
-impl Default for Orderbook {
-    fn default() -> Orderbook {
-        Orderbook {
-            orders: BTreeMap::default(),
-            orders_location: HashMap::default(),
-        }
+        self.touched_levels.insert(price_level);
     }
 }
 
@@ -86,18 +142,801 @@ impl Orderbooks {
         (bids_count, asks_count, bids_count + asks_count)
     }
 
+    pub fn now(&self) -> UTCNanoSeconds {
+        self.clock.now()
+    }
+
+    pub fn set_clock(&mut self, clock: EngineClock) {
+        self.clock = clock;
+    }
+
+    pub fn ticker(&self) -> Ticker {
+        self.stats.ticker(self.clock.now())
+    }
+
+    pub fn last_price(&self) -> Option<OrderPrice> {
+        self.stats.last_price
+    }
+
+    pub fn halt(&mut self) -> Result<HashMap<Uuid, Vec<OrderEvent>>, SessionTransitionError> {
+        self.transition(SessionState::Halted)
+    }
+
+    // Returns to the state the halt interrupted, an auction still open in
+    // continuous trading uncrosses.
+    pub fn resume(&mut self) -> Result<HashMap<Uuid, Vec<OrderEvent>>, SessionTransitionError> {
+        if self.session != SessionState::Halted {
+            return Err(SessionTransitionError {
+                from: self.session,
+                to: self.resume_session,
+            });
+        }
+
+        self.transition(self.resume_session)
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.session == SessionState::Halted
+    }
+
+    pub fn session(&self) -> SessionState {
+        self.session
+    }
+
+    // Entering pre-open or an auction starts collecting orders without
+    // matching, leaving an auction for continuous trading or the close
+    // uncrosses the book and returns the events of the uncross. Day orders
+    // still resting at the close expire.
+    pub fn transition(&mut self, to: SessionState) -> Result<HashMap<Uuid, Vec<OrderEvent>>, SessionTransitionError> {
+        let from = self.session;
+
+        if !from.can_move_to(to, self.resume_session) {
+            return Err(SessionTransitionError { from, to });
+        }
+
+        if to == SessionState::Halted {
+            self.resume_session = from;
+        }
+
+        self.session = to;
+        self.trades.clear();
+        let mut order_events = HashMap::new();
+
+        match to {
+            SessionState::PreOpen
+            | SessionState::OpeningAuction
+            | SessionState::ClosingAuction
+            | SessionState::VolatilityAuction => {
+                if self.auction.is_none() {
+                    self.start_auction(None);
+                } else {
+                    self.publish_indication();
+                }
+            }
+            SessionState::Continuous | SessionState::Closed if self.auction.is_some() => {
+                if from == SessionState::Halted && to == SessionState::Closed {
+                    self.auction = None;
+                } else {
+                    order_events = self.uncross();
+                }
+            }
+            _ => {}
+        }
+
+        if to == SessionState::Closed {
+            let day_orders: Vec<Uuid> = self.day_orders.drain().collect();
+
+            for order_id in day_orders.iter() {
+                self.expire(order_id, &mut order_events);
+            }
+
+            self.publish_depth();
+        }
+
+        Ok(order_events)
+    }
+
+    pub fn set_session_schedule(&mut self, session_schedule: SessionSchedule) {
+        self.session_schedule = Some(session_schedule);
+    }
+
+    // Moves to the state the schedule gives for the engine clock, through the
+    // states in between. Halts are left alone until resumed, a volatility
+    // auction until it runs out.
+    pub fn advance_session(&mut self) -> Result<HashMap<Uuid, Vec<OrderEvent>>, SessionTransitionError> {
+        if self.session == SessionState::VolatilityAuction {
+            let ends_at = self.auction.as_ref().and_then(|auction| auction.ends_at);
+
+            return match ends_at {
+                Some(ends_at) if self.clock.now() >= ends_at => self.transition(SessionState::Continuous),
+                _ => Ok(HashMap::new()),
+            };
+        }
+
+        let scheduled_session = self
+            .session_schedule
+            .as_ref()
+            .and_then(|session_schedule| session_schedule.state_at(self.clock.now()));
+
+        let to = match scheduled_session {
+            Some(to) if to != self.session && self.session != SessionState::Halted => to,
+            _ => return Ok(HashMap::new()),
+        };
+        let mut order_events: HashMap<Uuid, Vec<OrderEvent>> = HashMap::new();
+
+        // A clock that jumped past a state steps through it, so a skipped
+        // opening auction still uncrosses before continuous trading.
+        while self.session != to {
+            let step = if self.session.can_move_to(to, self.resume_session) {
+                to
+            } else {
+                match self.session.next_in_day() {
+                    Some(step) => step,
+                    None => return Err(SessionTransitionError { from: self.session, to }),
+                }
+            };
+
+            for (order_id, events) in self.transition(step)? {
+                order_events.entry(order_id).or_default().extend(events);
+            }
+        }
+
+        Ok(order_events)
+    }
+
+    // Orders rest without matching until `uncross`. The reference price breaks
+    // ties between equilibrium prices and defaults to the last traded price.
+    pub fn start_auction(&mut self, reference_price: Option<OrderPrice>) {
+        self.auction = Some(Auction {
+            reference_price: reference_price.or(self.stats.last_price),
+            indication: None,
+            ends_at: None,
+        });
+        self.publish_indication();
+    }
+
+    pub fn set_price_bands(&mut self, price_bands: PriceBands) {
+        self.price_bands = Some(price_bands);
+    }
+
+    pub fn price_bands(&self) -> Option<PriceBands> {
+        self.price_bands
+    }
+
+    // Funds orders with an account from then on, orders already resting stay
+    // unfunded. The ledger only covers this instrument.
+    pub fn set_ledger(&mut self, ledger: Ledger) {
+        self.ledger = Some(ledger);
+    }
+
+    pub fn ledger(&self) -> Option<&Ledger> {
+        self.ledger.as_ref()
+    }
+
+    pub fn ledger_mut(&mut self) -> Option<&mut Ledger> {
+        self.ledger.as_mut()
+    }
+
+    // Stops continuous trading at the band and collects orders until
+    // `advance_session` finds the auction has run its course. The reference
+    // price is the last trade of the order that reached the band, if it traded.
+    fn start_volatility_auction(&mut self, duration: UTCNanoSeconds, reference_price: Option<OrderPrice>) {
+        if !self
+            .session
+            .can_move_to(SessionState::VolatilityAuction, self.resume_session)
+        {
+            return;
+        }
+
+        self.auction = Some(Auction {
+            reference_price: reference_price.or(self.stats.last_price),
+            indication: None,
+            ends_at: Some(self.clock.now().saturating_add(duration)),
+        });
+        let _ = self.transition(SessionState::VolatilityAuction);
+    }
+
+    pub fn in_auction(&self) -> bool {
+        self.auction.is_some()
+    }
+
+    pub fn indicative_uncross(&self) -> Option<AuctionIndication> {
+        let auction = self.auction.as_ref()?;
+
+        Some(equilibrium(
+            &self.bids,
+            &self.asks,
+            auction.reference_price,
+            self.clock.now(),
+        ))
+    }
+
+    // Indications published during the call phase, one whenever the price,
+    // volume or imbalance changes.
+    pub fn drain_auction_indications(&mut self) -> Vec<AuctionIndication> {
+        mem::take(&mut self.auction_indications)
+    }
+
+    // Pre-open collects orders quietly.
+    fn publish_indication(&mut self) {
+        if self.session == SessionState::PreOpen {
+            return;
+        }
+
+        let indication = match self.indicative_uncross() {
+            Some(indication) => indication,
+            None => return,
+        };
+        let auction = self.auction.as_mut().unwrap();
+
+        if auction
+            .indication
+            .as_ref()
+            .is_none_or(|last_indication| !last_indication.same_as(&indication))
+        {
+            auction.indication = Some(indication.clone());
+            self.auction_indications.push(indication);
+        }
+    }
+
+    // Ends the auction and executes every crossing order at the equilibrium
+    // price, bids and asks each in price-time priority. Auction trades have no
+    // aggressor: `aggressor_id` is the bid and `aggressor_side` is `NoSide`.
+    pub fn uncross(&mut self) -> HashMap<Uuid, Vec<OrderEvent>> {
+        let mut order_events: HashMap<Uuid, Vec<OrderEvent>> = HashMap::new();
+        let indication = match self.indicative_uncross() {
+            Some(indication) => indication,
+            None => return order_events,
+        };
+        self.auction = None;
+        self.trades.clear();
+
+        let current_timestamp = indication.timestamp;
+        let mut remaining_volume = indication.volume;
+        let mut execute_messages = Vec::new();
+        let mut pending_order_removal_id = Vec::new();
+
+        if let Some(price) = indication.price {
+            let traded_price = Some(price);
+            let bid_touched_levels = &mut self.bids.touched_levels;
+            let ask_touched_levels = &mut self.asks.touched_levels;
+            let mut bid_orders = self
+                .bids
+                .orders
+                .range_mut(price..)
+                .rev()
+                .flat_map(|(price_level, orders)| orders.iter_mut().map(move |order| (*price_level, order)));
+            let mut ask_orders = self
+                .asks
+                .orders
+                .range_mut(..=price)
+                .flat_map(|(price_level, orders)| orders.iter_mut().map(move |order| (*price_level, order)));
+            let mut bid = bid_orders.next();
+            let mut ask = ask_orders.next();
+
+            while remaining_volume > 0 {
+                let ((bid_price_level, bid_order), (ask_price_level, ask_order)) = match (bid.as_mut(), ask.as_mut()) {
+                    (Some(bid), Some(ask)) => (bid, ask),
+                    _ => break,
+                };
+                let traded_volume = remaining_volume
+                    .min(bid_order.remaining_volume)
+                    .min(ask_order.remaining_volume);
+                bid_order.remaining_volume -= traded_volume;
+                ask_order.remaining_volume -= traded_volume;
+                remaining_volume -= traded_volume;
+                bid_touched_levels.insert(*bid_price_level);
+                ask_touched_levels.insert(*ask_price_level);
+
+                for (order, side, price_level, crossed_id) in [
+                    (&**bid_order, OrderSide::Bid, *bid_price_level, ask_order.id),
+                    (&**ask_order, OrderSide::Ask, *ask_price_level, bid_order.id),
+                ]
+                .iter()
+                {
+                    let events = order_events.entry(order.id).or_default();
+                    events.push(OrderEvent {
+                        timestamp: current_timestamp,
+                        r#type: OrderEventType::HasMatch,
+                        remaining_volume: Some(order.remaining_volume),
+                        crossed_id: Some(*crossed_id),
+                        traded_price,
+                    });
+
+                    if order.remaining_volume == 0 {
+                        events.push(OrderEvent {
+                            timestamp: current_timestamp,
+                            r#type: OrderEventType::Closed,
+                            remaining_volume: Some(0),
+                            crossed_id: None,
+                            traded_price: None,
+                        });
+                        pending_order_removal_id.push((*side, order.id));
+                    }
+
+                    execute_messages.push(OrderFeedMessage {
+                        timestamp: current_timestamp,
+                        r#type: OrderFeedMessageType::Execute,
+                        order_id: order.id,
+                        side: *side,
+                        price: *price_level,
+                        volume: order.remaining_volume,
+                        executed_volume: Some(traded_volume),
+                        ..Default::default()
+                    });
+                }
+
+                self.trades.push(Trade {
+                    timestamp: current_timestamp,
+                    price,
+                    volume: traded_volume,
+                    aggressor_id: bid_order.id,
+                    aggressor_side: OrderSide::NoSide,
+                    resting_id: ask_order.id,
+                    resting_remaining_volume: ask_order.remaining_volume,
+                    aggressor_account: bid_order.account,
+                    resting_account: ask_order.account,
+                });
+
+                if bid_order.remaining_volume == 0 {
+                    bid = bid_orders.next();
+                }
+
+                if ask_order.remaining_volume == 0 {
+                    ask = ask_orders.next();
+                }
+            }
+        }
+
+        for (side, order_id) in pending_order_removal_id {
+            match side {
+                OrderSide::Bid => self.bids.remove(&order_id),
+                _ => self.asks.remove(&order_id),
+            };
+            self.orders_location.remove(&order_id);
+            self.forget_expiry(&order_id);
+        }
+
+        if let Some(order_feed) = self.order_feed.as_mut() {
+            for message in execute_messages {
+                order_feed.publish(message);
+            }
+        }
+
+        self.publish_trades();
+
+        if let Some(ledger) = self.ledger.as_mut() {
+            ledger.settle(&self.trades, None);
+        }
+
+        self.publish_depth();
+
+        order_events
+    }
+
+    // FIFO until a policy is set.
+    pub fn set_matching_policy<P: MatchingPolicy + 'static>(&mut self, matching_policy: P) {
+        self.matching_policy = Some(Arc::new(matching_policy));
+    }
+
+    pub fn contains(&self, order_id: &Uuid) -> bool {
+        self.orders_location.contains_key(order_id)
+    }
+
+    // Removes the good till date orders due at or before `now`. Entries of
+    // orders that left the book earlier are skipped when they come due.
+    pub fn expire_until(&mut self, now: UTCNanoSeconds) -> HashMap<Uuid, Vec<OrderEvent>> {
+        let mut order_events = HashMap::new();
+
+        while let Some(Reverse((expires_at, order_id))) = self.expiries.peek().copied() {
+            if expires_at > now {
+                break;
+            }
+
+            self.expiries.pop();
+
+            if self.order_expiries.get(&order_id) == Some(&expires_at) {
+                self.order_expiries.remove(&order_id);
+                self.expire(&order_id, &mut order_events);
+            }
+        }
+
+        if !order_events.is_empty() {
+            self.publish_depth();
+            self.publish_indication();
+        }
+
+        order_events
+    }
+
+    // Called wherever an order leaves the book, so a reused id is not expired
+    // by the entries of the order that had it before.
+    fn forget_expiry(&mut self, order_id: &Uuid) {
+        self.order_expiries.remove(order_id);
+        self.day_orders.remove(order_id);
+    }
+
+    fn expire(&mut self, order_id: &Uuid, order_events: &mut HashMap<Uuid, Vec<OrderEvent>>) {
+        if let Some(removed_order) = self.remove(order_id) {
+            order_events.entry(*order_id).or_default().push(OrderEvent {
+                timestamp: self.clock.now(),
+                r#type: OrderEventType::Expired,
+                remaining_volume: Some(removed_order.remaining_volume),
+                crossed_id: None,
+                traded_price: None,
+            });
+        }
+    }
+
+    // Each order gets a `Cancelled` event crossed with `request_id`, and
+    // `request_id` gets a `MassCancelled` summary with the total volume
+    // cancelled.
+    pub fn mass_cancel(&mut self, request_id: Uuid, filter: &MassCancel) -> HashMap<Uuid, Vec<OrderEvent>> {
+        let (mut order_events, cancelled_volume) = self.cancel_resting(filter, Some(request_id));
+
+        order_events.insert(
+            request_id,
+            vec![OrderEvent {
+                timestamp: self.clock.now(),
+                r#type: OrderEventType::MassCancelled,
+                remaining_volume: Some(cancelled_volume),
+                crossed_id: None,
+                traded_price: None,
+            }],
+        );
+
+        order_events
+    }
+
+    // How long a client order id stays taken after it was used, a day by
+    // default. Ids of resting orders stay taken anyway.
+    pub fn set_client_order_id_window(&mut self, window: UTCNanoSeconds) {
+        self.client_order_ids.window = window;
+    }
+
+    // Resting orders only, like `lookup`.
+    pub fn lookup_client_order_id(&self, account: Option<AccountId>, client_order_id: &str) -> Option<OrderId> {
+        self.client_order_ids
+            .get(&(account, client_order_id.to_string()))
+            .filter(|order_id| self.orders_location.contains_key(order_id))
+    }
+
+    // Cancels and replaces name their target by order id or by the client
+    // order id of the same account.
+    fn target_id(&self, order_message: &OrderMessage) -> Option<Uuid> {
+        order_message.target_id.or_else(|| {
+            let client_order_id = order_message.target_client_order_id.clone()?;
+            self.client_order_ids.get(&(order_message.account, client_order_id))
+        })
+    }
+
+    // Sessions cancel on disconnect unless they opt out.
+    pub fn set_cancel_on_disconnect(&mut self, session_id: SessionId, enabled: bool) {
+        if enabled {
+            self.cancel_on_disconnect_opt_outs.remove(&session_id);
+        } else {
+            self.cancel_on_disconnect_opt_outs.insert(session_id);
+        }
+    }
+
+    // Called by a gateway when the session drops or misses its heartbeats.
+    // Returns the `Cancelled` events of the session's resting orders.
+    pub fn disconnect_session(&mut self, session_id: SessionId) -> HashMap<Uuid, Vec<OrderEvent>> {
+        if self.cancel_on_disconnect_opt_outs.contains(&session_id) {
+            return HashMap::new();
+        }
+
+        let filter = MassCancel {
+            session_id: Some(session_id),
+            ..Default::default()
+        };
+
+        self.cancel_resting(&filter, None).0
+    }
+
+    // Cancels every resting order the filter picks in one pass over the price
+    // range, taking whole levels unless it filters by owner.
+    fn cancel_resting(
+        &mut self,
+        filter: &MassCancel,
+        crossed_id: Option<Uuid>,
+    ) -> (HashMap<Uuid, Vec<OrderEvent>>, OrderVolume) {
+        let current_timestamp = self.clock.now();
+        let mut order_events = HashMap::new();
+        let mut order_feed_messages = Vec::new();
+        let mut cancelled_volume = 0;
+
+        for side in [OrderSide::Bid, OrderSide::Ask].iter() {
+            let price_range = match filter.price_range() {
+                Some(price_range) if filter.covers_side(*side) => price_range,
+                _ => continue,
+            };
+            let orderbook = match side {
+                OrderSide::Bid => &mut self.bids,
+                _ => &mut self.asks,
+            };
+            let price_levels: Vec<PriceLevel> = orderbook.orders.range(price_range).map(|(price, _)| *price).collect();
+
+            for price_level in price_levels {
+                let price_level_orders = orderbook.orders.get_mut(&price_level).unwrap();
+                let cancelled_orders: Vec<OrderbookOrder> = if filter.takes_whole_levels() {
+                    mem::take(price_level_orders)
+                } else {
+                    let (cancelled_orders, kept_orders) = mem::take(price_level_orders)
+                        .into_iter()
+                        .partition(|order| filter.covers_order(order));
+                    *price_level_orders = kept_orders;
+                    cancelled_orders
+                };
+
+                if price_level_orders.is_empty() {
+                    orderbook.orders.remove(&price_level);
+                }
+
+                if !cancelled_orders.is_empty() {
+                    orderbook.touched_levels.insert(price_level);
+                }
+
+                for cancelled_order in cancelled_orders {
+                    orderbook.orders_location.remove(&cancelled_order.id);
+                    self.orders_location.remove(&cancelled_order.id);
+                    self.order_expiries.remove(&cancelled_order.id);
+                    self.day_orders.remove(&cancelled_order.id);
+
+                    if let Some(ledger) = self.ledger.as_mut() {
+                        ledger.release(&cancelled_order.id);
+                    }
+
+                    cancelled_volume += cancelled_order.remaining_volume;
+                    order_events.insert(
+                        cancelled_order.id,
+                        vec![OrderEvent {
+                            timestamp: current_timestamp,
+                            r#type: OrderEventType::Cancelled,
+                            remaining_volume: Some(cancelled_order.remaining_volume),
+                            crossed_id,
+                            traded_price: None,
+                        }],
+                    );
+                    order_feed_messages.push(OrderFeedMessage {
+                        r#type: OrderFeedMessageType::Cancel,
+                        order_id: cancelled_order.id,
+                        side: *side,
+                        price: price_level,
+                        volume: cancelled_order.remaining_volume,
+                        ..Default::default()
+                    });
+                }
+            }
+        }
+
+        for order_feed_message in order_feed_messages {
+            self.publish_order_feed(order_feed_message);
+        }
+
+        self.publish_depth();
+        self.publish_indication();
+
+        (order_events, cancelled_volume)
+    }
+
+    // Trades of the last executed order only.
+    pub fn trades(&self) -> &[Trade] {
+        &self.trades
+    }
+
+    pub fn lookup(&self, order_id: &Uuid) -> Option<(OrderSide, PriceLevel, &OrderbookOrder)> {
+        let orderbook_side = *self.orders_location.get(order_id)?;
+        let orderbook = match orderbook_side {
+            OrderSide::Bid => &self.bids,
+            OrderSide::Ask => &self.asks,
+            OrderSide::NoSide => return None,
+        };
+        let price_level = *orderbook.orders_location.get(order_id)?;
+        let order = orderbook
+            .orders
+            .get(&price_level)?
+            .iter()
+            .find(|order| order.id == *order_id)?;
+
+        Some((orderbook_side, price_level, order))
+    }
+
+    pub fn attach_depth_publisher(&mut self) {
+        self.bids.touched_levels.clear();
+        self.asks.touched_levels.clear();
+        self.depth = Some(MarketDepth::from_orderbooks(&self.bids, &self.asks));
+    }
+
+    // Callers drain regularly, see `MAX_PENDING_DEPTH_UPDATES`.
+    pub fn drain_depth_updates(&mut self) -> Vec<DepthUpdate> {
+        match self.depth.as_mut() {
+            Some(depth) => depth.drain_updates(),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn depth_snapshot(&self, max_levels: usize) -> DepthSnapshot {
+        match &self.depth {
+            Some(depth) => depth.snapshot(max_levels),
+            None => MarketDepth::from_orderbooks(&self.bids, &self.asks).snapshot(max_levels),
+        }
+    }
+
+    // Bought minus sold volume of an account over every trade of the book.
+    pub fn position(&self, account: Option<AccountId>) -> Position {
+        self.positions.get(account)
+    }
+
+    // What a market order of `volume` would pay or receive sweeping the other
+    // side, best level first.
+    pub fn sweep_quote(&self, side: OrderSide, volume: OrderVolume) -> OrderQuote {
+        let levels: Box<dyn Iterator<Item = (&PriceLevel, &Vec<OrderbookOrder>)>> = match side {
+            OrderSide::Bid => Box::new(self.asks.orders.iter()),
+            _ => Box::new(self.bids.orders.iter().rev()),
+        };
+        let mut remaining_volume = volume;
+        let mut quote: OrderQuote = 0;
+
+        for (price_level, price_level_orders) in levels {
+            if remaining_volume == 0 {
+                break;
+            }
+
+            let level_volume: OrderVolume = price_level_orders.iter().map(|order| order.remaining_volume).sum();
+            let traded_volume = remaining_volume.min(level_volume);
+            quote = quote.saturating_add(price_level.saturating_mul(traded_volume));
+            remaining_volume -= traded_volume;
+        }
+
+        quote
+    }
+
+    pub fn attach_order_feed(&mut self) {
+        self.order_feed = Some(OrderFeed::default());
+    }
+
+    pub fn drain_order_feed(&mut self) -> Vec<OrderFeedMessage> {
+        match self.order_feed.as_mut() {
+            Some(order_feed) => order_feed.drain_messages(),
+            None => Vec::new(),
+        }
+    }
+
+    // Candles of the given intervals, built from every trade from then on,
+    // auction uncrosses included.
+    pub fn attach_candles(&mut self, intervals: &[CandleInterval]) {
+        self.candles = Some(CandleAggregator::new(intervals));
+    }
+
+    pub fn candles(&self) -> Option<&CandleAggregator> {
+        self.candles.as_ref()
+    }
+
+    // Closes the candles the engine clock has moved past before draining.
+    pub fn drain_closed_candles(&mut self) -> Vec<Candle> {
+        let now = self.clock.now();
+
+        match self.candles.as_mut() {
+            Some(candles) => {
+                candles.advance(now);
+                candles.drain_closed()
+            }
+            None => Vec::new(),
+        }
+    }
+
+    fn publish_order_feed(&mut self, mut message: OrderFeedMessage) {
+        if let Some(order_feed) = self.order_feed.as_mut() {
+            message.timestamp = self.clock.now();
+            order_feed.publish(message);
+        }
+    }
+
+    fn publish_trades(&mut self) {
+        self.stats.add_trades(&self.trades);
+        self.positions.add_trades(&self.trades);
+
+        if let Some(candles) = self.candles.as_mut() {
+            candles.add_trades(&self.trades);
+        }
+    }
+
+    fn record_trades(
+        &mut self,
+        order_message: &OrderMessage,
+        current_order_events: &[OrderEvent],
+        order_events: &HashMap<Uuid, Vec<OrderEvent>>,
+        resting_accounts: &HashMap<Uuid, Option<AccountId>>,
+    ) {
+        self.trades = Trade::from_events(order_message, current_order_events, order_events, resting_accounts);
+        self.publish_trades();
+
+        if let Some(ledger) = self.ledger.as_mut() {
+            ledger.settle(&self.trades, order_message.account);
+        }
+
+        if let Some(order_feed) = self.order_feed.as_mut() {
+            for trade in self.trades.iter() {
+                order_feed.publish(OrderFeedMessage {
+                    timestamp: trade.timestamp,
+                    r#type: OrderFeedMessageType::Execute,
+                    order_id: trade.resting_id,
+                    side: trade.resting_side(),
+                    price: trade.price,
+                    volume: trade.resting_remaining_volume,
+                    executed_volume: Some(trade.volume),
+                    ..Default::default()
+                });
+            }
+        }
+    }
+
+    fn publish_depth(&mut self) {
+        let touched_bid_levels = mem::take(&mut self.bids.touched_levels);
+        let touched_ask_levels = mem::take(&mut self.asks.touched_levels);
+
+        if let Some(depth) = self.depth.as_mut() {
+            let current_timestamp = self.clock.now();
+
+            for price_level in touched_bid_levels {
+                depth.publish(
+                    OrderSide::Bid,
+                    price_level,
+                    self.bids.volume(price_level),
+                    current_timestamp,
+                );
+            }
+
+            for price_level in touched_ask_levels {
+                depth.publish(
+                    OrderSide::Ask,
+                    price_level,
+                    self.asks.volume(price_level),
+                    current_timestamp,
+                );
+            }
+
+            depth.update_checksum();
+        }
+    }
+
     pub fn remove(&mut self, order_id: &Uuid) -> Option<OrderbookOrder> {
+        let (orderbook_side, price_level, removed_order) = self.detach(order_id)?;
+
+        self.publish_order_feed(OrderFeedMessage {
+            r#type: OrderFeedMessageType::Cancel,
+            order_id: removed_order.id,
+            side: orderbook_side,
+            price: price_level,
+            volume: removed_order.remaining_volume,
+            ..Default::default()
+        });
+
+        Some(removed_order)
+    }
+
+    fn detach(&mut self, order_id: &Uuid) -> Option<(OrderSide, PriceLevel, OrderbookOrder)> {
         if !self.orders_location.contains_key(order_id) {
             return None;
         }
 
         let (_, orderbook_side) = self.orders_location.remove_entry(order_id).unwrap();
-
-        match orderbook_side {
-            OrderSide::Bid => self.bids.remove(order_id),
-            OrderSide::Ask => self.asks.remove(order_id),
+        self.forget_expiry(order_id);
+        let orderbook = match orderbook_side {
+            OrderSide::Bid => &mut self.bids,
+            OrderSide::Ask => &mut self.asks,
             OrderSide::NoSide => panic!("Attempt to remove CancelOrder: {}", order_id),
+        };
+        let price_level = *orderbook.orders_location.get(order_id)?;
+
+        if let Some(ledger) = self.ledger.as_mut() {
+            ledger.release(order_id);
         }
+
+        orderbook
+            .remove(order_id)
+            .map(|removed_order| (orderbook_side, price_level, removed_order))
     }
 
     pub fn insert(&mut self, order_message: &OrderMessage, remaining_volume: OrderVolume) {
@@ -119,31 +958,363 @@ impl Orderbooks {
         let new_orderbook_order = OrderbookOrder {
             id: order_id,
             remaining_volume,
+            account: order_message.account,
+            session_id: order_message.session_id,
         };
         let order_price = order_message.price.unwrap();
 
         orderbook.insert(order_price, new_orderbook_order);
         self.orders_location.insert(order_id, order_message.side);
+
+        if let (Some(ledger), Some(account)) = (self.ledger.as_mut(), order_message.account) {
+            ledger.reserve(order_id, account, order_message.side, order_price, remaining_volume);
+        }
+
+        match (order_message.time_in_force, order_message.expires_at) {
+            (TimeInForce::GoodTillDate, Some(expires_at)) => {
+                // Orders that left the book before expiring leave their heap
+                // entries behind, they are dropped once they outnumber the rest.
+                if self.expiries.len() >= 2 * self.order_expiries.len() + MIN_EXPIRIES_COMPACTION {
+                    let order_expiries = &self.order_expiries;
+                    self.expiries
+                        .retain(|Reverse((expires_at, order_id))| order_expiries.get(order_id) == Some(expires_at));
+                }
+
+                self.order_expiries.insert(order_id, expires_at);
+                self.expiries.push(Reverse((expires_at, order_id)));
+            }
+            (TimeInForce::Day, _) => {
+                self.day_orders.insert(order_id);
+            }
+            _ => {}
+        }
+
+        let (order_feed_message_type, replaced_id) = if order_message.r#type == OrderType::Replace {
+            (OrderFeedMessageType::Replace, self.target_id(order_message))
+        } else {
+            (OrderFeedMessageType::Add, None)
+        };
+
+        self.publish_order_feed(OrderFeedMessage {
+            r#type: order_feed_message_type,
+            order_id,
+            replaced_id,
+            side: order_message.side,
+            price: order_price,
+            volume: remaining_volume,
+            ..Default::default()
+        });
+    }
+
+    // Checks everything `execute_order` would otherwise panic on, plus the session state.
+    pub fn validate(&self, order_message: &OrderMessage) -> Result<(), OrderRejection> {
+        self.session.accept(order_message.r#type)?;
+
+        if self.auction.is_some() && order_message.r#type == OrderType::Market {
+            return Err(OrderRejection::InAuction);
+        }
+
+        if self.orders_location.contains_key(&order_message.id) {
+            return Err(OrderRejection::DuplicateOrderId);
+        }
+
+        if let Some(client_order_id) = order_message.client_order_id.clone() {
+            let key = (order_message.account, client_order_id);
+
+            if self.client_order_ids.is_taken(&key, self.clock.now(), |order_id| {
+                self.orders_location.contains_key(order_id)
+            }) {
+                return Err(OrderRejection::DuplicateClientOrderId);
+            }
+        }
+
+        let target_side = self
+            .target_id(order_message)
+            .and_then(|target_id| self.orders_location.get(&target_id));
+
+        match order_message.r#type {
+            OrderType::Cancel => return target_side.map(|_| ()).ok_or(OrderRejection::UnknownOrder),
+            _ if order_message.side == OrderSide::NoSide => return Err(OrderRejection::MissingSide),
+            _ if order_message.volume.unwrap_or(0) == 0 => return Err(OrderRejection::InvalidVolume),
+            OrderType::Market => {
+                if order_message.side == OrderSide::Bid && order_message.max_quote.is_none() {
+                    return Err(OrderRejection::MissingMaxQuote);
+                }
+            }
+            _ if order_message.price.unwrap_or(0) == 0 => return Err(OrderRejection::InvalidPrice),
+            OrderType::Limit => {}
+            OrderType::Replace => match target_side {
+                None => return Err(OrderRejection::UnknownOrder),
+                Some(target_side) if *target_side != order_message.side => {
+                    return Err(OrderRejection::SideMismatch);
+                }
+                Some(_) => {}
+            },
+        }
+
+        self.check_funds(order_message)?;
+
+        if order_message.r#type != OrderType::Market
+            && order_message.time_in_force == TimeInForce::GoodTillDate
+            && order_message
+                .expires_at
+                .is_none_or(|expires_at| expires_at <= self.clock.now())
+        {
+            return Err(OrderRejection::InvalidExpiry);
+        }
+
+        if let Some(price_bands) = &self.price_bands {
+            let (low, high) = price_bands.static_range();
+
+            if order_message.r#type != OrderType::Market && !(low..=high).contains(&order_message.price.unwrap_or(0)) {
+                return Err(OrderRejection::OutsidePriceBand);
+            }
+        }
+
+        if self.breaches_price_band(order_message) {
+            return Err(OrderRejection::PriceBandBreach);
+        }
+
+        Ok(())
+    }
+
+    fn check_funds(&self, order_message: &OrderMessage) -> Result<(), OrderRejection> {
+        let (ledger, account) = match (&self.ledger, order_message.account) {
+            (Some(ledger), Some(account)) => (ledger, account),
+            _ => return Ok(()),
+        };
+        let released = self
+            .target_id(order_message)
+            .and_then(|target_id| ledger.reserved_for(&target_id))
+            .filter(|reservation| reservation.account == account)
+            .map_or(0, |reservation| reservation.amount);
+
+        ledger.check(account, order_message, released)
+    }
+
+    // Whether a rejecting band turns the order away before it trades.
+    fn breaches_price_band(&self, order_message: &OrderMessage) -> bool {
+        let price_bands = match &self.price_bands {
+            Some(price_bands) if price_bands.breach_action == BandBreachAction::Reject => price_bands,
+            _ => return false,
+        };
+
+        if self.auction.is_some() || order_message.r#type == OrderType::Cancel {
+            return false;
+        }
+
+        let band = price_bands.range(self.stats.last_price);
+
+        match order_message.side {
+            OrderSide::Bid => sweeps_outside(self.asks.orders.iter(), order_message, band),
+            _ => sweeps_outside(self.bids.orders.iter().rev(), order_message, band),
+        }
+    }
+
+    pub fn try_execute_order(
+        &mut self,
+        order_message: &OrderMessage,
+    ) -> Result<HashMap<Uuid, Vec<OrderEvent>>, OrderRejection> {
+        self.validate(order_message)?;
+
+        Ok(self.execute_order(order_message))
+    }
+
+    // Crosses the order with the other side, best level first, and returns its
+    // remaining volume. The matching policy splits each level's volume.
+    fn cross(
+        &mut self,
+        order_message: &OrderMessage,
+        current_timestamp: UTCNanoSeconds,
+        current_order_events: &mut Vec<OrderEvent>,
+        order_events: &mut HashMap<Uuid, Vec<OrderEvent>>,
+        resting_accounts: &mut HashMap<Uuid, Option<AccountId>>,
+    ) -> OrderVolume {
+        let current_order_id = order_message.id;
+        let current_order_side = order_message.side;
+        let mut order_remaining_volume = order_message.volume.unwrap();
+
+        if self.auction.is_some() {
+            return order_remaining_volume;
+        }
+
+        let available_quote = self
+            .ledger
+            .as_ref()
+            .zip(order_message.account)
+            .map(|(ledger, account)| ledger.balance(account).quote.available());
+        let (limit_price, mut order_remaining_quote) = match (order_message.r#type, current_order_side) {
+            (OrderType::Market, OrderSide::Bid) => (
+                None,
+                order_message.max_quote.map(|max_quote| {
+                    available_quote.map_or(max_quote, |available_quote| max_quote.min(available_quote))
+                }),
+            ),
+            (OrderType::Market, _) => (None, None),
+            _ => (order_message.price, None),
+        };
+        let matching_policy: &dyn MatchingPolicy = match &self.matching_policy {
+            Some(matching_policy) => matching_policy.as_ref(),
+            None => &FifoMatching,
+        };
+        let price_bands = self.price_bands;
+        let last_price = self.stats.last_price;
+        let band = price_bands.map(|price_bands| price_bands.range(last_price));
+        let orderbook = match current_order_side {
+            OrderSide::Bid => &mut self.asks,
+            OrderSide::Ask => &mut self.bids,
+            OrderSide::NoSide => panic!("Attempt to match CancelOrder: {}", current_order_id),
+        };
+        let mut band_breached = false;
+        let mut last_traded_price = None;
+        let touched_levels = &mut orderbook.touched_levels;
+        let mut pending_order_removal_id = Vec::new();
+        // Returns whether the order can go on to the next level.
+        let mut cross_level = |price_level: PriceLevel, price_level_orders: &mut Vec<OrderbookOrder>| {
+            let crosses = match (limit_price, current_order_side) {
+                (Some(order_price), OrderSide::Bid) => price_level <= order_price,
+                (Some(order_price), _) => price_level >= order_price,
+                (None, _) => true,
+            };
+
+            if !crosses {
+                return false;
+            }
+
+            if let Some((low, high)) = band {
+                if price_level < low || price_level > high {
+                    band_breached = true;
+                    return false;
+                }
+            }
+
+            let traded_price = Some(price_level);
+            touched_levels.insert(price_level);
+            let pricelevel_volume = match order_remaining_quote {
+                Some(quote) if order_remaining_volume.saturating_mul(price_level) > quote => quote / price_level,
+                _ => order_remaining_volume,
+            };
+
+            if pricelevel_volume == 0 {
+                return false;
+            }
+
+            let allocations = matching_policy.allocate(price_level_orders, pricelevel_volume);
+            let mut pricelevel_trade_volume = 0;
+
+            for (resting_order, allocated_volume) in price_level_orders.iter_mut().zip(allocations) {
+                if allocated_volume == 0 {
+                    continue;
+                }
+
+                let resting_order_id = resting_order.id;
+                resting_accounts.insert(resting_order_id, resting_order.account);
+                resting_order.remaining_volume -= allocated_volume;
+                order_remaining_volume -= allocated_volume;
+                pricelevel_trade_volume += allocated_volume;
+                current_order_events.push(OrderEvent {
+                    timestamp: current_timestamp,
+                    r#type: OrderEventType::HasMatch,
+                    remaining_volume: Some(order_remaining_volume),
+                    crossed_id: Some(resting_order_id),
+                    traded_price,
+                });
+                let mut resting_order_events = vec![OrderEvent {
+                    timestamp: current_timestamp,
+                    r#type: OrderEventType::HasMatch,
+                    remaining_volume: Some(resting_order.remaining_volume),
+                    crossed_id: Some(current_order_id),
+                    traded_price,
+                }];
+
+                if resting_order.remaining_volume == 0 {
+                    resting_order_events.push(OrderEvent {
+                        timestamp: current_timestamp,
+                        r#type: OrderEventType::Closed,
+                        remaining_volume: Some(0),
+                        crossed_id: None,
+                        traded_price: None,
+                    });
+                    pending_order_removal_id.push(resting_order_id);
+                }
+
+                order_events.insert(resting_order_id, resting_order_events);
+            }
+
+            if let Some(quote) = order_remaining_quote.as_mut() {
+                *quote -= pricelevel_trade_volume * price_level;
+            }
+
+            if pricelevel_trade_volume > 0 {
+                last_traded_price = traded_price;
+            }
+
+            order_remaining_volume > 0
+        };
+
+        if current_order_side == OrderSide::Bid {
+            for (price_level, price_level_orders) in orderbook.orders.iter_mut() {
+                if !cross_level(*price_level, price_level_orders) {
+                    break;
+                }
+            }
+        } else {
+            for (price_level, price_level_orders) in orderbook.orders.iter_mut().rev() {
+                if !cross_level(*price_level, price_level_orders) {
+                    break;
+                }
+            }
+        }
+
+        while let Some(pending_removal_id) = pending_order_removal_id.pop() {
+            orderbook.remove(&pending_removal_id);
+            self.orders_location.remove(&pending_removal_id);
+            self.order_expiries.remove(&pending_removal_id);
+            self.day_orders.remove(&pending_removal_id);
+        }
+
+        // A rejecting band turns the order away before it gets here.
+        if let Some(price_bands) = price_bands.filter(|_| band_breached) {
+            if price_bands.breach_action == BandBreachAction::VolatilityAuction {
+                self.start_volatility_auction(price_bands.volatility_auction_duration, last_traded_price);
+            }
+        }
+
+        order_remaining_volume
     }
 
     pub fn execute_order(&mut self, order_message: &OrderMessage) -> HashMap<Uuid, Vec<OrderEvent>> {
         let current_order_id = order_message.id;
         let current_order_type = order_message.r#type;
         let current_order_side = order_message.side;
-        let current_order_max_quote = order_message.max_quote;
         let current_order_volume = order_message.volume;
-        let current_order_price = order_message.price;
+        let current_timestamp = self.clock.now();
         let mut current_order_events = Vec::new();
         let mut order_events = HashMap::new();
         let current_order_events_ref = &mut current_order_events;
         let order_events_ref = &mut order_events;
-        let mut order_traded_volume = 0;
-        let mut pending_order_removal_id = Vec::new();
+        let target_id = self.target_id(order_message);
+        let mut resting_accounts = HashMap::new();
+        self.trades.clear();
+
+        // Without `validate` a rejecting band or missing funds still stop the
+        // whole order.
+        if self.breaches_price_band(order_message) || self.check_funds(order_message).is_err() {
+            current_order_events.push(OrderEvent {
+                timestamp: current_timestamp,
+                r#type: OrderEventType::Rejected,
+                remaining_volume: current_order_volume,
+                crossed_id: None,
+                traded_price: None,
+            });
+            order_events.insert(current_order_id, current_order_events);
+
+            return order_events;
+        }
 
         match current_order_type {
             OrderType::Cancel => {
-                let removed_order = self.remove(&order_message.target_id.unwrap()).unwrap();
-                let current_timestamp = Utc::now().timestamp_nanos();
+                let removed_order = self.remove(&target_id.unwrap()).unwrap();
                 let original_order_event = OrderEvent {
                     timestamp: current_timestamp,
                     r#type: OrderEventType::Cancelled,
@@ -162,215 +1333,25 @@ impl Orderbooks {
                 order_events_ref.insert(removed_order.id, [original_order_event].to_vec());
             }
             OrderType::Market => {
-                let mut order_remaining_volume = current_order_volume.unwrap();
-                match current_order_side {
-                    OrderSide::Ask => {
-                        if !self.bids.orders_location.is_empty() {
-                            let bid_orderbook_iter = self.bids.orders.iter_mut().rev();
-
-                            for (price_level_ref, next_bid_orders_ref) in bid_orderbook_iter {
-                                let price_level = *price_level_ref;
-                                let traded_price = Some(price_level);
-                                let pricelevel_bid_orders_iter = next_bid_orders_ref.iter_mut();
-
-                                for next_bid_order_ref in pricelevel_bid_orders_iter {
-                                    let bid_order_id = next_bid_order_ref.id;
-
-                                    if next_bid_order_ref.remaining_volume >= order_remaining_volume {
-                                        order_traded_volume += order_remaining_volume;
-                                        next_bid_order_ref.remaining_volume -= order_remaining_volume;
-                                        order_remaining_volume = 0;
-                                        current_order_events_ref.push(OrderEvent {
-                                            timestamp: Utc::now().timestamp_nanos(),
-                                            r#type: OrderEventType::HasMatch,
-                                            remaining_volume: Some(order_remaining_volume),
-                                            crossed_id: Some(bid_order_id),
-                                            traded_price,
-                                        });
-                                        let mut bid_order_events = Vec::new();
-                                        bid_order_events.push(OrderEvent {
-                                            timestamp: Utc::now().timestamp_nanos(),
-                                            r#type: OrderEventType::HasMatch,
-                                            remaining_volume: Some(next_bid_order_ref.remaining_volume),
-                                            crossed_id: Some(current_order_id),
-                                            traded_price,
-                                        });
-
-                                        if next_bid_order_ref.remaining_volume == 0 {
-                                            bid_order_events.push(OrderEvent {
-                                                timestamp: Utc::now().timestamp_nanos(),
-                                                r#type: OrderEventType::Closed,
-                                                remaining_volume: Some(0),
-                                                crossed_id: None,
-                                                traded_price: None,
-                                            });
-                                            pending_order_removal_id.push(bid_order_id);
-                                        }
-
-                                        order_events_ref.insert(bid_order_id, bid_order_events);
-                                        break;
-                                    } else {
-                                        let bid_order_traded_volume = next_bid_order_ref.remaining_volume;
-                                        next_bid_order_ref.remaining_volume = 0;
-                                        order_remaining_volume -= bid_order_traded_volume;
-                                        order_traded_volume += bid_order_traded_volume;
-                                        current_order_events_ref.push(OrderEvent {
-                                            timestamp: Utc::now().timestamp_nanos(),
-                                            r#type: OrderEventType::HasMatch,
-                                            remaining_volume: Some(order_remaining_volume),
-                                            crossed_id: Some(bid_order_id),
-                                            traded_price,
-                                        });
-                                        let mut bid_order_events = Vec::new();
-                                        bid_order_events.push(OrderEvent {
-                                            timestamp: Utc::now().timestamp_nanos(),
-                                            r#type: OrderEventType::HasMatch,
-                                            remaining_volume: Some(next_bid_order_ref.remaining_volume),
-                                            crossed_id: Some(current_order_id),
-                                            traded_price,
-                                        });
-                                        bid_order_events.push(OrderEvent {
-                                            timestamp: Utc::now().timestamp_nanos(),
-                                            r#type: OrderEventType::Closed,
-                                            remaining_volume: Some(0),
-                                            crossed_id: None,
-                                            traded_price: None,
-                                        });
-                                        pending_order_removal_id.push(bid_order_id);
-                                        order_events_ref.insert(bid_order_id, bid_order_events);
-                                    }
-
-                                    if order_remaining_volume == 0 {
-                                        break;
-                                    }
-                                }
-
-                                if order_remaining_volume == 0 {
-                                    break;
-                                }
-                            }
-
-                            while let Some(pending_removal_bid_id) = pending_order_removal_id.pop() {
-                                self.bids.remove(&pending_removal_bid_id);
-                            }
-                        }
-                    }
-                    OrderSide::Bid => {
-                        if !self.asks.orders_location.is_empty() {
-                            let mut order_remaining_quote = current_order_max_quote.unwrap();
-                            let ask_orderbook_iter = self.asks.orders.iter_mut();
-
-                            for (price_level_ref, next_ask_orders_ref) in ask_orderbook_iter {
-                                let price_level = *price_level_ref;
-                                let traded_price = Some(price_level);
-                                let pricelevel_ask_orders_iter = next_ask_orders_ref.iter_mut();
-                                let pricelevel_max_quote = order_remaining_volume * price_level;
-                                let mut pricelevel_trade_volume = 0;
-
-                                let mut remaining_pricelevel_volume = if pricelevel_max_quote > order_remaining_quote {
-                                    order_remaining_quote / price_level
-                                } else {
-                                    order_remaining_volume
-                                };
-
-                                if remaining_pricelevel_volume == 0 {
-                                    break;
-                                }
-
-                                for next_ask_order_ref in pricelevel_ask_orders_iter {
-                                    let ask_order_id = next_ask_order_ref.id;
-
-                                    if next_ask_order_ref.remaining_volume >= remaining_pricelevel_volume {
-                                        order_traded_volume += remaining_pricelevel_volume;
-                                        pricelevel_trade_volume += remaining_pricelevel_volume;
-                                        next_ask_order_ref.remaining_volume -= remaining_pricelevel_volume;
-                                        order_remaining_volume -= remaining_pricelevel_volume;
-                                        current_order_events_ref.push(OrderEvent {
-                                            timestamp: Utc::now().timestamp_nanos(),
-                                            r#type: OrderEventType::HasMatch,
-                                            remaining_volume: Some(order_remaining_volume),
-                                            crossed_id: Some(ask_order_id),
-                                            traded_price,
-                                        });
-                                        let mut ask_order_events = Vec::new();
-                                        ask_order_events.push(OrderEvent {
-                                            timestamp: Utc::now().timestamp_nanos(),
-                                            r#type: OrderEventType::HasMatch,
-                                            remaining_volume: Some(next_ask_order_ref.remaining_volume),
-                                            crossed_id: Some(current_order_id),
-                                            traded_price,
-                                        });
-
-                                        if next_ask_order_ref.remaining_volume == 0 {
-                                            ask_order_events.push(OrderEvent {
-                                                timestamp: Utc::now().timestamp_nanos(),
-                                                r#type: OrderEventType::Closed,
-                                                remaining_volume: Some(0),
-                                                crossed_id: None,
-                                                traded_price: None,
-                                            });
-                                            pending_order_removal_id.push(ask_order_id);
-                                        }
-
-                                        order_events_ref.insert(ask_order_id, ask_order_events);
-                                        break;
-                                    } else {
-                                        let ask_order_traded_volume = next_ask_order_ref.remaining_volume;
-                                        next_ask_order_ref.remaining_volume = 0;
-                                        order_remaining_volume -= ask_order_traded_volume;
-                                        order_traded_volume += ask_order_traded_volume;
-                                        pricelevel_trade_volume += ask_order_traded_volume;
-                                        remaining_pricelevel_volume -= ask_order_traded_volume;
-                                        current_order_events_ref.push(OrderEvent {
-                                            timestamp: Utc::now().timestamp_nanos(),
-                                            r#type: OrderEventType::HasMatch,
-                                            remaining_volume: Some(order_remaining_volume),
-                                            crossed_id: Some(ask_order_id),
-                                            traded_price,
-                                        });
-                                        let mut ask_order_events = Vec::new();
-                                        ask_order_events.push(OrderEvent {
-                                            timestamp: Utc::now().timestamp_nanos(),
-                                            r#type: OrderEventType::HasMatch,
-                                            remaining_volume: Some(next_ask_order_ref.remaining_volume),
-                                            crossed_id: Some(current_order_id),
-                                            traded_price,
-                                        });
-                                        ask_order_events.push(OrderEvent {
-                                            timestamp: Utc::now().timestamp_nanos(),
-                                            r#type: OrderEventType::Closed,
-                                            remaining_volume: Some(0),
-                                            crossed_id: None,
-                                            traded_price: None,
-                                        });
-                                        pending_order_removal_id.push(ask_order_id);
-                                        order_events_ref.insert(ask_order_id, ask_order_events);
-                                    }
-
-                                    if order_remaining_volume == 0 {
-                                        break;
-                                    }
-                                }
-
-                                order_remaining_quote -= pricelevel_trade_volume * price_level;
-                                if order_remaining_volume == 0 {
-                                    break;
-                                }
-                            }
-
-                            while let Some(pending_removal_ask_id) = pending_order_removal_id.pop() {
-                                self.asks.remove(&pending_removal_ask_id);
-                            }
-                        }
-                    }
-                    OrderSide::NoSide => {
-                        panic!("Attempt to executed CancelOrder as Market Order: {}", current_order_id)
-                    }
-                }
+                let order_remaining_volume = self.cross(
+                    order_message,
+                    current_timestamp,
+                    current_order_events_ref,
+                    order_events_ref,
+                    &mut resting_accounts,
+                );
+                let order_traded_volume = current_order_volume.unwrap() - order_remaining_volume;
+
+                self.record_trades(
+                    order_message,
+                    current_order_events_ref,
+                    order_events_ref,
+                    &resting_accounts,
+                );
 
                 if order_traded_volume == 0 {
                     current_order_events_ref.push(OrderEvent {
-                        timestamp: Utc::now().timestamp_nanos(),
+                        timestamp: current_timestamp,
                         r#type: OrderEventType::NoMatch,
                         remaining_volume: Some(order_remaining_volume),
                         crossed_id: None,
@@ -379,228 +1360,72 @@ impl Orderbooks {
                 }
 
                 current_order_events_ref.push(OrderEvent {
-                    timestamp: Utc::now().timestamp_nanos(),
+                    timestamp: current_timestamp,
                     r#type: OrderEventType::Closed,
                     remaining_volume: Some(order_remaining_volume),
                     crossed_id: None,
                     traded_price: None,
                 });
             }
-            OrderType::Limit => {
-                let order_price = current_order_price.unwrap();
-                let mut order_remaining_volume = current_order_volume.unwrap();
-
-                match current_order_side {
-                    OrderSide::Ask => {
-                        if self.bids.orders_location.is_empty() {
-                            self.insert(&order_message, order_remaining_volume);
-                        } else {
-                            let bid_orderbook_iter = self.bids.orders.iter_mut().rev();
-
-                            for (price_level_ref, next_bid_orders_ref) in bid_orderbook_iter {
-                                let price_level = *price_level_ref;
-
-                                if price_level < order_price {
-                                    break;
-                                }
-
-                                let traded_price = Some(price_level);
-                                let pricelevel_bid_orders_iter = next_bid_orders_ref.iter_mut();
-
-                                for next_bid_order_ref in pricelevel_bid_orders_iter {
-                                    let bid_order_id = next_bid_order_ref.id;
-
-                                    if next_bid_order_ref.remaining_volume >= order_remaining_volume {
-                                        order_traded_volume += order_remaining_volume;
-                                        next_bid_order_ref.remaining_volume -= order_remaining_volume;
-                                        order_remaining_volume = 0;
-                                        current_order_events_ref.push(OrderEvent {
-                                            timestamp: Utc::now().timestamp_nanos(),
-                                            r#type: OrderEventType::HasMatch,
-                                            remaining_volume: Some(order_remaining_volume),
-                                            crossed_id: Some(bid_order_id),
-                                            traded_price,
-                                        });
-                                        let mut bid_order_events = Vec::new();
-                                        bid_order_events.push(OrderEvent {
-                                            timestamp: Utc::now().timestamp_nanos(),
-                                            r#type: OrderEventType::HasMatch,
-                                            remaining_volume: Some(next_bid_order_ref.remaining_volume),
-                                            crossed_id: Some(current_order_id),
-                                            traded_price,
-                                        });
-
-                                        if next_bid_order_ref.remaining_volume == 0 {
-                                            bid_order_events.push(OrderEvent {
-                                                timestamp: Utc::now().timestamp_nanos(),
-                                                r#type: OrderEventType::Closed,
-                                                remaining_volume: Some(0),
-                                                crossed_id: None,
-                                                traded_price: None,
-                                            });
-                                            pending_order_removal_id.push(bid_order_id);
-                                        }
-
-                                        order_events_ref.insert(bid_order_id, bid_order_events);
-                                        break;
-                                    } else {
-                                        let bid_order_traded_volume = next_bid_order_ref.remaining_volume;
-                                        next_bid_order_ref.remaining_volume = 0;
-                                        order_remaining_volume -= bid_order_traded_volume;
-                                        order_traded_volume += bid_order_traded_volume;
-                                        current_order_events_ref.push(OrderEvent {
-                                            timestamp: Utc::now().timestamp_nanos(),
-                                            r#type: OrderEventType::HasMatch,
-                                            remaining_volume: Some(order_remaining_volume),
-                                            crossed_id: Some(bid_order_id),
-                                            traded_price,
-                                        });
-                                        let mut bid_order_events = Vec::new();
-                                        bid_order_events.push(OrderEvent {
-                                            timestamp: Utc::now().timestamp_nanos(),
-                                            r#type: OrderEventType::HasMatch,
-                                            remaining_volume: Some(next_bid_order_ref.remaining_volume),
-                                            crossed_id: Some(current_order_id),
-                                            traded_price,
-                                        });
-                                        bid_order_events.push(OrderEvent {
-                                            timestamp: Utc::now().timestamp_nanos(),
-                                            r#type: OrderEventType::Closed,
-                                            remaining_volume: Some(0),
-                                            crossed_id: None,
-                                            traded_price: None,
-                                        });
-                                        pending_order_removal_id.push(bid_order_id);
-                                        order_events_ref.insert(bid_order_id, bid_order_events);
-                                    }
-
-                                    if order_remaining_volume == 0 {
-                                        break;
-                                    }
-                                }
-
-                                if order_remaining_volume == 0 {
-                                    break;
-                                }
-                            }
-
-                            if order_remaining_volume > 0 {
-                                self.insert(&order_message, order_remaining_volume);
-                            }
-
-                            while let Some(pending_removal_bid_id) = pending_order_removal_id.pop() {
-                                self.bids.remove(&pending_removal_bid_id);
-                            }
-                        }
-                    }
-                    OrderSide::Bid => {
-                        if self.asks.orders_location.is_empty() {
-                            self.insert(&order_message, order_remaining_volume);
-                        } else {
-                            let ask_orderbook_iter = self.asks.orders.iter_mut();
-
-                            for (price_level_ref, next_ask_orders_ref) in ask_orderbook_iter {
-                                let price_level = *price_level_ref;
-
-                                if price_level > order_price {
-                                    break;
-                                }
-
-                                let traded_price = Some(price_level);
-                                let pricelevel_ask_orders_iter = next_ask_orders_ref.iter_mut();
-
-                                for next_ask_order_ref in pricelevel_ask_orders_iter {
-                                    let ask_order_id = next_ask_order_ref.id;
-
-                                    if next_ask_order_ref.remaining_volume >= order_remaining_volume {
-                                        order_traded_volume += order_remaining_volume;
-                                        next_ask_order_ref.remaining_volume -= order_remaining_volume;
-                                        order_remaining_volume = 0;
-                                        current_order_events_ref.push(OrderEvent {
-                                            timestamp: Utc::now().timestamp_nanos(),
-                                            r#type: OrderEventType::HasMatch,
-                                            remaining_volume: Some(order_remaining_volume),
-                                            crossed_id: Some(ask_order_id),
-                                            traded_price,
-                                        });
-                                        let mut bid_order_events = Vec::new();
-                                        bid_order_events.push(OrderEvent {
-                                            timestamp: Utc::now().timestamp_nanos(),
-                                            r#type: OrderEventType::HasMatch,
-                                            remaining_volume: Some(next_ask_order_ref.remaining_volume),
-                                            crossed_id: Some(current_order_id),
-                                            traded_price,
-                                        });
-
-                                        if next_ask_order_ref.remaining_volume == 0 {
-                                            bid_order_events.push(OrderEvent {
-                                                timestamp: Utc::now().timestamp_nanos(),
-                                                r#type: OrderEventType::Closed,
-                                                remaining_volume: Some(0),
-                                                crossed_id: None,
-                                                traded_price: None,
-                                            });
-                                            pending_order_removal_id.push(ask_order_id);
-                                        }
-
-                                        order_events_ref.insert(ask_order_id, bid_order_events);
-                                        break;
-                                    } else {
-                                        let ask_order_traded_volume = next_ask_order_ref.remaining_volume;
-                                        next_ask_order_ref.remaining_volume = 0;
-                                        order_remaining_volume -= ask_order_traded_volume;
-                                        order_traded_volume += ask_order_traded_volume;
-                                        current_order_events_ref.push(OrderEvent {
-                                            timestamp: Utc::now().timestamp_nanos(),
-                                            r#type: OrderEventType::HasMatch,
-                                            remaining_volume: Some(order_remaining_volume),
-                                            crossed_id: Some(ask_order_id),
-                                            traded_price,
-                                        });
-                                        let mut bid_order_events = Vec::new();
-                                        bid_order_events.push(OrderEvent {
-                                            timestamp: Utc::now().timestamp_nanos(),
-                                            r#type: OrderEventType::HasMatch,
-                                            remaining_volume: Some(next_ask_order_ref.remaining_volume),
-                                            crossed_id: Some(current_order_id),
-                                            traded_price,
-                                        });
-                                        bid_order_events.push(OrderEvent {
-                                            timestamp: Utc::now().timestamp_nanos(),
-                                            r#type: OrderEventType::Closed,
-                                            remaining_volume: Some(0),
-                                            crossed_id: None,
-                                            traded_price: None,
-                                        });
-                                        pending_order_removal_id.push(ask_order_id);
-                                        order_events_ref.insert(ask_order_id, bid_order_events);
-                                    }
-
-                                    if order_remaining_volume == 0 {
-                                        break;
-                                    }
-                                }
-
-                                if order_remaining_volume == 0 {
-                                    break;
-                                }
-                            }
-
-                            if order_remaining_volume > 0 {
-                                self.insert(&order_message, order_remaining_volume);
-                            }
-
-                            while let Some(pending_removal_ask_id) = pending_order_removal_id.pop() {
-                                self.asks.remove(&pending_removal_ask_id);
-                            }
-                        }
+            OrderType::Limit | OrderType::Replace => {
+                let replaced_order = if current_order_type == OrderType::Replace {
+                    let replaced_order_id = target_id.unwrap();
+                    let (replaced_side, replaced_price_level, replaced_order) =
+                        self.detach(&replaced_order_id).unwrap();
+
+                    if replaced_side != current_order_side {
+                        panic!("Attempt to replace order on the other side: {}", replaced_order_id);
                     }
-                    OrderSide::NoSide => panic!("Attempt to executed CancelOrder as Limit Order: {}", current_order_id),
+
+                    order_events_ref.insert(
+                        replaced_order_id,
+                        [OrderEvent {
+                            timestamp: current_timestamp,
+                            r#type: OrderEventType::Replaced,
+                            remaining_volume: Some(replaced_order.remaining_volume),
+                            crossed_id: Some(current_order_id),
+                            traded_price: None,
+                        }]
+                        .to_vec(),
+                    );
+
+                    Some((replaced_side, replaced_price_level, replaced_order))
+                } else {
+                    None
+                };
+
+                let order_remaining_volume = self.cross(
+                    order_message,
+                    current_timestamp,
+                    current_order_events_ref,
+                    order_events_ref,
+                    &mut resting_accounts,
+                );
+                let order_traded_volume = current_order_volume.unwrap() - order_remaining_volume;
+
+                self.record_trades(
+                    order_message,
+                    current_order_events_ref,
+                    order_events_ref,
+                    &resting_accounts,
+                );
+
+                if order_remaining_volume > 0 {
+                    self.insert(order_message, order_remaining_volume);
+                } else if let Some((replaced_side, replaced_price_level, replaced_order)) = replaced_order {
+                    self.publish_order_feed(OrderFeedMessage {
+                        r#type: OrderFeedMessageType::Cancel,
+                        order_id: replaced_order.id,
+                        side: replaced_side,
+                        price: replaced_price_level,
+                        volume: replaced_order.remaining_volume,
+                        ..Default::default()
+                    });
                 }
 
                 if order_traded_volume == 0 {
                     current_order_events_ref.push(OrderEvent {
-                        timestamp: Utc::now().timestamp_nanos(),
+                        timestamp: current_timestamp,
                         r#type: OrderEventType::NoMatch,
                         remaining_volume: Some(order_remaining_volume),
                         crossed_id: None,
@@ -610,7 +1435,7 @@ impl Orderbooks {
 
                 if order_remaining_volume > 0 {
                     current_order_events_ref.push(OrderEvent {
-                        timestamp: Utc::now().timestamp_nanos(),
+                        timestamp: current_timestamp,
                         r#type: OrderEventType::Open,
                         remaining_volume: Some(order_remaining_volume),
                         crossed_id: None,
@@ -618,7 +1443,7 @@ impl Orderbooks {
                     })
                 } else {
                     current_order_events_ref.push(OrderEvent {
-                        timestamp: Utc::now().timestamp_nanos(),
+                        timestamp: current_timestamp,
                         r#type: OrderEventType::Closed,
                         remaining_volume: Some(0),
                         crossed_id: None,
@@ -628,25 +1453,32 @@ impl Orderbooks {
             }
         }
 
+        // Only executed orders take their client order id.
+        let orders_location = &self.orders_location;
+        self.client_order_ids
+            .prune(current_timestamp, |order_id| orders_location.contains_key(order_id));
+
+        if let Some(client_order_id) = order_message.client_order_id.clone() {
+            self.client_order_ids.record(
+                (order_message.account, client_order_id),
+                current_order_id,
+                current_timestamp,
+            );
+        }
+
         order_events_ref.insert(current_order_id, current_order_events);
+        self.publish_depth();
+        self.publish_indication();
 
         order_events
     }
 }
 
-impl Default for Orderbooks {
-    fn default() -> Orderbooks {
-        Orderbooks {
-            bids: Orderbook::default(),
-            asks: Orderbook::default(),
-            orders_location: HashMap::default(),
-        }
-    }
-}
-
 #[cfg(test)]
 mod unit_test {
+    use super::test_support::*;
     use super::*;
+    use chrono::prelude::*;
 
     #[test]
     fn test_limit_insert_on_empty_books() {
@@ -654,13 +1486,14 @@ mod unit_test {
         let new_limit_order = OrderMessage {
             id: Uuid::new_v4(),
             target_id: None,
-            created_at: Utc::now().timestamp_nanos(),
+            created_at: Utc::now().timestamp_nanos_opt().unwrap(),
             side: OrderSide::Ask,
             r#type: OrderType::Limit,
             volume: Some(38_000),
             price: Some(9_800_000),
             max_quote: None,
             events: Vec::new(),
+            ..Default::default()
         };
         let events = the_orderbooks.execute_order(&new_limit_order);
 
@@ -675,13 +1508,14 @@ mod unit_test {
         let new_market_order = OrderMessage {
             id: Uuid::new_v4(),
             target_id: None,
-            created_at: Utc::now().timestamp_nanos(),
+            created_at: Utc::now().timestamp_nanos_opt().unwrap(),
             side: OrderSide::Ask,
             r#type: OrderType::Market,
             volume: Some(38_000),
             price: None,
             max_quote: None,
             events: Vec::new(),
+            ..Default::default()
         };
         let events = the_orderbooks.execute_order(&new_market_order);
 
@@ -696,24 +1530,26 @@ mod unit_test {
         let new_limit_order = OrderMessage {
             id: order_id,
             target_id: None,
-            created_at: Utc::now().timestamp_nanos(),
+            created_at: Utc::now().timestamp_nanos_opt().unwrap(),
             side: OrderSide::Bid,
             r#type: OrderType::Limit,
             volume: Some(38_000),
             price: Some(9_800_000),
             max_quote: None,
             events: Vec::new(),
+            ..Default::default()
         };
         let new_cancel_order = OrderMessage {
             id: Uuid::new_v4(),
             target_id: Some(order_id),
-            created_at: Utc::now().timestamp_nanos(),
+            created_at: Utc::now().timestamp_nanos_opt().unwrap(),
             side: OrderSide::NoSide,
             r#type: OrderType::Cancel,
             volume: None,
             price: None,
             max_quote: None,
             events: Vec::new(),
+            ..Default::default()
         };
         the_orderbooks.execute_order(&new_limit_order);
         let events = the_orderbooks.execute_order(&new_cancel_order);
@@ -728,24 +1564,26 @@ mod unit_test {
         let new_limit_order = OrderMessage {
             id: Uuid::new_v4(),
             target_id: None,
-            created_at: Utc::now().timestamp_nanos(),
+            created_at: Utc::now().timestamp_nanos_opt().unwrap(),
             side: OrderSide::Ask,
             r#type: OrderType::Limit,
             volume: Some(10),
             price: Some(100),
             max_quote: None,
             events: Vec::new(),
+            ..Default::default()
         };
         let new_market_order = OrderMessage {
             id: Uuid::new_v4(),
             target_id: None,
-            created_at: Utc::now().timestamp_nanos(),
+            created_at: Utc::now().timestamp_nanos_opt().unwrap(),
             side: OrderSide::Bid,
             r#type: OrderType::Market,
             volume: Some(10),
             price: None,
             max_quote: Some(1000),
             events: Vec::new(),
+            ..Default::default()
         };
         let limit_events = the_orderbooks.execute_order(&new_limit_order);
         let market_events = the_orderbooks.execute_order(&new_market_order);
@@ -761,24 +1599,26 @@ mod unit_test {
         let new_limit_order = OrderMessage {
             id: Uuid::new_v4(),
             target_id: None,
-            created_at: Utc::now().timestamp_nanos(),
+            created_at: Utc::now().timestamp_nanos_opt().unwrap(),
             side: OrderSide::Bid,
             r#type: OrderType::Limit,
             volume: Some(10),
             price: Some(100),
             max_quote: None,
             events: Vec::new(),
+            ..Default::default()
         };
         let new_market_order = OrderMessage {
             id: Uuid::new_v4(),
             target_id: None,
-            created_at: Utc::now().timestamp_nanos(),
+            created_at: Utc::now().timestamp_nanos_opt().unwrap(),
             side: OrderSide::Ask,
             r#type: OrderType::Market,
             volume: Some(10),
             price: None,
             max_quote: None,
             events: Vec::new(),
+            ..Default::default()
         };
         let limit_insertion_events = the_orderbooks.execute_order(&new_limit_order);
         let market_execution_events = the_orderbooks.execute_order(&new_market_order);
@@ -794,24 +1634,26 @@ mod unit_test {
         let new_limit_order = OrderMessage {
             id: Uuid::new_v4(),
             target_id: None,
-            created_at: Utc::now().timestamp_nanos(),
+            created_at: Utc::now().timestamp_nanos_opt().unwrap(),
             side: OrderSide::Ask,
             r#type: OrderType::Limit,
             volume: Some(10),
             price: Some(100),
             max_quote: None,
             events: Vec::new(),
+            ..Default::default()
         };
         let new_market_order = OrderMessage {
             id: Uuid::new_v4(),
             target_id: None,
-            created_at: Utc::now().timestamp_nanos(),
+            created_at: Utc::now().timestamp_nanos_opt().unwrap(),
             side: OrderSide::Bid,
             r#type: OrderType::Market,
             volume: Some(9),
             price: None,
             max_quote: Some(1000),
             events: Vec::new(),
+            ..Default::default()
         };
         let limit_id = new_limit_order.id;
         let market_id = new_market_order.id;
@@ -842,24 +1684,26 @@ mod unit_test {
         let new_limit_order = OrderMessage {
             id: Uuid::new_v4(),
             target_id: None,
-            created_at: Utc::now().timestamp_nanos(),
+            created_at: Utc::now().timestamp_nanos_opt().unwrap(),
             side: OrderSide::Bid,
             r#type: OrderType::Limit,
             volume: Some(10),
             price: Some(100),
             max_quote: None,
             events: Vec::new(),
+            ..Default::default()
         };
         let new_market_order = OrderMessage {
             id: Uuid::new_v4(),
             target_id: None,
-            created_at: Utc::now().timestamp_nanos(),
+            created_at: Utc::now().timestamp_nanos_opt().unwrap(),
             side: OrderSide::Ask,
             r#type: OrderType::Market,
             volume: Some(9),
             price: None,
             max_quote: None,
             events: Vec::new(),
+            ..Default::default()
         };
         let limit_id = new_limit_order.id;
         let market_id = new_market_order.id;
@@ -890,35 +1734,38 @@ mod unit_test {
         let new_limit_order_0 = OrderMessage {
             id: Uuid::new_v4(),
             target_id: None,
-            created_at: Utc::now().timestamp_nanos(),
+            created_at: Utc::now().timestamp_nanos_opt().unwrap(),
             side: OrderSide::Bid,
             r#type: OrderType::Limit,
             volume: Some(5),
             price: Some(100),
             max_quote: None,
             events: Vec::new(),
+            ..Default::default()
         };
         let new_limit_order_1 = OrderMessage {
             id: Uuid::new_v4(),
             target_id: None,
-            created_at: Utc::now().timestamp_nanos(),
+            created_at: Utc::now().timestamp_nanos_opt().unwrap(),
             side: OrderSide::Bid,
             r#type: OrderType::Limit,
             volume: Some(5),
             price: Some(200),
             max_quote: None,
             events: Vec::new(),
+            ..Default::default()
         };
         let new_market_order = OrderMessage {
             id: Uuid::new_v4(),
             target_id: None,
-            created_at: Utc::now().timestamp_nanos(),
+            created_at: Utc::now().timestamp_nanos_opt().unwrap(),
             side: OrderSide::Ask,
             r#type: OrderType::Market,
             volume: Some(10),
             price: None,
             max_quote: None,
             events: Vec::new(),
+            ..Default::default()
         };
         the_orderbooks.execute_order(&new_limit_order_0);
         the_orderbooks.execute_order(&new_limit_order_1);
@@ -934,35 +1781,38 @@ mod unit_test {
         let new_limit_order_0 = OrderMessage {
             id: Uuid::new_v4(),
             target_id: None,
-            created_at: Utc::now().timestamp_nanos(),
+            created_at: Utc::now().timestamp_nanos_opt().unwrap(),
             side: OrderSide::Ask,
             r#type: OrderType::Limit,
             volume: Some(5),
             price: Some(100),
             max_quote: None,
             events: Vec::new(),
+            ..Default::default()
         };
         let new_limit_order_1 = OrderMessage {
             id: Uuid::new_v4(),
             target_id: None,
-            created_at: Utc::now().timestamp_nanos(),
+            created_at: Utc::now().timestamp_nanos_opt().unwrap(),
             side: OrderSide::Ask,
             r#type: OrderType::Limit,
             volume: Some(5),
             price: Some(200),
             max_quote: None,
             events: Vec::new(),
+            ..Default::default()
         };
         let new_market_order = OrderMessage {
             id: Uuid::new_v4(),
             target_id: None,
-            created_at: Utc::now().timestamp_nanos(),
+            created_at: Utc::now().timestamp_nanos_opt().unwrap(),
             side: OrderSide::Bid,
             r#type: OrderType::Market,
             volume: Some(10),
             price: None,
             max_quote: Some(1500),
             events: Vec::new(),
+            ..Default::default()
         };
         the_orderbooks.execute_order(&new_limit_order_0);
         the_orderbooks.execute_order(&new_limit_order_1);
@@ -978,35 +1828,38 @@ mod unit_test {
         let new_limit_order_0 = OrderMessage {
             id: Uuid::new_v4(),
             target_id: None,
-            created_at: Utc::now().timestamp_nanos(),
+            created_at: Utc::now().timestamp_nanos_opt().unwrap(),
             side: OrderSide::Bid,
             r#type: OrderType::Limit,
             volume: Some(5),
             price: Some(100),
             max_quote: None,
             events: Vec::new(),
+            ..Default::default()
         };
         let new_limit_order_1 = OrderMessage {
             id: Uuid::new_v4(),
             target_id: None,
-            created_at: Utc::now().timestamp_nanos(),
+            created_at: Utc::now().timestamp_nanos_opt().unwrap(),
             side: OrderSide::Bid,
             r#type: OrderType::Limit,
             volume: Some(5),
             price: Some(200),
             max_quote: None,
             events: Vec::new(),
+            ..Default::default()
         };
         let new_market_order = OrderMessage {
             id: Uuid::new_v4(),
             target_id: None,
-            created_at: Utc::now().timestamp_nanos(),
+            created_at: Utc::now().timestamp_nanos_opt().unwrap(),
             side: OrderSide::Ask,
             r#type: OrderType::Market,
             volume: Some(20),
             price: None,
             max_quote: None,
             events: Vec::new(),
+            ..Default::default()
         };
         the_orderbooks.execute_order(&new_limit_order_0);
         the_orderbooks.execute_order(&new_limit_order_1);
@@ -1027,35 +1880,38 @@ mod unit_test {
         let new_limit_order_0 = OrderMessage {
             id: Uuid::new_v4(),
             target_id: None,
-            created_at: Utc::now().timestamp_nanos(),
+            created_at: Utc::now().timestamp_nanos_opt().unwrap(),
             side: OrderSide::Ask,
             r#type: OrderType::Limit,
             volume: Some(5),
             price: Some(100),
             max_quote: None,
             events: Vec::new(),
+            ..Default::default()
         };
         let new_limit_order_1 = OrderMessage {
             id: Uuid::new_v4(),
             target_id: None,
-            created_at: Utc::now().timestamp_nanos(),
+            created_at: Utc::now().timestamp_nanos_opt().unwrap(),
             side: OrderSide::Ask,
             r#type: OrderType::Limit,
             volume: Some(5),
             price: Some(200),
             max_quote: None,
             events: Vec::new(),
+            ..Default::default()
         };
         let new_market_order = OrderMessage {
             id: Uuid::new_v4(),
             target_id: None,
-            created_at: Utc::now().timestamp_nanos(),
+            created_at: Utc::now().timestamp_nanos_opt().unwrap(),
             side: OrderSide::Bid,
             r#type: OrderType::Market,
             volume: Some(20),
             price: None,
             max_quote: Some(100),
             events: Vec::new(),
+            ..Default::default()
         };
         the_orderbooks.execute_order(&new_limit_order_0);
         the_orderbooks.execute_order(&new_limit_order_1);
@@ -1076,24 +1932,26 @@ mod unit_test {
         let new_limit_order_0 = OrderMessage {
             id: Uuid::new_v4(),
             target_id: None,
-            created_at: Utc::now().timestamp_nanos(),
+            created_at: Utc::now().timestamp_nanos_opt().unwrap(),
             side: OrderSide::Ask,
             r#type: OrderType::Limit,
             volume: Some(10),
             price: Some(100),
             max_quote: None,
             events: Vec::new(),
+            ..Default::default()
         };
         let new_limit_order_1 = OrderMessage {
             id: Uuid::new_v4(),
             target_id: None,
-            created_at: Utc::now().timestamp_nanos(),
+            created_at: Utc::now().timestamp_nanos_opt().unwrap(),
             side: OrderSide::Bid,
             r#type: OrderType::Limit,
             volume: Some(10),
             price: Some(200),
             max_quote: None,
             events: Vec::new(),
+            ..Default::default()
         };
         let insertion_events = the_orderbooks.execute_order(&new_limit_order_0);
         let execution_events = the_orderbooks.execute_order(&new_limit_order_1);
@@ -1109,24 +1967,26 @@ mod unit_test {
         let new_limit_order_0 = OrderMessage {
             id: Uuid::new_v4(),
             target_id: None,
-            created_at: Utc::now().timestamp_nanos(),
+            created_at: Utc::now().timestamp_nanos_opt().unwrap(),
             side: OrderSide::Bid,
             r#type: OrderType::Limit,
             volume: Some(10),
             price: Some(200),
             max_quote: None,
             events: Vec::new(),
+            ..Default::default()
         };
         let new_limit_order_1 = OrderMessage {
             id: Uuid::new_v4(),
             target_id: None,
-            created_at: Utc::now().timestamp_nanos(),
+            created_at: Utc::now().timestamp_nanos_opt().unwrap(),
             side: OrderSide::Ask,
             r#type: OrderType::Limit,
             volume: Some(10),
             price: Some(100),
             max_quote: None,
             events: Vec::new(),
+            ..Default::default()
         };
         let insertion_events = the_orderbooks.execute_order(&new_limit_order_0);
         let execution_events = the_orderbooks.execute_order(&new_limit_order_1);
@@ -1142,24 +2002,26 @@ mod unit_test {
         let new_limit_order_0 = OrderMessage {
             id: Uuid::new_v4(),
             target_id: None,
-            created_at: Utc::now().timestamp_nanos(),
+            created_at: Utc::now().timestamp_nanos_opt().unwrap(),
             side: OrderSide::Ask,
             r#type: OrderType::Limit,
             volume: Some(12),
             price: Some(100),
             max_quote: None,
             events: Vec::new(),
+            ..Default::default()
         };
         let new_limit_order_1 = OrderMessage {
             id: Uuid::new_v4(),
             target_id: None,
-            created_at: Utc::now().timestamp_nanos(),
+            created_at: Utc::now().timestamp_nanos_opt().unwrap(),
             side: OrderSide::Bid,
             r#type: OrderType::Limit,
             volume: Some(10),
             price: Some(200),
             max_quote: None,
             events: Vec::new(),
+            ..Default::default()
         };
         let insertion_events = the_orderbooks.execute_order(&new_limit_order_0);
         let execution_events = the_orderbooks.execute_order(&new_limit_order_1);
@@ -1177,24 +2039,26 @@ mod unit_test {
         let new_limit_order_0 = OrderMessage {
             id: Uuid::new_v4(),
             target_id: None,
-            created_at: Utc::now().timestamp_nanos(),
+            created_at: Utc::now().timestamp_nanos_opt().unwrap(),
             side: OrderSide::Bid,
             r#type: OrderType::Limit,
             volume: Some(12),
             price: Some(200),
             max_quote: None,
             events: Vec::new(),
+            ..Default::default()
         };
         let new_limit_order_1 = OrderMessage {
             id: Uuid::new_v4(),
             target_id: None,
-            created_at: Utc::now().timestamp_nanos(),
+            created_at: Utc::now().timestamp_nanos_opt().unwrap(),
             side: OrderSide::Ask,
             r#type: OrderType::Limit,
             volume: Some(10),
             price: Some(100),
             max_quote: None,
             events: Vec::new(),
+            ..Default::default()
         };
         let insertion_events = the_orderbooks.execute_order(&new_limit_order_0);
         let execution_events = the_orderbooks.execute_order(&new_limit_order_1);
@@ -1212,35 +2076,38 @@ mod unit_test {
         let new_limit_order_0 = OrderMessage {
             id: Uuid::new_v4(),
             target_id: None,
-            created_at: Utc::now().timestamp_nanos(),
+            created_at: Utc::now().timestamp_nanos_opt().unwrap(),
             side: OrderSide::Ask,
             r#type: OrderType::Limit,
             volume: Some(10),
             price: Some(100),
             max_quote: None,
             events: Vec::new(),
+            ..Default::default()
         };
         let new_limit_order_1 = OrderMessage {
             id: Uuid::new_v4(),
             target_id: None,
-            created_at: Utc::now().timestamp_nanos(),
+            created_at: Utc::now().timestamp_nanos_opt().unwrap(),
             side: OrderSide::Ask,
             r#type: OrderType::Limit,
             volume: Some(10),
             price: Some(100),
             max_quote: None,
             events: Vec::new(),
+            ..Default::default()
         };
         let new_limit_order_2 = OrderMessage {
             id: Uuid::new_v4(),
             target_id: None,
-            created_at: Utc::now().timestamp_nanos(),
+            created_at: Utc::now().timestamp_nanos_opt().unwrap(),
             side: OrderSide::Bid,
             r#type: OrderType::Limit,
             volume: Some(20),
             price: Some(200),
             max_quote: None,
             events: Vec::new(),
+            ..Default::default()
         };
         the_orderbooks.execute_order(&new_limit_order_0);
         the_orderbooks.execute_order(&new_limit_order_1);
@@ -1256,35 +2123,38 @@ mod unit_test {
         let new_limit_order_0 = OrderMessage {
             id: Uuid::new_v4(),
             target_id: None,
-            created_at: Utc::now().timestamp_nanos(),
+            created_at: Utc::now().timestamp_nanos_opt().unwrap(),
             side: OrderSide::Bid,
             r#type: OrderType::Limit,
             volume: Some(10),
             price: Some(200),
             max_quote: None,
             events: Vec::new(),
+            ..Default::default()
         };
         let new_limit_order_1 = OrderMessage {
             id: Uuid::new_v4(),
             target_id: None,
-            created_at: Utc::now().timestamp_nanos(),
+            created_at: Utc::now().timestamp_nanos_opt().unwrap(),
             side: OrderSide::Bid,
             r#type: OrderType::Limit,
             volume: Some(10),
             price: Some(200),
             max_quote: None,
             events: Vec::new(),
+            ..Default::default()
         };
         let new_limit_order_2 = OrderMessage {
             id: Uuid::new_v4(),
             target_id: None,
-            created_at: Utc::now().timestamp_nanos(),
+            created_at: Utc::now().timestamp_nanos_opt().unwrap(),
             side: OrderSide::Ask,
             r#type: OrderType::Limit,
             volume: Some(20),
             price: Some(100),
             max_quote: None,
             events: Vec::new(),
+            ..Default::default()
         };
         the_orderbooks.execute_order(&new_limit_order_0);
         the_orderbooks.execute_order(&new_limit_order_1);
@@ -1300,35 +2170,38 @@ mod unit_test {
         let new_limit_order_0 = OrderMessage {
             id: Uuid::new_v4(),
             target_id: None,
-            created_at: Utc::now().timestamp_nanos(),
+            created_at: Utc::now().timestamp_nanos_opt().unwrap(),
             side: OrderSide::Ask,
             r#type: OrderType::Limit,
             volume: Some(6),
             price: Some(100),
             max_quote: None,
             events: Vec::new(),
+            ..Default::default()
         };
         let new_limit_order_1 = OrderMessage {
             id: Uuid::new_v4(),
             target_id: None,
-            created_at: Utc::now().timestamp_nanos(),
+            created_at: Utc::now().timestamp_nanos_opt().unwrap(),
             side: OrderSide::Ask,
             r#type: OrderType::Limit,
             volume: Some(6),
             price: Some(200),
             max_quote: None,
             events: Vec::new(),
+            ..Default::default()
         };
         let new_limit_order_2 = OrderMessage {
             id: Uuid::new_v4(),
             target_id: None,
-            created_at: Utc::now().timestamp_nanos(),
+            created_at: Utc::now().timestamp_nanos_opt().unwrap(),
             side: OrderSide::Bid,
             r#type: OrderType::Limit,
             volume: Some(10),
             price: Some(200),
             max_quote: None,
             events: Vec::new(),
+            ..Default::default()
         };
         the_orderbooks.execute_order(&new_limit_order_0);
         the_orderbooks.execute_order(&new_limit_order_1);
@@ -1346,35 +2219,38 @@ mod unit_test {
         let new_limit_order_0 = OrderMessage {
             id: Uuid::new_v4(),
             target_id: None,
-            created_at: Utc::now().timestamp_nanos(),
+            created_at: Utc::now().timestamp_nanos_opt().unwrap(),
             side: OrderSide::Bid,
             r#type: OrderType::Limit,
             volume: Some(5),
             price: Some(200),
             max_quote: None,
             events: Vec::new(),
+            ..Default::default()
         };
         let new_limit_order_1 = OrderMessage {
             id: Uuid::new_v4(),
             target_id: None,
-            created_at: Utc::now().timestamp_nanos(),
+            created_at: Utc::now().timestamp_nanos_opt().unwrap(),
             side: OrderSide::Bid,
             r#type: OrderType::Limit,
             volume: Some(10),
             price: Some(100),
             max_quote: None,
             events: Vec::new(),
+            ..Default::default()
         };
         let new_limit_order_2 = OrderMessage {
             id: Uuid::new_v4(),
             target_id: None,
-            created_at: Utc::now().timestamp_nanos(),
+            created_at: Utc::now().timestamp_nanos_opt().unwrap(),
             side: OrderSide::Ask,
             r#type: OrderType::Limit,
             volume: Some(10),
             price: Some(100),
             max_quote: None,
             events: Vec::new(),
+            ..Default::default()
         };
         the_orderbooks.execute_order(&new_limit_order_0);
         the_orderbooks.execute_order(&new_limit_order_1);
@@ -1385,4 +2261,228 @@ mod unit_test {
         assert_eq!(the_orderbooks.count().1, 0);
         assert_eq!(the_orderbooks.count().2, 1);
     }
+
+    #[test]
+    fn test_try_execute_order_rejections() {
+        let mut the_orderbooks = Orderbooks::default();
+        let bid_order = OrderMessage {
+            side: OrderSide::Bid,
+            r#type: OrderType::Limit,
+            volume: Some(10),
+            price: Some(100),
+            ..Default::default()
+        };
+        let unknown_cancel = OrderMessage {
+            target_id: Some(Uuid::new_v4()),
+            r#type: OrderType::Cancel,
+            ..Default::default()
+        };
+        let market_bid = OrderMessage {
+            side: OrderSide::Bid,
+            r#type: OrderType::Market,
+            volume: Some(10),
+            ..Default::default()
+        };
+        let ask_replace = OrderMessage {
+            target_id: Some(bid_order.id),
+            side: OrderSide::Ask,
+            r#type: OrderType::Replace,
+            volume: Some(10),
+            price: Some(101),
+            ..Default::default()
+        };
+        let bid_cancel = OrderMessage {
+            target_id: Some(bid_order.id),
+            r#type: OrderType::Cancel,
+            ..Default::default()
+        };
+
+        assert!(the_orderbooks.try_execute_order(&bid_order).is_ok());
+        assert_eq!(
+            the_orderbooks.try_execute_order(&bid_order).unwrap_err(),
+            OrderRejection::DuplicateOrderId
+        );
+        assert_eq!(
+            the_orderbooks.validate(&unknown_cancel),
+            Err(OrderRejection::UnknownOrder)
+        );
+        assert_eq!(
+            the_orderbooks.validate(&market_bid),
+            Err(OrderRejection::MissingMaxQuote)
+        );
+        assert_eq!(the_orderbooks.validate(&ask_replace), Err(OrderRejection::SideMismatch));
+
+        let (side, price_level, resting_order) = the_orderbooks.lookup(&bid_order.id).unwrap();
+
+        assert_eq!(side, OrderSide::Bid);
+        assert_eq!(price_level, 100);
+        assert_eq!(resting_order.remaining_volume, 10);
+
+        the_orderbooks.halt().unwrap();
+
+        assert_eq!(
+            the_orderbooks.validate(&market_bid),
+            Err(OrderRejection::Session(SessionState::Halted))
+        );
+        assert!(the_orderbooks.try_execute_order(&bid_cancel).is_ok());
+        assert!(the_orderbooks.lookup(&bid_order.id).is_none());
+    }
+
+    #[test]
+    fn test_pro_rata_matching_policy() {
+        let mut the_orderbooks = Orderbooks::default();
+        the_orderbooks.set_matching_policy(ProRataMatching {
+            remainder: ProRataRemainder::LargestFraction,
+        });
+        let ask_ids: Vec<Uuid> = [10, 30, 60]
+            .iter()
+            .map(|volume| {
+                let ask_order = OrderMessage {
+                    side: OrderSide::Ask,
+                    r#type: OrderType::Limit,
+                    volume: Some(*volume),
+                    price: Some(100),
+                    ..Default::default()
+                };
+                the_orderbooks.execute_order(&ask_order);
+                ask_order.id
+            })
+            .collect();
+        let bid_order = OrderMessage {
+            side: OrderSide::Bid,
+            r#type: OrderType::Limit,
+            volume: Some(8),
+            price: Some(100),
+            ..Default::default()
+        };
+        let events = the_orderbooks.execute_order(&bid_order);
+        let traded: Vec<(Uuid, OrderVolume)> = the_orderbooks
+            .trades()
+            .iter()
+            .map(|trade| (trade.resting_id, trade.volume))
+            .collect();
+
+        assert_eq!(traded, vec![(ask_ids[0], 1), (ask_ids[1], 2), (ask_ids[2], 5)]);
+        assert_eq!(events[&bid_order.id].last().unwrap().r#type, OrderEventType::Closed);
+        assert_eq!(the_orderbooks.lookup(&ask_ids[0]).unwrap().2.remaining_volume, 9);
+        assert_eq!(the_orderbooks.lookup(&ask_ids[2]).unwrap().2.remaining_volume, 55);
+        assert_eq!(the_orderbooks.asks.volume(100), 92);
+    }
+
+    #[test]
+    fn test_good_till_date_and_day_orders_expire() {
+        let mut the_orderbooks = Orderbooks::default();
+        the_orderbooks.set_clock(EngineClock::Manual(1_000));
+        let timed_order = |time_in_force: TimeInForce, expires_at: Option<UTCNanoSeconds>| OrderMessage {
+            time_in_force,
+            expires_at,
+            ..limit_order(OrderSide::Bid, 10, 100)
+        };
+        let early_order = timed_order(TimeInForce::GoodTillDate, Some(2_000));
+        let late_order = timed_order(TimeInForce::GoodTillDate, Some(3_000));
+        let day_order = timed_order(TimeInForce::Day, None);
+        let resting_order = timed_order(TimeInForce::GoodTillCancel, None);
+
+        assert_eq!(
+            the_orderbooks.validate(&timed_order(TimeInForce::GoodTillDate, Some(1_000))),
+            Err(OrderRejection::InvalidExpiry)
+        );
+        assert_eq!(
+            the_orderbooks.validate(&timed_order(TimeInForce::GoodTillDate, None)),
+            Err(OrderRejection::InvalidExpiry)
+        );
+
+        for order in [&early_order, &late_order, &day_order, &resting_order].iter() {
+            the_orderbooks.try_execute_order(order).unwrap();
+        }
+
+        the_orderbooks.remove(&late_order.id);
+
+        assert!(the_orderbooks.expire_until(1_999).is_empty());
+
+        let events = the_orderbooks.expire_until(5_000);
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[&early_order.id][0].r#type, OrderEventType::Expired);
+        assert_eq!(events[&early_order.id][0].remaining_volume, Some(10));
+        assert!(the_orderbooks.expiries.is_empty());
+
+        let events = the_orderbooks.transition(SessionState::Closed).unwrap();
+
+        assert_eq!(events[&day_order.id][0].r#type, OrderEventType::Expired);
+        assert_eq!(the_orderbooks.count(), (1, 0, 1));
+        assert!(the_orderbooks.contains(&resting_order.id));
+    }
+
+    #[test]
+    fn test_reused_id_is_not_expired_by_a_filled_order() {
+        let mut the_orderbooks = Orderbooks::default();
+        the_orderbooks.set_clock(EngineClock::Manual(1_000));
+        let good_till_date_order = OrderMessage {
+            time_in_force: TimeInForce::GoodTillDate,
+            expires_at: Some(2_000),
+            ..limit_order(OrderSide::Bid, 10, 100)
+        };
+        let day_order = OrderMessage {
+            time_in_force: TimeInForce::Day,
+            ..limit_order(OrderSide::Bid, 10, 100)
+        };
+
+        for order in [&good_till_date_order, &day_order].iter() {
+            the_orderbooks.try_execute_order(order).unwrap();
+        }
+
+        the_orderbooks
+            .try_execute_order(&limit_order(OrderSide::Ask, 20, 100))
+            .unwrap();
+
+        assert!(the_orderbooks.order_expiries.is_empty());
+        assert!(the_orderbooks.day_orders.is_empty());
+
+        let reused_orders = [
+            OrderMessage {
+                id: good_till_date_order.id,
+                ..limit_order(OrderSide::Bid, 5, 90)
+            },
+            OrderMessage {
+                id: day_order.id,
+                ..limit_order(OrderSide::Bid, 5, 90)
+            },
+        ];
+
+        for order in reused_orders.iter() {
+            the_orderbooks.try_execute_order(order).unwrap();
+        }
+
+        assert!(the_orderbooks.expire_until(5_000).is_empty());
+        assert!(the_orderbooks.transition(SessionState::Closed).unwrap().is_empty());
+        assert_eq!(the_orderbooks.count(), (2, 0, 2));
+    }
+
+    #[test]
+    fn test_disconnect_session_cancels_resting_orders() {
+        let mut the_orderbooks = Orderbooks::default();
+        let session_order = |price: OrderPrice, session_id: SessionId| OrderMessage {
+            session_id: Some(session_id),
+            ..limit_order(OrderSide::Ask, 10, price)
+        };
+        let orders = [session_order(100, 1), session_order(100, 2), session_order(101, 1)];
+
+        for order in orders.iter() {
+            the_orderbooks.try_execute_order(order).unwrap();
+        }
+
+        the_orderbooks.set_cancel_on_disconnect(1, false);
+
+        assert!(the_orderbooks.disconnect_session(1).is_empty());
+
+        the_orderbooks.set_cancel_on_disconnect(1, true);
+        let events = the_orderbooks.disconnect_session(1);
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[&orders[2].id][0].r#type, OrderEventType::Cancelled);
+        assert_eq!(events[&orders[2].id][0].crossed_id, None);
+        assert_eq!(the_orderbooks.count(), (0, 1, 1));
+        assert!(the_orderbooks.contains(&orders[1].id));
+    }
 }
@@ -0,0 +1,236 @@
+use super::market_data::SequenceNumber;
+use super::orders::*;
+use std::mem;
+use uuid::Uuid;
+
+#[derive(Copy, Clone, Debug)]
+#[repr(u8)]
+pub enum OrderFeedMessageType {
+    Add = 0,
+    Execute = 1,
+    Cancel = 2,
+    Replace = 3,
+}
+
+#[derive(Clone, Debug)]
+#[repr(align(8))]
+pub struct OrderFeedMessage {
+    pub sequence: SequenceNumber,             // 8 bytes
+    pub timestamp: UTCNanoSeconds,            // 8 bytes
+    pub r#type: OrderFeedMessageType,         // 1 byte
+    pub order_id: OrderId,                    // 16 bytes
+    pub replaced_id: Option<OrderId>,         // 16 bytes
+    pub side: OrderSide,                      // 1 byte
+    pub price: OrderPrice,                    // 8 bytes
+    pub volume: OrderVolume,                  // 8 bytes
+    pub executed_volume: Option<OrderVolume>, // 16 bytes
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct OrderFeed {
+    pub sequence: SequenceNumber,
+    pub messages: Vec<OrderFeedMessage>,
+}
+
+impl OrderFeed {
+    pub fn publish(&mut self, mut message: OrderFeedMessage) {
+        self.sequence += 1;
+        message.sequence = self.sequence;
+        self.messages.push(message);
+    }
+
+    pub fn drain_messages(&mut self) -> Vec<OrderFeedMessage> {
+        mem::take(&mut self.messages)
+    }
+}
+
+impl Default for OrderFeedMessage {
+    fn default() -> OrderFeedMessage {
+        OrderFeedMessage {
+            sequence: 0,
+            timestamp: 0,
+            r#type: OrderFeedMessageType::Add,
+            order_id: Uuid::nil(),
+            replaced_id: None,
+            side: OrderSide::NoSide,
+            price: 0,
+            volume: 0,
+            executed_volume: None,
+        }
+    }
+}
+
+impl PartialEq for OrderFeedMessageType {
+    fn eq(&self, other: &Self) -> bool {
+        mem::discriminant(self) == mem::discriminant(other)
+    }
+}
+
+impl Eq for OrderFeedMessageType {}
+
+#[cfg(test)]
+mod unit_test {
+    use super::super::test_support::*;
+    use super::super::*;
+
+    fn apply_to_mirror(mirror: &mut Orderbooks, message: &OrderFeedMessage) {
+        let orderbook = match message.side {
+            OrderSide::Bid => &mut mirror.bids,
+            OrderSide::Ask => &mut mirror.asks,
+            OrderSide::NoSide => panic!("Order feed message without side: {}", message.order_id),
+        };
+
+        match message.r#type {
+            OrderFeedMessageType::Add => orderbook.insert(
+                message.price,
+                OrderbookOrder {
+                    id: message.order_id,
+                    remaining_volume: message.volume,
+                    account: None,
+                    session_id: None,
+                },
+            ),
+            OrderFeedMessageType::Execute => {
+                if message.volume == 0 {
+                    orderbook.remove(&message.order_id).unwrap();
+                } else {
+                    let price_level_orders = orderbook.orders.get_mut(&message.price).unwrap();
+                    let mirror_order = price_level_orders
+                        .iter_mut()
+                        .find(|order| order.id == message.order_id)
+                        .unwrap();
+                    mirror_order.remaining_volume = message.volume;
+                }
+            }
+            OrderFeedMessageType::Cancel => {
+                orderbook.remove(&message.order_id).unwrap();
+            }
+            OrderFeedMessageType::Replace => {
+                orderbook.remove(&message.replaced_id.unwrap()).unwrap();
+                orderbook.insert(
+                    message.price,
+                    OrderbookOrder {
+                        id: message.order_id,
+                        remaining_volume: message.volume,
+                        account: None,
+                        session_id: None,
+                    },
+                );
+            }
+        }
+    }
+
+    fn levels(orderbook: &Orderbook) -> Vec<(PriceLevel, Vec<(OrderId, OrderVolume)>)> {
+        orderbook
+            .orders
+            .iter()
+            .map(|(price_level, orders)| {
+                let orders = orders.iter().map(|order| (order.id, order.remaining_volume)).collect();
+                (*price_level, orders)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_order_feed_messages() {
+        let mut the_orderbooks = Orderbooks::default();
+        the_orderbooks.attach_order_feed();
+        let resting_order = limit_order(OrderSide::Ask, 10, 100);
+        let aggressor_order = limit_order(OrderSide::Bid, 4, 100);
+        the_orderbooks.execute_order(&resting_order);
+        the_orderbooks.execute_order(&aggressor_order);
+        let messages = the_orderbooks.drain_order_feed();
+
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].r#type, OrderFeedMessageType::Add);
+        assert_eq!(messages[0].order_id, resting_order.id);
+        assert_eq!(messages[1].r#type, OrderFeedMessageType::Execute);
+        assert_eq!(messages[1].order_id, resting_order.id);
+        assert_eq!(messages[1].side, OrderSide::Ask);
+        assert_eq!(messages[1].volume, 6);
+        assert_eq!(messages[1].executed_volume, Some(4));
+        assert_eq!(messages[1].sequence, 2);
+    }
+
+    #[test]
+    fn test_replace_order_keeps_side_and_emits_replace() {
+        let mut the_orderbooks = Orderbooks::default();
+        the_orderbooks.attach_order_feed();
+        let original_order = limit_order(OrderSide::Bid, 10, 100);
+        let replace_order = OrderMessage {
+            target_id: Some(original_order.id),
+            r#type: OrderType::Replace,
+            ..limit_order(OrderSide::Bid, 8, 99)
+        };
+        the_orderbooks.execute_order(&original_order);
+        let events = the_orderbooks.execute_order(&replace_order);
+        let messages = the_orderbooks.drain_order_feed();
+
+        assert_eq!(
+            events.get(&original_order.id).unwrap()[0].r#type,
+            OrderEventType::Replaced
+        );
+        assert_eq!(events.get(&replace_order.id).unwrap()[1].r#type, OrderEventType::Open);
+        assert_eq!(messages[1].r#type, OrderFeedMessageType::Replace);
+        assert_eq!(messages[1].replaced_id, Some(original_order.id));
+        assert_eq!(the_orderbooks.bids.volume(99), 8);
+        assert_eq!(the_orderbooks.count().2, 1);
+    }
+
+    #[test]
+    fn test_rebuild_mirror_book_from_order_feed() {
+        let mut the_orderbooks = Orderbooks::default();
+        let mut mirror_orderbooks = Orderbooks::default();
+        the_orderbooks.attach_order_feed();
+        let bid_order_0 = limit_order(OrderSide::Bid, 10, 95);
+        let bid_order_1 = limit_order(OrderSide::Bid, 5, 96);
+        let ask_order_0 = limit_order(OrderSide::Ask, 10, 105);
+        let ask_order_1 = limit_order(OrderSide::Ask, 7, 104);
+        let order_messages = vec![
+            bid_order_0.clone(),
+            bid_order_1.clone(),
+            ask_order_0.clone(),
+            ask_order_1.clone(),
+            limit_order(OrderSide::Bid, 3, 96),
+            limit_order(OrderSide::Ask, 12, 95),
+            OrderMessage {
+                target_id: Some(ask_order_1.id),
+                r#type: OrderType::Replace,
+                ..limit_order(OrderSide::Ask, 20, 103)
+            },
+            OrderMessage {
+                side: OrderSide::Bid,
+                r#type: OrderType::Market,
+                volume: Some(4),
+                max_quote: Some(1_000),
+                ..Default::default()
+            },
+            OrderMessage {
+                target_id: Some(ask_order_0.id),
+                r#type: OrderType::Replace,
+                ..limit_order(OrderSide::Ask, 2, 90)
+            },
+            OrderMessage {
+                target_id: Some(bid_order_0.id),
+                side: OrderSide::NoSide,
+                r#type: OrderType::Cancel,
+                ..Default::default()
+            },
+            limit_order(OrderSide::Bid, 6, 101),
+        ];
+
+        for order_message in order_messages.iter() {
+            the_orderbooks.execute_order(order_message);
+
+            for message in the_orderbooks.drain_order_feed() {
+                apply_to_mirror(&mut mirror_orderbooks, &message);
+            }
+
+            assert_eq!(levels(&mirror_orderbooks.bids), levels(&the_orderbooks.bids));
+            assert_eq!(levels(&mirror_orderbooks.asks), levels(&the_orderbooks.asks));
+        }
+
+        assert_eq!(the_orderbooks.count().2, 2);
+        assert_eq!(the_orderbooks.orders_location.len(), 2);
+    }
+}
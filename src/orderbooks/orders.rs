@@ -1,4 +1,5 @@
 use chrono::Utc;
+use serde::{Deserialize, Serialize};
 use std::mem;
 use uuid::Uuid;
 
@@ -7,8 +8,12 @@ pub type OrderVolume = u64;
 pub type OrderPrice = u64;
 pub type OrderQuote = u64;
 pub type UTCNanoSeconds = i64;
+pub type AccountId = u64;
+pub type SessionId = u64;
+pub type ClientOrderId = String;
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
 #[repr(u8)]
 pub enum OrderSide {
     NoSide = 0,
@@ -16,15 +21,18 @@ pub enum OrderSide {
     Ask = 2,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
 #[repr(u8)]
 pub enum OrderType {
     Cancel = 0,
     Market = 1,
     Limit = 2,
+    Replace = 3,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
 #[repr(u8)]
 pub enum OrderEventType {
     NoMatch = 0,
@@ -32,9 +40,25 @@ pub enum OrderEventType {
     Open = 2,
     Closed = 3,
     Cancelled = 4,
+    Replaced = 5,
+    Expired = 6,
+    MassCancelled = 7,
+    Rejected = 8,
 }
 
-#[derive(Clone, Debug)]
+// Good till date orders rest until `expires_at`, which is a full timestamp, so
+// good till time is the same thing. Day orders rest until the session closes.
+#[derive(Copy, Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[repr(u8)]
+pub enum TimeInForce {
+    #[default]
+    GoodTillCancel = 0,
+    GoodTillDate = 1,
+    Day = 2,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[repr(align(8))] // Packed Size is 64 bytes
 pub struct OrderEvent {
     pub timestamp: UTCNanoSeconds,             // 8 bytes
@@ -44,25 +68,34 @@ pub struct OrderEvent {
     pub crossed_id: Option<Uuid>,              // 16 bytes
 }
 
-#[derive(Clone, Debug)]
-#[repr(align(8))] // Packed Size is 96 bytes
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+#[repr(align(8))] // Packed Size is 200 bytes
 pub struct OrderMessage {
-    pub id: OrderId,                   // 16 bytes
-    pub target_id: Option<OrderId>,    // 16 bytes
-    pub created_at: UTCNanoSeconds,    // 8 bytes
-    pub side: OrderSide,               // 1 byte
-    pub r#type: OrderType,             // 1 byte
-    pub volume: Option<OrderVolume>,   // 16 bytes
-    pub price: Option<OrderPrice>,     // 16 bytes
-    pub max_quote: Option<OrderQuote>, // 16 bytes
-    pub events: Vec<OrderEvent>,       // 24 bytes
+    pub id: OrderId,                                   // 16 bytes
+    pub target_id: Option<OrderId>,                    // 16 bytes
+    pub created_at: UTCNanoSeconds,                    // 8 bytes
+    pub side: OrderSide,                               // 1 byte
+    pub r#type: OrderType,                             // 1 byte
+    pub volume: Option<OrderVolume>,                   // 16 bytes
+    pub price: Option<OrderPrice>,                     // 16 bytes
+    pub max_quote: Option<OrderQuote>,                 // 16 bytes
+    pub time_in_force: TimeInForce,                    // 1 byte
+    pub expires_at: Option<UTCNanoSeconds>,            // 16 bytes
+    pub account: Option<AccountId>,                    // 16 bytes
+    pub session_id: Option<SessionId>,                 // 16 bytes
+    pub client_order_id: Option<ClientOrderId>,        // 24 bytes
+    pub target_client_order_id: Option<ClientOrderId>, // 24 bytes
+    pub events: Vec<OrderEvent>,                       // 24 bytes
 }
 
 #[derive(Clone, Debug)]
-#[repr(align(8))] // Packed Size 24 bytes
+#[repr(align(8))] // Packed Size 56 bytes
 pub struct OrderbookOrder {
     pub id: OrderId,                   // 16 bytes
     pub remaining_volume: OrderVolume, // 8 bytes
+    pub account: Option<AccountId>,    // 16 bytes
+    pub session_id: Option<SessionId>, // 16 bytes
 }
 
 impl Default for OrderMessage {
@@ -70,12 +103,18 @@ impl Default for OrderMessage {
         OrderMessage {
             id: Uuid::new_v4(),
             target_id: None,
-            created_at: Utc::now().timestamp_nanos(),
+            created_at: Utc::now().timestamp_nanos_opt().unwrap(),
             side: OrderSide::NoSide,
             r#type: OrderType::Limit,
             volume: None,
             price: None,
             max_quote: None,
+            time_in_force: TimeInForce::GoodTillCancel,
+            expires_at: None,
+            account: None,
+            session_id: None,
+            client_order_id: None,
+            target_client_order_id: None,
             events: Vec::new(),
         }
     }
@@ -93,6 +132,12 @@ impl PartialEq for OrderType {
     }
 }
 
+impl PartialEq for TimeInForce {
+    fn eq(&self, other: &Self) -> bool {
+        mem::discriminant(self) == mem::discriminant(other)
+    }
+}
+
 impl PartialEq for OrderEventType {
     fn eq(&self, other: &Self) -> bool {
         mem::discriminant(self) == mem::discriminant(other)
@@ -101,4 +146,5 @@ impl PartialEq for OrderEventType {
 
 impl Eq for OrderSide {}
 impl Eq for OrderType {}
+impl Eq for TimeInForce {}
 impl Eq for OrderEventType {}
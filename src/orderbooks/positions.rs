@@ -0,0 +1,32 @@
+use super::orders::*;
+use super::trades::Trade;
+use std::collections::HashMap;
+
+pub type Position = i64;
+
+// Bought minus sold volume per account, orders without one share an entry.
+// Every trade of the book counts, auction uncrosses included.
+#[derive(Clone, Debug, Default)]
+pub struct Positions {
+    pub(crate) positions: HashMap<Option<AccountId>, Position>,
+}
+
+impl Positions {
+    pub fn get(&self, account: Option<AccountId>) -> Position {
+        self.positions.get(&account).copied().unwrap_or(0)
+    }
+
+    // Auction trades report the bid as the aggressor.
+    pub(crate) fn add_trades(&mut self, trades: &[Trade]) {
+        for trade in trades {
+            let (bid_account, ask_account) = match trade.aggressor_side {
+                OrderSide::Ask => (trade.resting_account, trade.aggressor_account),
+                _ => (trade.aggressor_account, trade.resting_account),
+            };
+            let volume = trade.volume as Position;
+
+            *self.positions.entry(bid_account).or_default() += volume;
+            *self.positions.entry(ask_account).or_default() -= volume;
+        }
+    }
+}
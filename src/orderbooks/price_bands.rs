@@ -0,0 +1,196 @@
+use super::orders::*;
+use super::{OrderbookOrder, PriceLevel};
+
+pub const BASIS_POINTS: u64 = 10_000;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum BandBreachAction {
+    // Reject an order that would trade outside the band before it trades.
+    #[default]
+    Reject,
+    // Trade up to the band, then collect orders in a volatility auction.
+    VolatilityAuction,
+}
+
+// Bands are in basis points of their reference: the static band around
+// `reference_price` and the dynamic band around the last trade. Either can be
+// left out. Limit prices outside the static band are rejected up front.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct PriceBands {
+    pub reference_price: Option<OrderPrice>,
+    pub static_band: Option<u64>,
+    pub dynamic_band: Option<u64>,
+    pub breach_action: BandBreachAction,
+    pub volatility_auction_duration: UTCNanoSeconds,
+}
+
+fn band(reference_price: OrderPrice, basis_points: u64) -> (OrderPrice, OrderPrice) {
+    let width = (reference_price as u128 * basis_points as u128 / BASIS_POINTS as u128) as OrderPrice;
+
+    (
+        reference_price.saturating_sub(width),
+        reference_price.saturating_add(width),
+    )
+}
+
+impl PriceBands {
+    pub fn static_range(&self) -> (OrderPrice, OrderPrice) {
+        match (self.reference_price, self.static_band) {
+            (Some(reference_price), Some(static_band)) => band(reference_price, static_band),
+            _ => (0, OrderPrice::MAX),
+        }
+    }
+
+    // Both bands at once, the dynamic one only after the first trade.
+    pub fn range(&self, last_price: Option<OrderPrice>) -> (OrderPrice, OrderPrice) {
+        let (static_low, static_high) = self.static_range();
+
+        match (last_price, self.dynamic_band) {
+            (Some(last_price), Some(dynamic_band)) => {
+                let (dynamic_low, dynamic_high) = band(last_price, dynamic_band);
+                (static_low.max(dynamic_low), static_high.min(dynamic_high))
+            }
+            _ => (static_low, static_high),
+        }
+    }
+}
+
+// Walks the levels an order would cross, best first, and tells whether it
+// reaches one outside `low..=high` before it is filled.
+pub(crate) fn sweeps_outside<'a, I>(
+    levels: I,
+    order_message: &OrderMessage,
+    (low, high): (OrderPrice, OrderPrice),
+) -> bool
+where
+    I: Iterator<Item = (&'a PriceLevel, &'a Vec<OrderbookOrder>)>,
+{
+    let mut remaining_volume = order_message.volume.unwrap_or(0);
+    let (limit_price, mut remaining_quote) = match (order_message.r#type, order_message.side) {
+        (OrderType::Market, OrderSide::Bid) => (None, order_message.max_quote),
+        (OrderType::Market, _) => (None, None),
+        _ => (order_message.price, None),
+    };
+
+    for (price_level, price_level_orders) in levels {
+        let price_level = *price_level;
+        let crosses = match (limit_price, order_message.side) {
+            (Some(order_price), OrderSide::Bid) => price_level <= order_price,
+            (Some(order_price), _) => price_level >= order_price,
+            (None, _) => true,
+        };
+
+        if !crosses || remaining_volume == 0 {
+            return false;
+        }
+
+        if price_level < low || price_level > high {
+            return true;
+        }
+
+        let level_volume: OrderVolume = price_level_orders.iter().map(|order| order.remaining_volume).sum();
+        let mut traded_volume = remaining_volume.min(level_volume);
+
+        if let Some(quote) = remaining_quote.as_mut() {
+            traded_volume = traded_volume.min(*quote / price_level);
+
+            if traded_volume == 0 {
+                return false;
+            }
+
+            *quote -= traded_volume * price_level;
+        }
+
+        remaining_volume -= traded_volume;
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod unit_test {
+    use super::super::test_support::*;
+    use super::super::*;
+
+    // Asks at 100, 104 and 106 after a trade at 100.
+    fn banded_orderbooks(breach_action: BandBreachAction) -> Orderbooks {
+        let mut the_orderbooks = Orderbooks::default();
+        the_orderbooks.set_clock(EngineClock::Manual(1_000));
+        the_orderbooks.set_price_bands(PriceBands {
+            reference_price: Some(100),
+            static_band: Some(1_000),
+            dynamic_band: Some(500),
+            breach_action,
+            volatility_auction_duration: 500,
+        });
+
+        for (volume, price) in [(10, 100), (10, 104), (10, 106)].iter() {
+            let ask_order = limit_order(OrderSide::Ask, *volume, *price);
+            the_orderbooks.try_execute_order(&ask_order).unwrap();
+        }
+
+        let bid_order = limit_order(OrderSide::Bid, 1, 100);
+        the_orderbooks.try_execute_order(&bid_order).unwrap();
+        the_orderbooks
+    }
+
+    #[test]
+    fn test_price_bands_reject_the_aggressor() {
+        let the_orderbooks = banded_orderbooks(BandBreachAction::Reject);
+
+        assert_eq!(
+            the_orderbooks.validate(&limit_order(OrderSide::Bid, 1, 111)),
+            Err(OrderRejection::OutsidePriceBand)
+        );
+        assert!(the_orderbooks.validate(&limit_order(OrderSide::Bid, 1, 110)).is_ok());
+        // 9 at 100 and 6 at 104 stay inside 95..=105, 25 would reach 106.
+        assert!(the_orderbooks.validate(&market_order(OrderSide::Bid, 15)).is_ok());
+        assert_eq!(
+            the_orderbooks.validate(&market_order(OrderSide::Bid, 25)),
+            Err(OrderRejection::PriceBandBreach)
+        );
+        assert!(the_orderbooks.validate(&limit_order(OrderSide::Bid, 25, 105)).is_ok());
+    }
+
+    #[test]
+    fn test_price_band_breach_without_validate_rejects_the_order() {
+        let mut the_orderbooks = banded_orderbooks(BandBreachAction::Reject);
+        let market_bid = market_order(OrderSide::Bid, 25);
+        let events = the_orderbooks.execute_order(&market_bid);
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[&market_bid.id][0].r#type, OrderEventType::Rejected);
+        assert_eq!(events[&market_bid.id][0].remaining_volume, Some(25));
+        assert_eq!(the_orderbooks.count(), (0, 3, 3));
+        assert!(the_orderbooks.trades().is_empty());
+    }
+
+    #[test]
+    fn test_price_band_breach_starts_a_volatility_auction() {
+        let mut the_orderbooks = banded_orderbooks(BandBreachAction::VolatilityAuction);
+        let market_bid = market_order(OrderSide::Bid, 25);
+        let events = the_orderbooks.try_execute_order(&market_bid).unwrap();
+
+        assert_eq!(events[&market_bid.id].last().unwrap().remaining_volume, Some(6));
+        assert_eq!(the_orderbooks.session(), SessionState::VolatilityAuction);
+        // The auction starts from the order's last trade, not the one before it.
+        assert_eq!(the_orderbooks.auction.as_ref().unwrap().reference_price, Some(104));
+        assert_eq!(the_orderbooks.count(), (0, 1, 1));
+        assert_eq!(
+            the_orderbooks.validate(&market_order(OrderSide::Bid, 1)),
+            Err(OrderRejection::Session(SessionState::VolatilityAuction))
+        );
+
+        let bid_order = limit_order(OrderSide::Bid, 4, 106);
+        the_orderbooks.try_execute_order(&bid_order).unwrap();
+        the_orderbooks.set_clock(EngineClock::Manual(1_499));
+
+        assert!(the_orderbooks.advance_session().unwrap().is_empty());
+
+        the_orderbooks.set_clock(EngineClock::Manual(1_500));
+        let events = the_orderbooks.advance_session().unwrap();
+
+        assert_eq!(events[&bid_order.id].first().unwrap().traded_price, Some(106));
+        assert_eq!(the_orderbooks.session(), SessionState::Continuous);
+    }
+}
@@ -0,0 +1,50 @@
+use super::session::SessionState;
+use std::fmt;
+
+// `InAuction` is a call phase opened with `start_auction`, `Session` a
+// session state that does not accept the order, halts included.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum OrderRejection {
+    UnknownOrder = 1,
+    DuplicateOrderId = 2,
+    MissingSide = 3,
+    InvalidVolume = 4,
+    InvalidPrice = 5,
+    MissingMaxQuote = 6,
+    SideMismatch = 7,
+    InAuction = 8,
+    Session(SessionState) = 9,
+    OutsidePriceBand = 10,
+    PriceBandBreach = 11,
+    InvalidExpiry = 12,
+    DuplicateClientOrderId = 13,
+    InsufficientFunds = 14,
+}
+
+impl fmt::Display for OrderRejection {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let reason = match self {
+            OrderRejection::UnknownOrder => "Unknown order",
+            OrderRejection::DuplicateOrderId => "Duplicate order id",
+            OrderRejection::MissingSide => "Order side is required",
+            OrderRejection::InvalidVolume => "Order volume must be positive",
+            OrderRejection::InvalidPrice => "Order price must be positive",
+            OrderRejection::MissingMaxQuote => "Market bid requires max_quote",
+            OrderRejection::SideMismatch => "Order side cannot be replaced",
+            OrderRejection::InAuction => "Market orders are not accepted during an auction",
+            OrderRejection::Session(SessionState::Halted) => "Instrument is halted",
+            OrderRejection::Session(SessionState::Closed) => "Instrument is closed",
+            OrderRejection::Session(state) => {
+                return write!(formatter, "Order type is not accepted during the {}", state);
+            }
+            OrderRejection::OutsidePriceBand => "Order price is outside the price band",
+            OrderRejection::PriceBandBreach => "Order would trade outside the price band",
+            OrderRejection::InvalidExpiry => "Good till date order requires a future expires_at",
+            OrderRejection::DuplicateClientOrderId => "Duplicate client order id",
+            OrderRejection::InsufficientFunds => "Insufficient funds",
+        };
+
+        write!(formatter, "{}", reason)
+    }
+}
@@ -0,0 +1,294 @@
+use super::orders::*;
+use super::rejections::OrderRejection;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::mem;
+
+pub const NANOSECONDS_PER_DAY: UTCNanoSeconds = 86_400_000_000_000;
+
+#[derive(Copy, Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[repr(u8)]
+pub enum SessionState {
+    PreOpen = 0,
+    OpeningAuction = 1,
+    #[default]
+    Continuous = 2,
+    ClosingAuction = 3,
+    Halted = 4,
+    Closed = 5,
+    VolatilityAuction = 6,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SessionTransitionError {
+    pub from: SessionState,
+    pub to: SessionState,
+}
+
+// Daily transitions as nanoseconds since midnight UTC. Before the first one of
+// the day the last one of the previous day still applies.
+#[derive(Clone, Debug, Default)]
+pub struct SessionSchedule {
+    pub transitions: Vec<(UTCNanoSeconds, SessionState)>,
+}
+
+impl SessionState {
+    // Pre-open and the auctions collect orders without matching them, so only
+    // market orders are turned away. A halt only lets cancels through. The
+    // rejection names the state.
+    pub fn accept(&self, order_type: OrderType) -> Result<(), OrderRejection> {
+        match (self, order_type) {
+            (SessionState::Continuous, _) => Ok(()),
+            (SessionState::PreOpen, OrderType::Market)
+            | (SessionState::OpeningAuction, OrderType::Market)
+            | (SessionState::ClosingAuction, OrderType::Market)
+            | (SessionState::VolatilityAuction, OrderType::Market) => Err(OrderRejection::Session(*self)),
+            (SessionState::PreOpen, _)
+            | (SessionState::OpeningAuction, _)
+            | (SessionState::ClosingAuction, _)
+            | (SessionState::VolatilityAuction, _) => Ok(()),
+            (SessionState::Halted, OrderType::Cancel) => Ok(()),
+            (SessionState::Halted, _) | (SessionState::Closed, _) => Err(OrderRejection::Session(*self)),
+        }
+    }
+
+    // The next state of the trading day, used to catch up with a schedule
+    // the clock jumped through.
+    pub fn next_in_day(&self) -> Option<SessionState> {
+        match self {
+            SessionState::PreOpen => Some(SessionState::OpeningAuction),
+            SessionState::OpeningAuction | SessionState::VolatilityAuction => Some(SessionState::Continuous),
+            SessionState::Continuous => Some(SessionState::ClosingAuction),
+            SessionState::ClosingAuction => Some(SessionState::Closed),
+            SessionState::Closed => Some(SessionState::PreOpen),
+            SessionState::Halted => None,
+        }
+    }
+
+    // The day runs pre-open, opening auction, continuous, closing auction (which
+    // may be skipped) and closed. A volatility auction interrupts continuous
+    // trading. A halt returns to the state it interrupted or closes the instrument.
+    pub fn can_move_to(&self, to: SessionState, resume_to: SessionState) -> bool {
+        match (self, to) {
+            (SessionState::Closed, SessionState::Halted) | (SessionState::Halted, SessionState::Halted) => false,
+            (_, SessionState::Halted) => true,
+            (SessionState::Halted, _) => to == resume_to || to == SessionState::Closed,
+            (SessionState::PreOpen, SessionState::OpeningAuction)
+            | (SessionState::OpeningAuction, SessionState::Continuous)
+            | (SessionState::Continuous, SessionState::ClosingAuction)
+            | (SessionState::Continuous, SessionState::Closed)
+            | (SessionState::Continuous, SessionState::VolatilityAuction)
+            | (SessionState::VolatilityAuction, SessionState::Continuous)
+            | (SessionState::VolatilityAuction, SessionState::Closed)
+            | (SessionState::ClosingAuction, SessionState::Closed)
+            | (SessionState::Closed, SessionState::PreOpen) => true,
+            _ => false,
+        }
+    }
+}
+
+impl SessionSchedule {
+    pub fn new(mut transitions: Vec<(UTCNanoSeconds, SessionState)>) -> SessionSchedule {
+        transitions.sort_by_key(|(time_of_day, _)| *time_of_day);
+
+        SessionSchedule { transitions }
+    }
+
+    pub fn state_at(&self, timestamp: UTCNanoSeconds) -> Option<SessionState> {
+        let time_of_day = timestamp.rem_euclid(NANOSECONDS_PER_DAY);
+
+        self.transitions
+            .iter()
+            .rev()
+            .find(|(transition_time, _)| *transition_time <= time_of_day)
+            .or_else(|| self.transitions.last())
+            .map(|(_, state)| *state)
+    }
+}
+
+impl fmt::Display for SessionState {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            SessionState::PreOpen => "pre-open",
+            SessionState::OpeningAuction => "opening auction",
+            SessionState::Continuous => "continuous",
+            SessionState::ClosingAuction => "closing auction",
+            SessionState::Halted => "halted",
+            SessionState::Closed => "closed",
+            SessionState::VolatilityAuction => "volatility auction",
+        };
+
+        write!(formatter, "{}", name)
+    }
+}
+
+impl fmt::Display for SessionTransitionError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "Cannot move from {} to {}", self.from, self.to)
+    }
+}
+
+impl PartialEq for SessionState {
+    fn eq(&self, other: &Self) -> bool {
+        mem::discriminant(self) == mem::discriminant(other)
+    }
+}
+
+impl Eq for SessionState {}
+
+#[cfg(test)]
+mod unit_test {
+    use super::super::test_support::*;
+    use super::super::*;
+
+    const HOUR: UTCNanoSeconds = 3_600_000_000_000;
+
+    #[test]
+    fn test_scheduled_trading_day() {
+        let day = 20_000 * NANOSECONDS_PER_DAY;
+        let schedule = SessionSchedule::new(vec![
+            (16 * HOUR, SessionState::Closed),
+            (8 * HOUR, SessionState::PreOpen),
+            (9 * HOUR, SessionState::OpeningAuction),
+            (10 * HOUR, SessionState::Continuous),
+        ]);
+        let mut the_orderbooks = Orderbooks::default();
+        let market_ask = OrderMessage {
+            side: OrderSide::Ask,
+            r#type: OrderType::Market,
+            volume: Some(1),
+            ..Default::default()
+        };
+
+        assert_eq!(schedule.state_at(day + 7 * HOUR), Some(SessionState::Closed));
+        assert_eq!(schedule.state_at(day + 9 * HOUR), Some(SessionState::OpeningAuction));
+
+        the_orderbooks.set_session_schedule(schedule);
+        the_orderbooks.set_clock(EngineClock::Manual(day + 7 * HOUR));
+        the_orderbooks.advance_session().unwrap();
+
+        assert_eq!(
+            the_orderbooks.validate(&market_ask),
+            Err(OrderRejection::Session(SessionState::Closed))
+        );
+
+        the_orderbooks.set_clock(EngineClock::Manual(day + 8 * HOUR));
+        the_orderbooks.advance_session().unwrap();
+
+        assert_eq!(the_orderbooks.session(), SessionState::PreOpen);
+        assert_eq!(
+            the_orderbooks.validate(&market_ask),
+            Err(OrderRejection::Session(SessionState::PreOpen))
+        );
+
+        let bid_order = limit_order(OrderSide::Bid, 10, 101);
+        let ask_order = limit_order(OrderSide::Ask, 6, 100);
+        the_orderbooks.try_execute_order(&bid_order).unwrap();
+        the_orderbooks.try_execute_order(&ask_order).unwrap();
+
+        assert_eq!(the_orderbooks.count(), (1, 1, 2));
+        assert!(the_orderbooks.drain_auction_indications().is_empty());
+
+        the_orderbooks.set_clock(EngineClock::Manual(day + 9 * HOUR));
+        the_orderbooks.advance_session().unwrap();
+
+        assert_eq!(
+            the_orderbooks.validate(&market_ask),
+            Err(OrderRejection::Session(SessionState::OpeningAuction))
+        );
+        assert_eq!(the_orderbooks.drain_auction_indications()[0].volume, 6);
+
+        the_orderbooks.set_clock(EngineClock::Manual(day + 10 * HOUR));
+        let events = the_orderbooks.advance_session().unwrap();
+
+        assert_eq!(events[&ask_order.id].last().unwrap().r#type, OrderEventType::Closed);
+        assert_eq!(the_orderbooks.count(), (1, 0, 1));
+        assert!(the_orderbooks.validate(&market_ask).is_ok());
+
+        the_orderbooks.halt().unwrap();
+
+        assert_eq!(
+            the_orderbooks.validate(&market_ask),
+            Err(OrderRejection::Session(SessionState::Halted))
+        );
+        // Halts override the schedule until resumed.
+        assert!(the_orderbooks.advance_session().unwrap().is_empty());
+        assert_eq!(
+            the_orderbooks
+                .transition(SessionState::PreOpen)
+                .unwrap_err()
+                .to_string(),
+            "Cannot move from halted to pre-open"
+        );
+
+        the_orderbooks.resume().unwrap();
+        the_orderbooks.set_clock(EngineClock::Manual(day + 17 * HOUR));
+        the_orderbooks.advance_session().unwrap();
+
+        assert_eq!(the_orderbooks.session(), SessionState::Closed);
+        assert!(the_orderbooks.contains(&bid_order.id));
+    }
+
+    #[test]
+    fn test_advance_session_steps_through_skipped_states() {
+        let day = 20_000 * NANOSECONDS_PER_DAY;
+        let mut the_orderbooks = Orderbooks::default();
+        the_orderbooks.set_session_schedule(SessionSchedule::new(vec![
+            (8 * HOUR, SessionState::PreOpen),
+            (9 * HOUR, SessionState::OpeningAuction),
+            (10 * HOUR, SessionState::Continuous),
+            (16 * HOUR, SessionState::Closed),
+        ]));
+        the_orderbooks.transition(SessionState::Closed).unwrap();
+        the_orderbooks.transition(SessionState::PreOpen).unwrap();
+        let bid_order = limit_order(OrderSide::Bid, 10, 101);
+        let ask_order = limit_order(OrderSide::Ask, 6, 100);
+        the_orderbooks.try_execute_order(&bid_order).unwrap();
+        the_orderbooks.try_execute_order(&ask_order).unwrap();
+
+        // The opening auction was skipped, it still uncrosses.
+        the_orderbooks.set_clock(EngineClock::Manual(day + 11 * HOUR));
+        let events = the_orderbooks.advance_session().unwrap();
+
+        assert_eq!(the_orderbooks.session(), SessionState::Continuous);
+        assert_eq!(events[&ask_order.id].last().unwrap().r#type, OrderEventType::Closed);
+        assert_eq!(the_orderbooks.count(), (1, 0, 1));
+
+        // Overnight, continuous trading goes through the close to pre-open.
+        the_orderbooks.set_clock(EngineClock::Manual(day + NANOSECONDS_PER_DAY + 8 * HOUR));
+        the_orderbooks.advance_session().unwrap();
+
+        assert_eq!(the_orderbooks.session(), SessionState::PreOpen);
+    }
+
+    #[test]
+    fn test_resume_returns_the_uncross_of_an_open_auction() {
+        let mut the_orderbooks = Orderbooks::default();
+        let bid_order = limit_order(OrderSide::Bid, 10, 101);
+        let ask_order = limit_order(OrderSide::Ask, 6, 100);
+        the_orderbooks.start_auction(None);
+        the_orderbooks.try_execute_order(&bid_order).unwrap();
+        the_orderbooks.try_execute_order(&ask_order).unwrap();
+
+        assert!(the_orderbooks.halt().unwrap().is_empty());
+        assert!(the_orderbooks.halt().is_err());
+
+        let events = the_orderbooks.resume().unwrap();
+
+        assert_eq!(the_orderbooks.session(), SessionState::Continuous);
+        assert_eq!(events[&ask_order.id].last().unwrap().r#type, OrderEventType::Closed);
+        assert_eq!(the_orderbooks.trades().len(), 1);
+        assert_eq!(
+            the_orderbooks.resume().unwrap_err().to_string(),
+            "Cannot move from continuous to continuous"
+        );
+
+        the_orderbooks.transition(SessionState::Closed).unwrap();
+
+        assert_eq!(
+            the_orderbooks.halt().unwrap_err().to_string(),
+            "Cannot move from closed to halted"
+        );
+    }
+}
@@ -0,0 +1,142 @@
+use super::candles::{Candle, CandleInterval, QuoteVolume, TradeCount};
+use super::orders::*;
+use super::trades::Trade;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+pub const STATISTICS_WINDOW: UTCNanoSeconds = 86_400_000_000_000;
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Ticker {
+    pub timestamp: UTCNanoSeconds,
+    pub last_price: Option<OrderPrice>,
+    pub open_price: Option<OrderPrice>,
+    pub high_price: Option<OrderPrice>,
+    pub low_price: Option<OrderPrice>,
+    pub price_change: Option<i64>,
+    pub volume: OrderVolume,
+    pub quote_volume: QuoteVolume,
+    pub trade_count: TradeCount,
+}
+
+// Rolling window statistics kept as one-minute buckets, so the window edge
+// moves with minute granularity.
+#[derive(Clone, Debug)]
+pub(crate) struct MarketStatistics {
+    pub window: UTCNanoSeconds,
+    pub last_price: Option<OrderPrice>,
+    pub buckets: VecDeque<Candle>,
+}
+
+impl MarketStatistics {
+    pub fn add_trades(&mut self, trades: &[Trade]) {
+        for trade in trades {
+            let open_time = CandleInterval::OneMinute.open_time(trade.timestamp);
+
+            match self.buckets.back_mut() {
+                Some(bucket) if bucket.open_time >= open_time => bucket.add_trade(trade),
+                _ => {
+                    let mut bucket = Candle::new(CandleInterval::OneMinute, open_time, trade.price);
+                    bucket.add_trade(trade);
+                    self.buckets.push_back(bucket);
+                }
+            }
+
+            self.last_price = Some(trade.price);
+        }
+
+        if let Some(last_trade) = trades.last() {
+            self.expire(last_trade.timestamp);
+        }
+    }
+
+    pub fn expire(&mut self, now: UTCNanoSeconds) {
+        while let Some(bucket) = self.buckets.front() {
+            if bucket.close_time > now - self.window {
+                break;
+            }
+
+            self.buckets.pop_front();
+        }
+    }
+
+    pub fn ticker(&self, now: UTCNanoSeconds) -> Ticker {
+        let mut ticker = Ticker {
+            timestamp: now,
+            last_price: self.last_price,
+            ..Default::default()
+        };
+
+        for bucket in self.buckets.iter() {
+            if bucket.close_time <= now - self.window {
+                continue;
+            }
+
+            ticker.open_price = ticker.open_price.or(Some(bucket.open));
+            ticker.high_price = Some(ticker.high_price.map_or(bucket.high, |high| high.max(bucket.high)));
+            ticker.low_price = Some(ticker.low_price.map_or(bucket.low, |low| low.min(bucket.low)));
+            ticker.volume += bucket.volume;
+            ticker.quote_volume += bucket.quote_volume;
+            ticker.trade_count += bucket.trade_count;
+        }
+
+        if let (Some(last_price), Some(open_price)) = (ticker.last_price, ticker.open_price) {
+            ticker.price_change = Some(last_price as i64 - open_price as i64);
+        }
+
+        ticker
+    }
+}
+
+impl Default for MarketStatistics {
+    fn default() -> MarketStatistics {
+        MarketStatistics {
+            window: STATISTICS_WINDOW,
+            last_price: None,
+            buckets: VecDeque::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod unit_test {
+    use super::super::test_support::*;
+    use super::super::*;
+
+    const ONE_HOUR: UTCNanoSeconds = 3_600_000_000_000;
+
+    #[test]
+    fn test_ticker_rolling_window() {
+        let mut the_orderbooks = Orderbooks::default();
+        let timed_orders = [
+            (0, limit_order(OrderSide::Ask, 100, 100)),
+            (ONE_HOUR, limit_order(OrderSide::Bid, 10, 100)),
+            (2 * ONE_HOUR, limit_order(OrderSide::Ask, 50, 90)),
+            (3 * ONE_HOUR, limit_order(OrderSide::Bid, 5, 120)),
+            (26 * ONE_HOUR, limit_order(OrderSide::Bid, 20, 120)),
+        ];
+        let mut tickers = Vec::new();
+
+        for (timestamp, order_message) in timed_orders.iter() {
+            the_orderbooks.clock.set(*timestamp);
+            the_orderbooks.execute_order(order_message);
+            tickers.push(the_orderbooks.ticker());
+        }
+
+        assert_eq!(tickers[0].last_price, None);
+        assert_eq!(tickers[0].trade_count, 0);
+        assert_eq!(tickers[3].last_price, Some(90));
+        assert_eq!(tickers[3].open_price, Some(100));
+        assert_eq!(tickers[3].high_price, Some(100));
+        assert_eq!(tickers[3].low_price, Some(90));
+        assert_eq!(tickers[3].price_change, Some(-10));
+        assert_eq!(tickers[3].volume, 15);
+        assert_eq!(tickers[3].quote_volume, 1_000 + 450);
+        assert_eq!(tickers[3].trade_count, 2);
+        assert_eq!(tickers[4].open_price, Some(90));
+        assert_eq!(tickers[4].volume, 25);
+        assert_eq!(tickers[4].trade_count, 2);
+        assert_eq!(tickers[4].price_change, Some(0));
+        assert_eq!(the_orderbooks.stats.buckets.len(), 2);
+    }
+}
@@ -0,0 +1,22 @@
+use super::orders::*;
+
+pub(crate) fn limit_order(side: OrderSide, volume: OrderVolume, price: OrderPrice) -> OrderMessage {
+    OrderMessage {
+        side,
+        r#type: OrderType::Limit,
+        volume: Some(volume),
+        price: Some(price),
+        ..Default::default()
+    }
+}
+
+// Market bids may spend any quote.
+pub(crate) fn market_order(side: OrderSide, volume: OrderVolume) -> OrderMessage {
+    OrderMessage {
+        side,
+        r#type: OrderType::Market,
+        volume: Some(volume),
+        max_quote: Some(OrderQuote::MAX).filter(|_| side == OrderSide::Bid),
+        ..Default::default()
+    }
+}
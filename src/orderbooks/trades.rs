@@ -0,0 +1,73 @@
+use super::orders::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[repr(align(8))]
+pub struct Trade {
+    pub timestamp: UTCNanoSeconds,             // 8 bytes
+    pub price: OrderPrice,                     // 8 bytes
+    pub volume: OrderVolume,                   // 8 bytes
+    pub aggressor_id: OrderId,                 // 16 bytes
+    pub aggressor_side: OrderSide,             // 1 byte
+    pub resting_id: OrderId,                   // 16 bytes
+    pub resting_remaining_volume: OrderVolume, // 8 bytes
+    #[serde(default)]
+    pub aggressor_account: Option<AccountId>, // 16 bytes
+    #[serde(default)]
+    pub resting_account: Option<AccountId>, // 16 bytes
+}
+
+impl Trade {
+    pub fn from_events(
+        order_message: &OrderMessage,
+        current_order_events: &[OrderEvent],
+        order_events: &HashMap<Uuid, Vec<OrderEvent>>,
+        resting_accounts: &HashMap<Uuid, Option<AccountId>>,
+    ) -> Vec<Trade> {
+        let mut trades = Vec::new();
+        let mut order_remaining_volume = order_message.volume.unwrap_or(0);
+
+        for order_event in current_order_events {
+            if order_event.r#type != OrderEventType::HasMatch {
+                continue;
+            }
+
+            let resting_id = order_event.crossed_id.unwrap();
+            let remaining_volume = order_event.remaining_volume.unwrap();
+            let resting_remaining_volume = order_events
+                .get(&resting_id)
+                .and_then(|resting_order_events| resting_order_events.first())
+                .and_then(|resting_order_event| resting_order_event.remaining_volume)
+                .unwrap();
+
+            trades.push(Trade {
+                timestamp: order_event.timestamp,
+                price: order_event.traded_price.unwrap(),
+                volume: order_remaining_volume - remaining_volume,
+                aggressor_id: order_message.id,
+                aggressor_side: order_message.side,
+                resting_id,
+                resting_remaining_volume,
+                aggressor_account: order_message.account,
+                resting_account: resting_accounts.get(&resting_id).copied().flatten(),
+            });
+            order_remaining_volume = remaining_volume;
+        }
+
+        trades
+    }
+
+    pub fn resting_side(&self) -> OrderSide {
+        match self.aggressor_side {
+            OrderSide::Bid => OrderSide::Ask,
+            OrderSide::Ask => OrderSide::Bid,
+            OrderSide::NoSide => OrderSide::NoSide,
+        }
+    }
+
+    pub fn quote(&self) -> OrderQuote {
+        self.price.saturating_mul(self.volume)
+    }
+}
@@ -0,0 +1,373 @@
+use super::message::*;
+use crate::orderbooks::*;
+use crate::risk::*;
+use std::collections::{HashMap, HashSet};
+use std::sync::mpsc::Sender;
+use uuid::Uuid;
+
+pub type ClientId = u64;
+
+#[derive(Clone, Debug)]
+pub struct OuchOrder {
+    pub client_id: ClientId,
+    pub token: OrderToken,
+    pub symbol: Symbol,
+    pub side: OrderSide,
+    pub remaining_volume: OrderVolume,
+}
+
+// Every order goes through the instrument's risk engine before the book.
+#[derive(Debug, Default)]
+pub struct OuchGateway {
+    pub instruments: HashMap<Symbol, Orderbooks>,
+    pub risk_engines: HashMap<Symbol, RiskEngine>,
+    pub orders: HashMap<OrderId, OuchOrder>,
+    tokens: HashMap<(ClientId, OrderToken), OrderId>,
+    used_tokens: HashSet<(ClientId, OrderToken)>,
+    clients: HashMap<ClientId, Sender<Vec<u8>>>,
+    last_client_id: ClientId,
+    last_match_number: MatchNumber,
+}
+
+impl OuchGateway {
+    pub fn new(symbols: &[String]) -> OuchGateway {
+        OuchGateway {
+            instruments: symbols
+                .iter()
+                .map(|name| (symbol(name), Orderbooks::default()))
+                .collect(),
+            risk_engines: symbols
+                .iter()
+                .map(|name| (symbol(name), RiskEngine::default()))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    // Encoded responses are queued on the outbox, the connection's writer
+    // thread sends them outside the gateway lock.
+    pub fn connect(&mut self, outbox: Sender<Vec<u8>>) -> ClientId {
+        self.last_client_id += 1;
+        self.clients.insert(self.last_client_id, outbox);
+        self.last_client_id
+    }
+
+    // A disconnected client's resting orders are cancelled unless it opted out,
+    // its tokens can be reused on the next connection.
+    pub fn disconnect(&mut self, client_id: ClientId) {
+        self.clients.remove(&client_id);
+        self.used_tokens
+            .retain(|(token_client_id, _)| *token_client_id != client_id);
+
+        let mut cancelled_ids = Vec::new();
+
+        for the_orderbooks in self.instruments.values_mut() {
+            cancelled_ids.extend(the_orderbooks.disconnect_session(client_id).into_keys());
+        }
+
+        for order_id in cancelled_ids.iter() {
+            self.forget(order_id);
+        }
+    }
+
+    // OUCH has no message for it, so the opt-out is set by whoever accepts the
+    // connection.
+    pub fn set_cancel_on_disconnect(&mut self, client_id: ClientId, enabled: bool) {
+        for the_orderbooks in self.instruments.values_mut() {
+            the_orderbooks.set_cancel_on_disconnect(client_id, enabled);
+        }
+    }
+
+    pub fn order_id(&self, client_id: ClientId, token: OrderToken) -> Option<OrderId> {
+        self.tokens.get(&(client_id, token)).copied()
+    }
+
+    pub fn handle(&mut self, client_id: ClientId, payload: &[u8]) {
+        let mut responses = Vec::new();
+
+        match OuchRequest::decode(payload) {
+            Some(OuchRequest::EnterOrder {
+                token,
+                symbol,
+                side,
+                r#type,
+                volume,
+                price,
+                max_quote,
+            }) => {
+                let order_message = OrderMessage {
+                    side,
+                    r#type,
+                    volume: Some(volume).filter(|volume| *volume > 0),
+                    price: Some(price).filter(|price| *price > 0 && r#type == OrderType::Limit),
+                    max_quote: Some(max_quote).filter(|max_quote| *max_quote > 0),
+                    session_id: Some(client_id),
+                    ..Default::default()
+                };
+                self.enter(client_id, token, symbol, order_message, &mut responses);
+            }
+            Some(OuchRequest::ReplaceOrder {
+                existing_token,
+                replacement_token,
+                volume,
+                price,
+            }) => self.replace(
+                client_id,
+                existing_token,
+                replacement_token,
+                volume,
+                price,
+                &mut responses,
+            ),
+            Some(OuchRequest::CancelOrder { token }) => self.cancel(client_id, token, &mut responses),
+            None => responses.push((client_id, rejected(0, REJECT_REASON_INVALID_MESSAGE))),
+        }
+
+        self.flush(responses);
+    }
+
+    fn flush(&mut self, responses: Vec<(ClientId, OuchResponse)>) {
+        let mut buffers: HashMap<ClientId, Vec<u8>> = HashMap::new();
+
+        for (client_id, response) in responses {
+            response.encode(buffers.entry(client_id).or_default());
+        }
+
+        // Responses for a client whose writer has stopped are dropped, its
+        // connection thread cleans up.
+        for (client_id, buffer) in buffers {
+            if let Some(outbox) = self.clients.get(&client_id) {
+                let _ = outbox.send(buffer);
+            }
+        }
+    }
+
+    fn check_token(&self, client_id: ClientId, token: OrderToken) -> Result<(), u8> {
+        if self.used_tokens.contains(&(client_id, token)) {
+            Err(REJECT_REASON_DUPLICATE_TOKEN)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn try_execute_order(
+        &mut self,
+        symbol: &Symbol,
+        order_message: &OrderMessage,
+    ) -> Result<HashMap<Uuid, Vec<OrderEvent>>, u8> {
+        let the_orderbooks = self.instruments.get_mut(symbol).ok_or(REJECT_REASON_UNKNOWN_SYMBOL)?;
+        let risk = self.risk_engines.get_mut(symbol).unwrap();
+
+        risk.try_execute_order(the_orderbooks, order_message)
+            .map_err(pre_trade_reject_reason)
+    }
+
+    fn enter(
+        &mut self,
+        client_id: ClientId,
+        token: OrderToken,
+        symbol: Symbol,
+        order_message: OrderMessage,
+        responses: &mut Vec<(ClientId, OuchResponse)>,
+    ) {
+        let result = self
+            .check_token(client_id, token)
+            .and_then(|_| self.try_execute_order(&symbol, &order_message));
+
+        let order_events = match result {
+            Ok(order_events) => order_events,
+            Err(reason) => return responses.push((client_id, rejected(token, reason))),
+        };
+
+        let timestamp = order_events[&order_message.id][0].timestamp;
+        let volume = order_message.volume.unwrap();
+        self.used_tokens.insert((client_id, token));
+        self.tokens.insert((client_id, token), order_message.id);
+        self.orders.insert(
+            order_message.id,
+            OuchOrder {
+                client_id,
+                token,
+                symbol,
+                side: order_message.side,
+                remaining_volume: volume,
+            },
+        );
+        responses.push((
+            client_id,
+            OuchResponse::Accepted {
+                timestamp,
+                token,
+                symbol,
+                side: order_message.side,
+                r#type: order_message.r#type,
+                volume,
+                price: order_message.price.unwrap_or(0),
+                order_id: order_message.id,
+            },
+        ));
+        self.report_trades(&symbol, order_message.id, order_message.r#type, responses);
+    }
+
+    fn replace(
+        &mut self,
+        client_id: ClientId,
+        existing_token: OrderToken,
+        replacement_token: OrderToken,
+        volume: OrderVolume,
+        price: OrderPrice,
+        responses: &mut Vec<(ClientId, OuchResponse)>,
+    ) {
+        let result = self
+            .order_id(client_id, existing_token)
+            .ok_or(REJECT_REASON_UNKNOWN_ORDER)
+            .and_then(|existing_id| {
+                self.check_token(client_id, replacement_token)?;
+                let existing_order = self.orders[&existing_id].clone();
+                let order_message = OrderMessage {
+                    target_id: Some(existing_id),
+                    side: existing_order.side,
+                    r#type: OrderType::Replace,
+                    volume: Some(volume).filter(|volume| *volume > 0),
+                    price: Some(price).filter(|price| *price > 0),
+                    session_id: Some(client_id),
+                    ..Default::default()
+                };
+                let order_events = self.try_execute_order(&existing_order.symbol, &order_message)?;
+                Ok((existing_id, existing_order, order_message, order_events))
+            });
+
+        let (existing_id, existing_order, order_message, order_events) = match result {
+            Ok(replaced) => replaced,
+            Err(reason) => return responses.push((client_id, rejected(replacement_token, reason))),
+        };
+
+        self.orders.remove(&existing_id);
+        self.tokens.remove(&(client_id, existing_token));
+        self.used_tokens.insert((client_id, replacement_token));
+        self.tokens.insert((client_id, replacement_token), order_message.id);
+        self.orders.insert(
+            order_message.id,
+            OuchOrder {
+                token: replacement_token,
+                remaining_volume: volume,
+                ..existing_order
+            },
+        );
+        responses.push((
+            client_id,
+            OuchResponse::Replaced {
+                timestamp: order_events[&order_message.id][0].timestamp,
+                replacement_token,
+                previous_token: existing_token,
+                volume,
+                price,
+                order_id: order_message.id,
+            },
+        ));
+        self.report_trades(&existing_order.symbol, order_message.id, OrderType::Replace, responses);
+    }
+
+    fn cancel(&mut self, client_id: ClientId, token: OrderToken, responses: &mut Vec<(ClientId, OuchResponse)>) {
+        let order_id = match self.order_id(client_id, token) {
+            Some(order_id) => order_id,
+            None => return responses.push((client_id, rejected(token, REJECT_REASON_UNKNOWN_ORDER))),
+        };
+        let cancel_message = OrderMessage {
+            target_id: Some(order_id),
+            r#type: OrderType::Cancel,
+            ..Default::default()
+        };
+        let symbol = self.orders[&order_id].symbol;
+
+        match self.try_execute_order(&symbol, &cancel_message) {
+            Ok(order_events) => {
+                let cancelled_event = &order_events[&order_id][0];
+                self.orders.remove(&order_id);
+                self.tokens.remove(&(client_id, token));
+                responses.push((
+                    client_id,
+                    OuchResponse::Canceled {
+                        timestamp: cancelled_event.timestamp,
+                        token,
+                        canceled_volume: cancelled_event.remaining_volume.unwrap_or(0),
+                        reason: CANCEL_REASON_USER_REQUESTED,
+                    },
+                ));
+            }
+            Err(reason) => responses.push((client_id, rejected(token, reason))),
+        }
+    }
+
+    // Reports the trades of the last execution to both counterparties. Market
+    // orders never rest, whatever they could not fill is canceled.
+    fn report_trades(
+        &mut self,
+        symbol: &Symbol,
+        aggressor_id: OrderId,
+        aggressor_type: OrderType,
+        responses: &mut Vec<(ClientId, OuchResponse)>,
+    ) {
+        let the_orderbooks = &self.instruments[symbol];
+        let timestamp = the_orderbooks.now();
+        let trades = the_orderbooks.trades().to_vec();
+
+        for trade in trades.iter() {
+            self.last_match_number += 1;
+
+            for order_id in [trade.aggressor_id, trade.resting_id].iter() {
+                if let Some(order) = self.orders.get_mut(order_id) {
+                    order.remaining_volume = order.remaining_volume.saturating_sub(trade.volume);
+                    responses.push((
+                        order.client_id,
+                        OuchResponse::Executed {
+                            timestamp: trade.timestamp,
+                            token: order.token,
+                            executed_volume: trade.volume,
+                            price: trade.price,
+                            match_number: self.last_match_number,
+                            remaining_volume: order.remaining_volume,
+                        },
+                    ));
+                }
+            }
+
+            if trade.resting_remaining_volume == 0 {
+                self.forget(&trade.resting_id);
+            }
+        }
+
+        let remaining_volume = self.orders[&aggressor_id].remaining_volume;
+
+        if aggressor_type == OrderType::Market && remaining_volume > 0 {
+            let order = &self.orders[&aggressor_id];
+            responses.push((
+                order.client_id,
+                OuchResponse::Canceled {
+                    timestamp,
+                    token: order.token,
+                    canceled_volume: remaining_volume,
+                    reason: CANCEL_REASON_IMMEDIATE_OR_CANCEL,
+                },
+            ));
+        }
+
+        if aggressor_type == OrderType::Market || remaining_volume == 0 {
+            self.forget(&aggressor_id);
+        }
+    }
+
+    fn forget(&mut self, order_id: &Uuid) {
+        if let Some(order) = self.orders.remove(order_id) {
+            self.tokens.remove(&(order.client_id, order.token));
+        }
+    }
+}
+
+fn rejected(token: OrderToken, reason: u8) -> OuchResponse {
+    OuchResponse::Rejected {
+        timestamp: EngineClock::System.now(),
+        token,
+        reason,
+    }
+}
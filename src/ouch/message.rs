@@ -0,0 +1,572 @@
+use crate::orderbooks::*;
+use crate::risk::PreTradeRejection;
+use std::convert::TryInto;
+use uuid::Uuid;
+
+pub type OrderToken = u64;
+pub type MatchNumber = u64;
+pub type Symbol = [u8; SYMBOL_LENGTH];
+
+pub const SYMBOL_LENGTH: usize = 8;
+pub const LENGTH_PREFIX_SIZE: usize = 2;
+
+pub const ENTER_ORDER_SIZE: usize = 43;
+pub const REPLACE_ORDER_SIZE: usize = 33;
+pub const CANCEL_ORDER_SIZE: usize = 9;
+pub const ACCEPTED_SIZE: usize = 59;
+pub const REPLACED_SIZE: usize = 57;
+pub const EXECUTED_SIZE: usize = 49;
+pub const CANCELED_SIZE: usize = 26;
+pub const REJECTED_SIZE: usize = 18;
+
+// Reasons carried by Canceled messages.
+pub const CANCEL_REASON_USER_REQUESTED: u8 = b'U';
+pub const CANCEL_REASON_IMMEDIATE_OR_CANCEL: u8 = b'I';
+
+// Reasons carried by Rejected messages.
+pub const REJECT_REASON_HALTED: u8 = b'H';
+pub const REJECT_REASON_UNKNOWN_ORDER: u8 = b'U';
+pub const REJECT_REASON_DUPLICATE_TOKEN: u8 = b'D';
+pub const REJECT_REASON_INVALID_SIDE: u8 = b'B';
+pub const REJECT_REASON_INVALID_VOLUME: u8 = b'Z';
+pub const REJECT_REASON_INVALID_PRICE: u8 = b'X';
+pub const REJECT_REASON_MISSING_MAX_QUOTE: u8 = b'Q';
+pub const REJECT_REASON_IN_AUCTION: u8 = b'A';
+pub const REJECT_REASON_PRE_OPEN: u8 = b'P';
+pub const REJECT_REASON_CLOSED: u8 = b'C';
+pub const REJECT_REASON_OPENING_AUCTION: u8 = b'N';
+pub const REJECT_REASON_CLOSING_AUCTION: u8 = b'L';
+pub const REJECT_REASON_VOLATILITY_AUCTION: u8 = b'T';
+pub const REJECT_REASON_CONTINUOUS: u8 = b'R';
+pub const REJECT_REASON_RISK_LIMIT: u8 = b'K';
+pub const REJECT_REASON_OUTSIDE_PRICE_BAND: u8 = b'O';
+pub const REJECT_REASON_PRICE_BAND_BREACH: u8 = b'V';
+pub const REJECT_REASON_INVALID_EXPIRY: u8 = b'E';
+pub const REJECT_REASON_INSUFFICIENT_FUNDS: u8 = b'F';
+pub const REJECT_REASON_UNKNOWN_SYMBOL: u8 = b'S';
+pub const REJECT_REASON_INVALID_MESSAGE: u8 = b'M';
+
+// Inbound messages. Prices, volumes and quotes are unsigned big-endian
+// integers; a zero price or max quote means "not set".
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OuchRequest {
+    EnterOrder {
+        token: OrderToken,
+        symbol: Symbol,
+        side: OrderSide,
+        r#type: OrderType,
+        volume: OrderVolume,
+        price: OrderPrice,
+        max_quote: OrderQuote,
+    },
+    ReplaceOrder {
+        existing_token: OrderToken,
+        replacement_token: OrderToken,
+        volume: OrderVolume,
+        price: OrderPrice,
+    },
+    CancelOrder {
+        token: OrderToken,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OuchResponse {
+    Accepted {
+        timestamp: UTCNanoSeconds,
+        token: OrderToken,
+        symbol: Symbol,
+        side: OrderSide,
+        r#type: OrderType,
+        volume: OrderVolume,
+        price: OrderPrice,
+        order_id: OrderId,
+    },
+    Replaced {
+        timestamp: UTCNanoSeconds,
+        replacement_token: OrderToken,
+        previous_token: OrderToken,
+        volume: OrderVolume,
+        price: OrderPrice,
+        order_id: OrderId,
+    },
+    Executed {
+        timestamp: UTCNanoSeconds,
+        token: OrderToken,
+        executed_volume: OrderVolume,
+        price: OrderPrice,
+        match_number: MatchNumber,
+        remaining_volume: OrderVolume,
+    },
+    Canceled {
+        timestamp: UTCNanoSeconds,
+        token: OrderToken,
+        canceled_volume: OrderVolume,
+        reason: u8,
+    },
+    Rejected {
+        timestamp: UTCNanoSeconds,
+        token: OrderToken,
+        reason: u8,
+    },
+}
+
+pub fn symbol(name: &str) -> Symbol {
+    let mut symbol = [b' '; SYMBOL_LENGTH];
+
+    for (index, byte) in name.bytes().take(SYMBOL_LENGTH).enumerate() {
+        symbol[index] = byte;
+    }
+
+    symbol
+}
+
+pub fn symbol_name(symbol: &Symbol) -> String {
+    String::from_utf8_lossy(symbol).trim_end().to_string()
+}
+
+fn encode_side(side: OrderSide) -> u8 {
+    match side {
+        OrderSide::Bid => b'B',
+        OrderSide::Ask => b'S',
+        OrderSide::NoSide => b' ',
+    }
+}
+
+fn decode_side(side: u8) -> OrderSide {
+    match side {
+        b'B' => OrderSide::Bid,
+        b'S' => OrderSide::Ask,
+        _ => OrderSide::NoSide,
+    }
+}
+
+fn encode_type(r#type: OrderType) -> u8 {
+    match r#type {
+        OrderType::Market => b'M',
+        OrderType::Limit => b'L',
+        OrderType::Cancel => b'X',
+        OrderType::Replace => b'U',
+    }
+}
+
+fn decode_type(r#type: u8) -> Option<OrderType> {
+    match r#type {
+        b'M' => Some(OrderType::Market),
+        b'L' => Some(OrderType::Limit),
+        _ => None,
+    }
+}
+
+struct Reader<'a> {
+    payload: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn u8(&mut self) -> u8 {
+        let value = self.payload[0];
+        self.payload = &self.payload[1..];
+        value
+    }
+
+    fn bytes(&mut self, length: usize) -> &'a [u8] {
+        let (value, rest) = self.payload.split_at(length);
+        self.payload = rest;
+        value
+    }
+
+    fn u64(&mut self) -> u64 {
+        u64::from_be_bytes(self.bytes(8).try_into().unwrap())
+    }
+
+    fn i64(&mut self) -> i64 {
+        i64::from_be_bytes(self.bytes(8).try_into().unwrap())
+    }
+
+    fn uuid(&mut self) -> Uuid {
+        Uuid::from_slice(self.bytes(16)).unwrap()
+    }
+
+    fn symbol(&mut self) -> Symbol {
+        self.bytes(SYMBOL_LENGTH).try_into().unwrap()
+    }
+}
+
+fn frame(payload: Vec<u8>, buffer: &mut Vec<u8>) {
+    buffer.extend_from_slice(&(payload.len() as u16).to_be_bytes());
+    buffer.extend_from_slice(&payload);
+}
+
+// Splits the next length-prefixed payload off the front of `buffer`.
+pub fn take_payload(buffer: &mut Vec<u8>) -> Option<Vec<u8>> {
+    if buffer.len() < LENGTH_PREFIX_SIZE {
+        return None;
+    }
+
+    let payload_length = u16::from_be_bytes([buffer[0], buffer[1]]) as usize;
+
+    if buffer.len() < LENGTH_PREFIX_SIZE + payload_length {
+        return None;
+    }
+
+    let payload = buffer[LENGTH_PREFIX_SIZE..LENGTH_PREFIX_SIZE + payload_length].to_vec();
+    buffer.drain(..LENGTH_PREFIX_SIZE + payload_length);
+    Some(payload)
+}
+
+impl OuchRequest {
+    pub fn encode(&self, buffer: &mut Vec<u8>) {
+        let mut payload = Vec::with_capacity(ENTER_ORDER_SIZE);
+
+        match self {
+            OuchRequest::EnterOrder {
+                token,
+                symbol,
+                side,
+                r#type,
+                volume,
+                price,
+                max_quote,
+            } => {
+                payload.push(b'O');
+                payload.extend_from_slice(&token.to_be_bytes());
+                payload.extend_from_slice(symbol);
+                payload.push(encode_side(*side));
+                payload.push(encode_type(*r#type));
+                payload.extend_from_slice(&volume.to_be_bytes());
+                payload.extend_from_slice(&price.to_be_bytes());
+                payload.extend_from_slice(&max_quote.to_be_bytes());
+            }
+            OuchRequest::ReplaceOrder {
+                existing_token,
+                replacement_token,
+                volume,
+                price,
+            } => {
+                payload.push(b'U');
+                payload.extend_from_slice(&existing_token.to_be_bytes());
+                payload.extend_from_slice(&replacement_token.to_be_bytes());
+                payload.extend_from_slice(&volume.to_be_bytes());
+                payload.extend_from_slice(&price.to_be_bytes());
+            }
+            OuchRequest::CancelOrder { token } => {
+                payload.push(b'X');
+                payload.extend_from_slice(&token.to_be_bytes());
+            }
+        }
+
+        frame(payload, buffer);
+    }
+
+    pub fn decode(payload: &[u8]) -> Option<OuchRequest> {
+        let mut reader = Reader { payload };
+
+        match (payload.first(), payload.len()) {
+            (Some(b'O'), ENTER_ORDER_SIZE) => {
+                reader.u8();
+                Some(OuchRequest::EnterOrder {
+                    token: reader.u64(),
+                    symbol: reader.symbol(),
+                    side: decode_side(reader.u8()),
+                    r#type: decode_type(reader.u8())?,
+                    volume: reader.u64(),
+                    price: reader.u64(),
+                    max_quote: reader.u64(),
+                })
+            }
+            (Some(b'U'), REPLACE_ORDER_SIZE) => {
+                reader.u8();
+                Some(OuchRequest::ReplaceOrder {
+                    existing_token: reader.u64(),
+                    replacement_token: reader.u64(),
+                    volume: reader.u64(),
+                    price: reader.u64(),
+                })
+            }
+            (Some(b'X'), CANCEL_ORDER_SIZE) => {
+                reader.u8();
+                Some(OuchRequest::CancelOrder { token: reader.u64() })
+            }
+            _ => None,
+        }
+    }
+}
+
+impl OuchResponse {
+    pub fn encode(&self, buffer: &mut Vec<u8>) {
+        let mut payload = Vec::with_capacity(ACCEPTED_SIZE);
+
+        match self {
+            OuchResponse::Accepted {
+                timestamp,
+                token,
+                symbol,
+                side,
+                r#type,
+                volume,
+                price,
+                order_id,
+            } => {
+                payload.push(b'A');
+                payload.extend_from_slice(&timestamp.to_be_bytes());
+                payload.extend_from_slice(&token.to_be_bytes());
+                payload.extend_from_slice(symbol);
+                payload.push(encode_side(*side));
+                payload.push(encode_type(*r#type));
+                payload.extend_from_slice(&volume.to_be_bytes());
+                payload.extend_from_slice(&price.to_be_bytes());
+                payload.extend_from_slice(order_id.as_bytes());
+            }
+            OuchResponse::Replaced {
+                timestamp,
+                replacement_token,
+                previous_token,
+                volume,
+                price,
+                order_id,
+            } => {
+                payload.push(b'U');
+                payload.extend_from_slice(&timestamp.to_be_bytes());
+                payload.extend_from_slice(&replacement_token.to_be_bytes());
+                payload.extend_from_slice(&previous_token.to_be_bytes());
+                payload.extend_from_slice(&volume.to_be_bytes());
+                payload.extend_from_slice(&price.to_be_bytes());
+                payload.extend_from_slice(order_id.as_bytes());
+            }
+            OuchResponse::Executed {
+                timestamp,
+                token,
+                executed_volume,
+                price,
+                match_number,
+                remaining_volume,
+            } => {
+                payload.push(b'E');
+                payload.extend_from_slice(&timestamp.to_be_bytes());
+                payload.extend_from_slice(&token.to_be_bytes());
+                payload.extend_from_slice(&executed_volume.to_be_bytes());
+                payload.extend_from_slice(&price.to_be_bytes());
+                payload.extend_from_slice(&match_number.to_be_bytes());
+                payload.extend_from_slice(&remaining_volume.to_be_bytes());
+            }
+            OuchResponse::Canceled {
+                timestamp,
+                token,
+                canceled_volume,
+                reason,
+            } => {
+                payload.push(b'C');
+                payload.extend_from_slice(&timestamp.to_be_bytes());
+                payload.extend_from_slice(&token.to_be_bytes());
+                payload.extend_from_slice(&canceled_volume.to_be_bytes());
+                payload.push(*reason);
+            }
+            OuchResponse::Rejected {
+                timestamp,
+                token,
+                reason,
+            } => {
+                payload.push(b'J');
+                payload.extend_from_slice(&timestamp.to_be_bytes());
+                payload.extend_from_slice(&token.to_be_bytes());
+                payload.push(*reason);
+            }
+        }
+
+        frame(payload, buffer);
+    }
+
+    pub fn decode(payload: &[u8]) -> Option<OuchResponse> {
+        let mut reader = Reader { payload };
+
+        match (payload.first(), payload.len()) {
+            (Some(b'A'), ACCEPTED_SIZE) => {
+                reader.u8();
+                Some(OuchResponse::Accepted {
+                    timestamp: reader.i64(),
+                    token: reader.u64(),
+                    symbol: reader.symbol(),
+                    side: decode_side(reader.u8()),
+                    r#type: decode_type(reader.u8())?,
+                    volume: reader.u64(),
+                    price: reader.u64(),
+                    order_id: reader.uuid(),
+                })
+            }
+            (Some(b'U'), REPLACED_SIZE) => {
+                reader.u8();
+                Some(OuchResponse::Replaced {
+                    timestamp: reader.i64(),
+                    replacement_token: reader.u64(),
+                    previous_token: reader.u64(),
+                    volume: reader.u64(),
+                    price: reader.u64(),
+                    order_id: reader.uuid(),
+                })
+            }
+            (Some(b'E'), EXECUTED_SIZE) => {
+                reader.u8();
+                Some(OuchResponse::Executed {
+                    timestamp: reader.i64(),
+                    token: reader.u64(),
+                    executed_volume: reader.u64(),
+                    price: reader.u64(),
+                    match_number: reader.u64(),
+                    remaining_volume: reader.u64(),
+                })
+            }
+            (Some(b'C'), CANCELED_SIZE) => {
+                reader.u8();
+                Some(OuchResponse::Canceled {
+                    timestamp: reader.i64(),
+                    token: reader.u64(),
+                    canceled_volume: reader.u64(),
+                    reason: reader.u8(),
+                })
+            }
+            (Some(b'J'), REJECTED_SIZE) => {
+                reader.u8();
+                Some(OuchResponse::Rejected {
+                    timestamp: reader.i64(),
+                    token: reader.u64(),
+                    reason: reader.u8(),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    pub fn token(&self) -> OrderToken {
+        match self {
+            OuchResponse::Accepted { token, .. }
+            | OuchResponse::Executed { token, .. }
+            | OuchResponse::Canceled { token, .. }
+            | OuchResponse::Rejected { token, .. } => *token,
+            OuchResponse::Replaced { replacement_token, .. } => *replacement_token,
+        }
+    }
+}
+
+pub fn reject_reason(rejection: OrderRejection) -> u8 {
+    match rejection {
+        OrderRejection::UnknownOrder => REJECT_REASON_UNKNOWN_ORDER,
+        OrderRejection::DuplicateOrderId | OrderRejection::DuplicateClientOrderId => REJECT_REASON_DUPLICATE_TOKEN,
+        OrderRejection::MissingSide | OrderRejection::SideMismatch => REJECT_REASON_INVALID_SIDE,
+        OrderRejection::InvalidVolume => REJECT_REASON_INVALID_VOLUME,
+        OrderRejection::InvalidPrice => REJECT_REASON_INVALID_PRICE,
+        OrderRejection::MissingMaxQuote => REJECT_REASON_MISSING_MAX_QUOTE,
+        OrderRejection::InAuction => REJECT_REASON_IN_AUCTION,
+        // Unused while continuous trading accepts every order type.
+        OrderRejection::Session(SessionState::Continuous) => REJECT_REASON_CONTINUOUS,
+        OrderRejection::Session(SessionState::PreOpen) => REJECT_REASON_PRE_OPEN,
+        OrderRejection::Session(SessionState::OpeningAuction) => REJECT_REASON_OPENING_AUCTION,
+        OrderRejection::Session(SessionState::ClosingAuction) => REJECT_REASON_CLOSING_AUCTION,
+        OrderRejection::Session(SessionState::VolatilityAuction) => REJECT_REASON_VOLATILITY_AUCTION,
+        OrderRejection::Session(SessionState::Halted) => REJECT_REASON_HALTED,
+        OrderRejection::Session(SessionState::Closed) => REJECT_REASON_CLOSED,
+        OrderRejection::OutsidePriceBand => REJECT_REASON_OUTSIDE_PRICE_BAND,
+        OrderRejection::PriceBandBreach => REJECT_REASON_PRICE_BAND_BREACH,
+        OrderRejection::InvalidExpiry => REJECT_REASON_INVALID_EXPIRY,
+        OrderRejection::InsufficientFunds => REJECT_REASON_INSUFFICIENT_FUNDS,
+    }
+}
+
+pub fn pre_trade_reject_reason(rejection: PreTradeRejection) -> u8 {
+    match rejection {
+        PreTradeRejection::Risk(_) => REJECT_REASON_RISK_LIMIT,
+        PreTradeRejection::Order(rejection) => reject_reason(rejection),
+    }
+}
+
+#[cfg(test)]
+mod unit_test {
+    use super::*;
+
+    #[test]
+    fn test_message_sizes_and_round_trip() {
+        let mut buffer = Vec::new();
+        let requests = [
+            OuchRequest::EnterOrder {
+                token: 1,
+                symbol: symbol("BTC-IDR"),
+                side: OrderSide::Bid,
+                r#type: OrderType::Limit,
+                volume: 10,
+                price: 95_000,
+                max_quote: 0,
+            },
+            OuchRequest::ReplaceOrder {
+                existing_token: 1,
+                replacement_token: 2,
+                volume: 5,
+                price: 94_000,
+            },
+            OuchRequest::CancelOrder { token: 2 },
+        ];
+
+        for request in requests.iter() {
+            request.encode(&mut buffer);
+        }
+
+        assert_eq!(
+            buffer.len(),
+            3 * LENGTH_PREFIX_SIZE + ENTER_ORDER_SIZE + REPLACE_ORDER_SIZE + CANCEL_ORDER_SIZE
+        );
+
+        for request in requests.iter() {
+            let payload = take_payload(&mut buffer).unwrap();
+
+            assert_eq!(OuchRequest::decode(&payload).as_ref(), Some(request));
+        }
+
+        let responses = [
+            OuchResponse::Accepted {
+                timestamp: 1,
+                token: 1,
+                symbol: symbol("BTC-IDR"),
+                side: OrderSide::Ask,
+                r#type: OrderType::Market,
+                volume: 10,
+                price: 0,
+                order_id: Uuid::new_v4(),
+            },
+            OuchResponse::Replaced {
+                timestamp: 2,
+                replacement_token: 2,
+                previous_token: 1,
+                volume: 5,
+                price: 94_000,
+                order_id: Uuid::new_v4(),
+            },
+            OuchResponse::Executed {
+                timestamp: 3,
+                token: 2,
+                executed_volume: 4,
+                price: 94_000,
+                match_number: 1,
+                remaining_volume: 1,
+            },
+            OuchResponse::Canceled {
+                timestamp: 4,
+                token: 2,
+                canceled_volume: 1,
+                reason: CANCEL_REASON_USER_REQUESTED,
+            },
+            OuchResponse::Rejected {
+                timestamp: 5,
+                token: 3,
+                reason: REJECT_REASON_HALTED,
+            },
+        ];
+
+        for response in responses.iter() {
+            response.encode(&mut buffer);
+            let payload = take_payload(&mut buffer).unwrap();
+
+            assert_eq!(OuchResponse::decode(&payload).as_ref(), Some(response));
+        }
+
+        assert!(buffer.is_empty());
+        assert_eq!(symbol_name(&symbol("BTC-IDR")), "BTC-IDR");
+    }
+}
@@ -0,0 +1,7 @@
+mod gateway;
+mod message;
+mod server;
+
+pub use gateway::*;
+pub use message::*;
+pub use server::*;
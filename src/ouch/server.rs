@@ -0,0 +1,93 @@
+use super::gateway::*;
+use super::message::*;
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+const READ_BUFFER_SIZE: usize = 64 * 1024;
+
+pub struct OuchServer {
+    listener: TcpListener,
+    gateway: Arc<Mutex<OuchGateway>>,
+}
+
+impl OuchServer {
+    pub fn bind<A: ToSocketAddrs>(addr: A, symbols: &[String]) -> io::Result<OuchServer> {
+        Ok(OuchServer {
+            listener: TcpListener::bind(addr)?,
+            gateway: Arc::new(Mutex::new(OuchGateway::new(symbols))),
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    pub fn gateway(&self) -> Arc<Mutex<OuchGateway>> {
+        self.gateway.clone()
+    }
+
+    pub fn run(&self) -> io::Result<()> {
+        for stream in self.listener.incoming() {
+            let stream = stream?;
+            let gateway = self.gateway.clone();
+
+            thread::spawn(move || {
+                let peer_addr = stream.peer_addr();
+
+                if let Err(error) = serve_client(stream, gateway) {
+                    eprintln!("OUCH connection {:?} closed: {}", peer_addr, error);
+                }
+            });
+        }
+
+        Ok(())
+    }
+}
+
+fn serve_client(mut stream: TcpStream, gateway: Arc<Mutex<OuchGateway>>) -> io::Result<()> {
+    stream.set_nodelay(true)?;
+    let (outbox, inbox) = mpsc::channel();
+    let writer_stream = stream.try_clone()?;
+    thread::spawn(move || write_loop(writer_stream, inbox));
+    // The writer stops once `disconnect` drops the outbox.
+    let client_id = gateway.lock().unwrap().connect(outbox);
+    let result = read_loop(&mut stream, &gateway, client_id);
+    gateway.lock().unwrap().disconnect(client_id);
+    result
+}
+
+// A client that stops reading only blocks its own writer. A failed write
+// shuts the connection down, which ends the read loop.
+fn write_loop(mut stream: TcpStream, inbox: Receiver<Vec<u8>>) {
+    for buffer in inbox {
+        if stream.write_all(&buffer).is_err() {
+            let _ = stream.shutdown(Shutdown::Both);
+            return;
+        }
+    }
+}
+
+fn read_loop(stream: &mut TcpStream, gateway: &Mutex<OuchGateway>, client_id: ClientId) -> io::Result<()> {
+    let mut buffer = Vec::new();
+    let mut chunk = vec![0; READ_BUFFER_SIZE];
+
+    loop {
+        let read_size = stream.read(&mut chunk)?;
+
+        if read_size == 0 {
+            return Ok(());
+        }
+
+        buffer.extend_from_slice(&chunk[..read_size]);
+
+        // Everything that arrived in one read is handled under a single lock.
+        let mut gateway = gateway.lock().unwrap();
+
+        while let Some(payload) = take_payload(&mut buffer) {
+            gateway.handle(client_id, &payload);
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use crate::orderbooks::*;
+
+pub const HELP: &str = "\
+buy limit VOLUME @ PRICE          enter a limit bid
+sell limit VOLUME @ PRICE         enter a limit ask
+buy market VOLUME [max QUOTE]     enter a market bid, optionally capped at QUOTE
+sell market VOLUME                enter a market ask
+cancel #N|UUID                    cancel a resting order
+book [LEVELS]                     show the price ladder
+trades                            show every trade of the session
+help                              show this help
+quit                              leave the REPL";
+
+// Orders are referred to either by the `#N` shown when they were entered, so
+// scripts stay reproducible, or by their full id.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OrderRef {
+    Number(usize),
+    Id(OrderId),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReplCommand {
+    Submit {
+        side: OrderSide,
+        r#type: OrderType,
+        volume: OrderVolume,
+        price: Option<OrderPrice>,
+        max_quote: Option<OrderQuote>,
+    },
+    Cancel(OrderRef),
+    Book(Option<usize>),
+    Trades,
+    Help,
+    Quit,
+    Nothing,
+}
+
+fn parse_number<T: std::str::FromStr>(word: Option<&str>, name: &str) -> Result<T, String> {
+    let word = word.ok_or_else(|| format!("Missing {}", name))?;
+    word.parse().map_err(|_| format!("Invalid {}: {}", name, word))
+}
+
+impl ReplCommand {
+    pub fn parse(line: &str) -> Result<ReplCommand, String> {
+        let line = line.trim();
+        let words: Vec<&str> = line.split_whitespace().collect();
+
+        match words.as_slice() {
+            [] => Ok(ReplCommand::Nothing),
+            _ if line.starts_with('#') => Ok(ReplCommand::Nothing),
+            ["buy", rest @ ..] => ReplCommand::parse_submit(OrderSide::Bid, rest),
+            ["sell", rest @ ..] => ReplCommand::parse_submit(OrderSide::Ask, rest),
+            ["cancel", order_ref] => Ok(ReplCommand::Cancel(OrderRef::parse(order_ref)?)),
+            ["book"] => Ok(ReplCommand::Book(None)),
+            ["book", levels] => Ok(ReplCommand::Book(Some(parse_number(Some(levels), "levels")?))),
+            ["trades"] => Ok(ReplCommand::Trades),
+            ["help"] => Ok(ReplCommand::Help),
+            ["quit"] | ["exit"] => Ok(ReplCommand::Quit),
+            _ => Err(format!("Unknown command: {}", line)),
+        }
+    }
+
+    fn parse_submit(side: OrderSide, words: &[&str]) -> Result<ReplCommand, String> {
+        match words {
+            ["limit", volume, "@", price] => Ok(ReplCommand::Submit {
+                side,
+                r#type: OrderType::Limit,
+                volume: parse_number(Some(volume), "volume")?,
+                price: Some(parse_number(Some(price), "price")?),
+                max_quote: None,
+            }),
+            ["market", volume] => Ok(ReplCommand::Submit {
+                side,
+                r#type: OrderType::Market,
+                volume: parse_number(Some(volume), "volume")?,
+                price: None,
+                max_quote: None,
+            }),
+            ["market", volume, "max", max_quote] => Ok(ReplCommand::Submit {
+                side,
+                r#type: OrderType::Market,
+                volume: parse_number(Some(volume), "volume")?,
+                price: None,
+                max_quote: Some(parse_number(Some(max_quote), "max quote")?),
+            }),
+            _ => Err("Expected `limit VOLUME @ PRICE` or `market VOLUME [max QUOTE]`".to_string()),
+        }
+    }
+}
+
+impl OrderRef {
+    pub fn parse(word: &str) -> Result<OrderRef, String> {
+        if let Ok(number) = word.trim_start_matches('#').parse() {
+            return Ok(OrderRef::Number(number));
+        }
+
+        word.parse()
+            .map(OrderRef::Id)
+            .map_err(|_| format!("Invalid order: {}", word))
+    }
+}
+
+#[cfg(test)]
+mod unit_test {
+    use super::*;
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(
+            ReplCommand::parse("buy limit 100 @ 95000"),
+            Ok(ReplCommand::Submit {
+                side: OrderSide::Bid,
+                r#type: OrderType::Limit,
+                volume: 100,
+                price: Some(95_000),
+                max_quote: None,
+            })
+        );
+        assert_eq!(
+            ReplCommand::parse("  sell market 20  "),
+            Ok(ReplCommand::Submit {
+                side: OrderSide::Ask,
+                r#type: OrderType::Market,
+                volume: 20,
+                price: None,
+                max_quote: None,
+            })
+        );
+        assert_eq!(
+            ReplCommand::parse("buy market 20 max 1000000"),
+            Ok(ReplCommand::Submit {
+                side: OrderSide::Bid,
+                r#type: OrderType::Market,
+                volume: 20,
+                price: None,
+                max_quote: Some(1_000_000),
+            })
+        );
+        assert_eq!(
+            ReplCommand::parse("cancel #3"),
+            Ok(ReplCommand::Cancel(OrderRef::Number(3)))
+        );
+        assert_eq!(ReplCommand::parse("book 5"), Ok(ReplCommand::Book(Some(5))));
+        assert_eq!(ReplCommand::parse("# a comment"), Ok(ReplCommand::Nothing));
+        assert!(ReplCommand::parse("buy limit 100 95000").is_err());
+        assert!(ReplCommand::parse("sell limit x @ 1").is_err());
+        assert!(ReplCommand::parse("cancel nope").is_err());
+    }
+}
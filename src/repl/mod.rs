@@ -0,0 +1,5 @@
+mod command;
+mod session;
+
+pub use command::*;
+pub use session::*;
@@ -0,0 +1,240 @@
+use super::command::*;
+use crate::orderbooks::*;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+#[derive(Clone, Debug, Default)]
+pub struct ReplSession {
+    pub orderbooks: Orderbooks,
+    pub order_ids: Vec<OrderId>,
+    pub trades: Vec<Trade>,
+    order_numbers: HashMap<OrderId, usize>,
+}
+
+fn event_name(r#type: OrderEventType) -> &'static str {
+    match r#type {
+        OrderEventType::NoMatch => "no_match",
+        OrderEventType::HasMatch => "has_match",
+        OrderEventType::Open => "open",
+        OrderEventType::Closed => "closed",
+        OrderEventType::Cancelled => "cancelled",
+        OrderEventType::Replaced => "replaced",
+        OrderEventType::Expired => "expired",
+        OrderEventType::MassCancelled => "mass_cancelled",
+        OrderEventType::Rejected => "rejected",
+    }
+}
+
+fn side_name(side: OrderSide) -> &'static str {
+    match side {
+        OrderSide::Bid => "bid",
+        OrderSide::Ask => "ask",
+        OrderSide::NoSide => "-",
+    }
+}
+
+impl ReplSession {
+    // Returns the text to print; rejected orders and malformed input are errors.
+    pub fn run(&mut self, command: ReplCommand) -> Result<String, String> {
+        match command {
+            ReplCommand::Submit {
+                side,
+                r#type,
+                volume,
+                price,
+                mut max_quote,
+            } => {
+                if r#type == OrderType::Market && side == OrderSide::Bid {
+                    max_quote = max_quote.or(Some(OrderQuote::MAX));
+                }
+
+                let order_message = OrderMessage {
+                    side,
+                    r#type,
+                    volume: Some(volume),
+                    price,
+                    max_quote,
+                    ..Default::default()
+                };
+                self.execute(order_message, true)
+            }
+            ReplCommand::Cancel(order_ref) => {
+                let cancel_message = OrderMessage {
+                    target_id: Some(self.resolve(order_ref)?),
+                    r#type: OrderType::Cancel,
+                    ..Default::default()
+                };
+                self.execute(cancel_message, false)
+            }
+            ReplCommand::Book(max_levels) => Ok(self.book(max_levels.unwrap_or(usize::MAX))),
+            ReplCommand::Trades => Ok(self.trades()),
+            ReplCommand::Help => Ok(HELP.to_string()),
+            ReplCommand::Quit | ReplCommand::Nothing => Ok(String::new()),
+        }
+    }
+
+    pub fn label(&self, order_id: &OrderId) -> String {
+        match self.order_numbers.get(order_id) {
+            Some(number) => format!("#{}", number),
+            None => order_id.to_string(),
+        }
+    }
+
+    fn resolve(&self, order_ref: OrderRef) -> Result<OrderId, String> {
+        match order_ref {
+            OrderRef::Number(number) => number
+                .checked_sub(1)
+                .and_then(|index| self.order_ids.get(index))
+                .copied()
+                .ok_or_else(|| format!("No order #{}", number)),
+            OrderRef::Id(order_id) => Ok(order_id),
+        }
+    }
+
+    fn execute(&mut self, order_message: OrderMessage, numbered: bool) -> Result<String, String> {
+        let order_events = self
+            .orderbooks
+            .try_execute_order(&order_message)
+            .map_err(|rejection| rejection.to_string())?;
+        let mut output = String::new();
+
+        if numbered {
+            self.order_ids.push(order_message.id);
+            self.order_numbers.insert(order_message.id, self.order_ids.len());
+            writeln!(
+                output,
+                "{} accepted as {}",
+                self.label(&order_message.id),
+                order_message.id
+            )
+            .unwrap();
+        }
+
+        self.trades.extend(self.orderbooks.trades().iter().cloned());
+
+        // The entered order first, then its counterparties in the order they were entered.
+        let mut order_ids: Vec<&OrderId> = order_events
+            .keys()
+            .filter(|order_id| numbered || **order_id != order_message.id)
+            .collect();
+        order_ids.sort_by_key(|order_id| {
+            (
+                **order_id != order_message.id,
+                self.order_numbers.get(order_id).copied().unwrap_or(usize::MAX),
+            )
+        });
+
+        for order_id in order_ids {
+            for event in order_events[order_id].iter() {
+                write!(output, "  {} {}", self.label(order_id), event_name(event.r#type)).unwrap();
+
+                if let Some(remaining_volume) = event.remaining_volume {
+                    write!(output, " remaining={}", remaining_volume).unwrap();
+                }
+
+                if let Some(traded_price) = event.traded_price {
+                    write!(output, " price={}", traded_price).unwrap();
+                }
+
+                match event.crossed_id {
+                    Some(crossed_id) if crossed_id != *order_id => {
+                        write!(output, " crossed={}", self.label(&crossed_id)).unwrap()
+                    }
+                    _ => {}
+                }
+
+                output.push('\n');
+            }
+        }
+
+        Ok(output.trim_end().to_string())
+    }
+
+    fn book(&self, max_levels: usize) -> String {
+        let snapshot = self.orderbooks.depth_snapshot(max_levels);
+        let mut output = String::new();
+
+        for (price, volume) in snapshot.asks.iter().rev() {
+            writeln!(output, "  ask {:>12} {:>12}", price, volume).unwrap();
+        }
+
+        writeln!(output, "  {}", "-".repeat(29)).unwrap();
+
+        for (price, volume) in snapshot.bids.iter() {
+            writeln!(output, "  bid {:>12} {:>12}", price, volume).unwrap();
+        }
+
+        output.trim_end().to_string()
+    }
+
+    fn trades(&self) -> String {
+        if self.trades.is_empty() {
+            return "  no trades".to_string();
+        }
+
+        self.trades
+            .iter()
+            .map(|trade| {
+                format!(
+                    "  {} {} {} @ {} resting {} remaining={}",
+                    self.label(&trade.aggressor_id),
+                    side_name(trade.aggressor_side),
+                    trade.volume,
+                    trade.price,
+                    self.label(&trade.resting_id),
+                    trade.resting_remaining_volume
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod unit_test {
+    use super::*;
+
+    fn run(session: &mut ReplSession, line: &str) -> Result<String, String> {
+        session.run(ReplCommand::parse(line)?)
+    }
+
+    #[test]
+    fn test_session_output() {
+        let mut session = ReplSession::default();
+
+        assert!(run(&mut session, "sell limit 10 @ 100")
+            .unwrap()
+            .ends_with("  #1 no_match remaining=10\n  #1 open remaining=10"));
+        assert!(run(&mut session, "sell limit 5 @ 101").is_ok());
+        assert_eq!(
+            run(&mut session, "buy limit 12 @ 101")
+                .unwrap()
+                .lines()
+                .skip(1)
+                .collect::<Vec<_>>(),
+            vec![
+                "  #3 has_match remaining=2 price=100 crossed=#1",
+                "  #3 has_match remaining=0 price=101 crossed=#2",
+                "  #3 closed remaining=0",
+                "  #1 has_match remaining=0 price=100 crossed=#3",
+                "  #1 closed remaining=0",
+                "  #2 has_match remaining=3 price=101 crossed=#3",
+            ]
+        );
+        assert_eq!(
+            run(&mut session, "book").unwrap(),
+            "  ask          101            3\n  -----------------------------"
+        );
+        assert_eq!(
+            run(&mut session, "trades").unwrap(),
+            "  #3 bid 10 @ 100 resting #1 remaining=0\n  #3 bid 2 @ 101 resting #2 remaining=3"
+        );
+        assert_eq!(run(&mut session, "cancel #2").unwrap(), "  #2 cancelled remaining=3");
+        assert_eq!(run(&mut session, "cancel #2"), Err("Unknown order".to_string()));
+        assert_eq!(run(&mut session, "cancel #9"), Err("No order #9".to_string()));
+        assert_eq!(
+            run(&mut session, "sell limit 0 @ 100"),
+            Err("Order volume must be positive".to_string())
+        );
+    }
+}
@@ -0,0 +1,5 @@
+mod order_log;
+mod replayer;
+
+pub use order_log::*;
+pub use replayer::*;
@@ -0,0 +1,100 @@
+use crate::orderbooks::*;
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead, BufReader, ErrorKind, Read};
+use std::path::Path;
+use uuid::Uuid;
+
+// One line of an exported order log. Logs without ids get one derived from the
+// record number, so the same file always replays with the same ids.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct LoggedOrder {
+    pub id: Option<OrderId>,
+    pub r#type: OrderType,
+    pub side: Option<OrderSide>,
+    pub price: Option<OrderPrice>,
+    pub volume: Option<OrderVolume>,
+    pub max_quote: Option<OrderQuote>,
+    pub target_id: Option<OrderId>,
+    pub timestamp: UTCNanoSeconds,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum OrderLogFormat {
+    Csv,
+    Ndjson,
+}
+
+impl LoggedOrder {
+    pub fn to_order_message(&self, record_number: usize) -> OrderMessage {
+        OrderMessage {
+            id: self.id.unwrap_or_else(|| Uuid::from_u128(record_number as u128)),
+            target_id: self.target_id,
+            created_at: self.timestamp,
+            side: self.side.unwrap_or(OrderSide::NoSide),
+            r#type: self.r#type,
+            volume: self.volume,
+            price: self.price,
+            max_quote: self.max_quote,
+            time_in_force: TimeInForce::GoodTillCancel,
+            expires_at: None,
+            account: None,
+            session_id: None,
+            client_order_id: None,
+            target_client_order_id: None,
+            events: Vec::new(),
+        }
+    }
+}
+
+impl OrderLogFormat {
+    pub fn from_path(path: &Path) -> Option<OrderLogFormat> {
+        match path.extension()?.to_str()? {
+            "csv" => Some(OrderLogFormat::Csv),
+            "ndjson" | "jsonl" => Some(OrderLogFormat::Ndjson),
+            _ => None,
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<OrderLogFormat> {
+        match name {
+            "csv" => Some(OrderLogFormat::Csv),
+            "ndjson" => Some(OrderLogFormat::Ndjson),
+            _ => None,
+        }
+    }
+}
+
+fn invalid_record(line: usize, error: impl ToString) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, format!("Line {}: {}", line, error.to_string()))
+}
+
+// Yields the orders in log order. CSV logs need a header row, NDJSON logs skip
+// blank lines. A record that does not parse yields an error naming its line,
+// and reading goes on with the next record.
+pub fn read_order_log<'a, R: Read + 'a>(
+    input: R,
+    format: OrderLogFormat,
+) -> Box<dyn Iterator<Item = io::Result<LoggedOrder>> + 'a> {
+    match format {
+        OrderLogFormat::Csv => Box::new(
+            csv::ReaderBuilder::new()
+                .trim(csv::Trim::All)
+                .from_reader(input)
+                .into_deserialize()
+                .enumerate()
+                .map(|(index, record)| {
+                    record.map_err(|error| {
+                        let line = error.position().map_or(index as u64 + 2, |position| position.line());
+                        invalid_record(line as usize, error)
+                    })
+                }),
+        ),
+        OrderLogFormat::Ndjson => Box::new(
+            BufReader::new(input)
+                .lines()
+                .enumerate()
+                .filter(|(_, line)| !matches!(line, Ok(line) if line.trim().is_empty()))
+                .map(|(index, line)| serde_json::from_str(&line?).map_err(|error| invalid_record(index + 1, error))),
+        ),
+    }
+}
@@ -0,0 +1,175 @@
+use super::order_log::*;
+use crate::orderbooks::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::{self, ErrorKind, Read, Write};
+
+// What one logged order did. Events are keyed by order id in a `BTreeMap` so
+// two runs over the same log serialize to identical lines.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ReplayResult {
+    pub record: usize,
+    pub order_id: OrderId,
+    pub timestamp: UTCNanoSeconds,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rejection: Option<String>,
+    pub events: BTreeMap<OrderId, Vec<OrderEvent>>,
+    pub trades: Vec<Trade>,
+}
+
+// `invalid_records` holds one message per record that did not parse, naming
+// its line.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReplaySummary {
+    pub orders: usize,
+    pub rejected: usize,
+    pub trades: usize,
+    pub invalid_records: Vec<String>,
+}
+
+// A strict replay stops at the first record that does not parse, otherwise
+// the record is skipped and noted in the summary.
+#[derive(Clone, Debug, Default)]
+pub struct Replayer {
+    pub orderbooks: Orderbooks,
+    pub summary: ReplaySummary,
+    pub strict: bool,
+}
+
+impl Replayer {
+    // The engine clock follows the logged timestamps, so every event and trade
+    // carries the time of the order that caused it.
+    pub fn replay(&mut self, record: usize, logged_order: &LoggedOrder) -> ReplayResult {
+        let order_message = logged_order.to_order_message(record);
+        self.orderbooks.set_clock(EngineClock::Manual(logged_order.timestamp));
+        self.summary.orders += 1;
+        let mut result = ReplayResult {
+            record,
+            order_id: order_message.id,
+            timestamp: logged_order.timestamp,
+            rejection: None,
+            events: BTreeMap::new(),
+            trades: Vec::new(),
+        };
+
+        match self.orderbooks.try_execute_order(&order_message) {
+            Ok(order_events) => {
+                result.events = order_events.into_iter().collect();
+                result.trades = self.orderbooks.trades().to_vec();
+                self.summary.trades += result.trades.len();
+            }
+            Err(rejection) => {
+                result.rejection = Some(rejection.to_string());
+                self.summary.rejected += 1;
+            }
+        }
+
+        result
+    }
+
+    // Writes one NDJSON line per logged order.
+    pub fn replay_log<R: Read, W: Write>(
+        &mut self,
+        input: R,
+        format: OrderLogFormat,
+        mut output: W,
+    ) -> io::Result<ReplaySummary> {
+        for (index, logged_order) in read_order_log(input, format).enumerate() {
+            let logged_order = match logged_order {
+                Ok(logged_order) => logged_order,
+                Err(error) if !self.strict && error.kind() == ErrorKind::InvalidData => {
+                    self.summary.invalid_records.push(error.to_string());
+                    continue;
+                }
+                Err(error) => return Err(error),
+            };
+            let result = self.replay(index + 1, &logged_order);
+            serde_json::to_writer(&mut output, &result)?;
+            output.write_all(b"\n")?;
+        }
+
+        output.flush()?;
+        Ok(self.summary.clone())
+    }
+}
+
+#[cfg(test)]
+mod unit_test {
+    use super::*;
+
+    #[test]
+    fn test_csv_and_ndjson_replay_identically() {
+        let csv_log = "\
+type,side,price,volume,max_quote,target_id,timestamp
+limit,ask,100,10,,,1000
+market,bid,,4,1000,,2000
+cancel,,,,,00000000-0000-0000-0000-000000000001,3000
+cancel,,,,,00000000-0000-0000-0000-000000000001,4000
+";
+        let ndjson_log = r#"
+{"type": "limit", "side": "ask", "price": 100, "volume": 10, "timestamp": 1000}
+{"type": "market", "side": "bid", "volume": 4, "max_quote": 1000, "timestamp": 2000}
+
+{"type": "cancel", "target_id": "00000000-0000-0000-0000-000000000001", "timestamp": 3000}
+{"type": "cancel", "target_id": "00000000-0000-0000-0000-000000000001", "timestamp": 4000}
+"#;
+        let mut csv_output = Vec::new();
+        let mut ndjson_output = Vec::new();
+        let summary = Replayer::default()
+            .replay_log(csv_log.as_bytes(), OrderLogFormat::Csv, &mut csv_output)
+            .unwrap();
+        Replayer::default()
+            .replay_log(ndjson_log.as_bytes(), OrderLogFormat::Ndjson, &mut ndjson_output)
+            .unwrap();
+
+        assert_eq!(
+            summary,
+            ReplaySummary {
+                orders: 4,
+                rejected: 1,
+                trades: 1,
+                invalid_records: Vec::new(),
+            }
+        );
+        assert_eq!(csv_output, ndjson_output);
+
+        let results: Vec<ReplayResult> = String::from_utf8(csv_output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(results[1].trades[0].timestamp, 2000);
+        assert_eq!(results[1].trades[0].resting_id, results[0].order_id);
+        assert_eq!(results[2].events[&results[0].order_id][0].remaining_volume, Some(6));
+        assert_eq!(results[3].rejection, Some("Unknown order".to_string()));
+
+        let bad_log = "type,side,price,volume,timestamp\nlimit,up,1,1,1\nlimit,ask,1,1,2\n";
+        let error = Replayer {
+            strict: true,
+            ..Default::default()
+        }
+        .replay_log(bad_log.as_bytes(), OrderLogFormat::Csv, Vec::new())
+        .unwrap_err();
+
+        assert!(error.to_string().starts_with("Line 2:"));
+    }
+
+    #[test]
+    fn test_invalid_records_are_skipped() {
+        let ndjson_log = r#"{"type": "limit", "side": "ask", "price": 1, "volume": 1, "timestamp": 1}
+
+{"type": "limit", "side": "up"}
+{"type": "market", "side": "ask", "volume": 1, "timestamp": 2}
+"#;
+        let mut output = Vec::new();
+        let summary = Replayer::default()
+            .replay_log(ndjson_log.as_bytes(), OrderLogFormat::Ndjson, &mut output)
+            .unwrap();
+
+        assert_eq!(summary.orders, 2);
+        assert_eq!(summary.invalid_records.len(), 1);
+        assert!(summary.invalid_records[0].starts_with("Line 3:"));
+        assert_eq!(String::from_utf8(output).unwrap().lines().count(), 2);
+    }
+}
@@ -0,0 +1,5 @@
+mod server;
+mod service;
+
+pub use server::*;
+pub use service::*;
@@ -0,0 +1,52 @@
+use super::service::*;
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use tiny_http::{Header, Response, Server};
+
+pub struct RestServer {
+    server: Server,
+    service: Arc<Mutex<RestService>>,
+}
+
+impl RestServer {
+    pub fn bind<A: ToSocketAddrs>(addr: A, symbols: &[String]) -> io::Result<RestServer> {
+        Ok(RestServer {
+            server: Server::http(addr).map_err(io::Error::other)?,
+            service: Arc::new(Mutex::new(RestService::new(symbols))),
+        })
+    }
+
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.server.server_addr().to_ip()
+    }
+
+    pub fn service(&self) -> Arc<Mutex<RestService>> {
+        self.service.clone()
+    }
+
+    pub fn run(&self) -> io::Result<()> {
+        let content_type = Header::from_bytes("Content-Type", "application/json").unwrap();
+
+        for mut request in self.server.incoming_requests() {
+            let mut body = String::new();
+
+            let (status, response_body) = match request.as_reader().read_to_string(&mut body) {
+                Ok(_) => {
+                    let method = request.method().as_str().to_string();
+                    self.service.lock().unwrap().handle(&method, request.url(), &body)
+                }
+                Err(_) => (400, r#"{"error":"Request body is not UTF-8"}"#.to_string()),
+            };
+            let response = Response::from_string(response_body)
+                .with_status_code(status)
+                .with_header(content_type.clone());
+
+            if let Err(error) = request.respond(response) {
+                eprintln!("Failed to send HTTP response: {}", error);
+            }
+        }
+
+        Ok(())
+    }
+}
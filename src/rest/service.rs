@@ -0,0 +1,359 @@
+use crate::orderbooks::*;
+use crate::risk::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+pub const RECENT_TRADES_CAPACITY: usize = 1_000;
+pub const DEFAULT_TRADES_LIMIT: usize = 100;
+
+pub type HttpStatus = u16;
+
+#[derive(Clone, Debug, Default)]
+pub struct Instrument {
+    pub orderbooks: Orderbooks,
+    pub risk: RiskEngine,
+    pub recent_trades: VecDeque<Trade>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct InstrumentStatus {
+    pub symbol: String,
+    pub halted: bool,
+    pub session: SessionState,
+    pub ticker: Ticker,
+}
+
+// Events of the orders a session change closed, e.g. by uncrossing an auction.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SessionResult {
+    #[serde(flatten)]
+    pub status: InstrumentStatus,
+    pub events: HashMap<OrderId, Vec<OrderEvent>>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SessionRequest {
+    pub state: SessionState,
+}
+
+// Without an account it sets the default limits.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RiskRequest {
+    #[serde(default)]
+    pub account: Option<AccountId>,
+    pub limits: RiskLimits,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RiskStatus {
+    pub account: Option<AccountId>,
+    pub limits: RiskLimits,
+    pub position: Position,
+    pub open_orders: usize,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct OrderStatus {
+    pub id: OrderId,
+    pub side: OrderSide,
+    pub price: PriceLevel,
+    pub remaining_volume: OrderVolume,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct OrderResult {
+    pub order_id: OrderId,
+    pub events: HashMap<OrderId, Vec<OrderEvent>>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ErrorResponse {
+    pub error: String,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct RestService {
+    pub instruments: HashMap<String, Instrument>,
+}
+
+pub fn rejection_status(rejection: OrderRejection) -> HttpStatus {
+    match rejection {
+        OrderRejection::UnknownOrder => 404,
+        OrderRejection::DuplicateOrderId
+        | OrderRejection::DuplicateClientOrderId
+        | OrderRejection::InAuction
+        | OrderRejection::Session(_) => 409,
+        OrderRejection::MissingSide
+        | OrderRejection::InvalidVolume
+        | OrderRejection::InvalidPrice
+        | OrderRejection::MissingMaxQuote
+        | OrderRejection::SideMismatch
+        | OrderRejection::OutsidePriceBand
+        | OrderRejection::PriceBandBreach
+        | OrderRejection::InvalidExpiry
+        | OrderRejection::InsufficientFunds => 422,
+    }
+}
+
+pub fn pre_trade_rejection_status(rejection: PreTradeRejection) -> HttpStatus {
+    match rejection {
+        PreTradeRejection::Risk(RiskRejection {
+            limit: RiskLimit::MaxMessageRate,
+            ..
+        }) => 429,
+        PreTradeRejection::Risk(_) => 422,
+        PreTradeRejection::Order(rejection) => rejection_status(rejection),
+    }
+}
+
+fn json_response<T: Serialize>(status: HttpStatus, body: &T) -> (HttpStatus, String) {
+    (status, serde_json::to_string(body).unwrap())
+}
+
+fn error_response(status: HttpStatus, error: &str) -> (HttpStatus, String) {
+    json_response(
+        status,
+        &ErrorResponse {
+            error: error.to_string(),
+        },
+    )
+}
+
+fn query_param<'a>(query: &'a str, name: &str) -> Option<&'a str> {
+    query
+        .split('&')
+        .filter_map(|pair| {
+            let mut key_value = pair.splitn(2, '=');
+            Some((key_value.next()?, key_value.next()?))
+        })
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
+
+impl Instrument {
+    fn record_trades(&mut self) {
+        for trade in self.orderbooks.trades().iter() {
+            if self.recent_trades.len() == RECENT_TRADES_CAPACITY {
+                self.recent_trades.pop_front();
+            }
+
+            self.recent_trades.push_back(trade.clone());
+        }
+    }
+}
+
+impl RestService {
+    pub fn new(symbols: &[String]) -> RestService {
+        RestService {
+            instruments: symbols
+                .iter()
+                .map(|symbol| (symbol.clone(), Instrument::default()))
+                .collect(),
+        }
+    }
+
+    pub fn handle(&mut self, method: &str, url: &str, body: &str) -> (HttpStatus, String) {
+        let (path, query) = match url.find('?') {
+            Some(query_start) => (&url[..query_start], &url[query_start + 1..]),
+            None => (url, ""),
+        };
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+
+        if segments == ["instruments"] {
+            return match method {
+                "GET" => {
+                    let mut symbols: Vec<&String> = self.instruments.keys().collect();
+                    symbols.sort();
+                    let statuses: Vec<InstrumentStatus> =
+                        symbols.iter().map(|symbol| self.instrument_status(symbol)).collect();
+                    json_response(200, &statuses)
+                }
+                _ => error_response(405, "Method not allowed"),
+            };
+        }
+
+        if segments.len() < 2 || segments[0] != "instruments" {
+            return error_response(404, "Not found");
+        }
+
+        let symbol = segments[1];
+
+        if !self.instruments.contains_key(symbol) {
+            return error_response(404, "Unknown symbol");
+        }
+
+        match (method, &segments[2..]) {
+            ("GET", []) => json_response(200, &self.instrument_status(symbol)),
+            ("GET", ["depth"]) => {
+                let max_levels = query_param(query, "levels").and_then(|levels| levels.parse().ok());
+                let the_orderbooks = &self.instruments[symbol].orderbooks;
+                json_response(200, &the_orderbooks.depth_snapshot(max_levels.unwrap_or(usize::MAX)))
+            }
+            ("GET", ["trades"]) => {
+                let limit = query_param(query, "limit").and_then(|limit| limit.parse().ok());
+                let recent_trades = &self.instruments[symbol].recent_trades;
+                let skipped = recent_trades
+                    .len()
+                    .saturating_sub(limit.unwrap_or(DEFAULT_TRADES_LIMIT));
+                let trades: Vec<&Trade> = recent_trades.iter().skip(skipped).collect();
+                json_response(200, &trades)
+            }
+            ("POST", ["orders"]) => match serde_json::from_str::<OrderMessage>(body) {
+                Ok(order_message) => self.execute(symbol, &order_message, 201),
+                Err(error) => error_response(400, &format!("Malformed order: {}", error)),
+            },
+            ("DELETE", ["orders"]) => {
+                let filter = if body.trim().is_empty() {
+                    Ok(MassCancel::all())
+                } else {
+                    serde_json::from_str::<MassCancel>(body)
+                };
+
+                match filter {
+                    Ok(filter) => {
+                        let request_id = OrderId::new_v4();
+                        let instrument = self.instruments.get_mut(symbol).unwrap();
+                        let order_result = OrderResult {
+                            order_id: request_id,
+                            events: instrument.orderbooks.mass_cancel(request_id, &filter),
+                        };
+                        json_response(200, &order_result)
+                    }
+                    Err(error) => error_response(400, &format!("Malformed mass cancel: {}", error)),
+                }
+            }
+            ("GET", ["orders", order_id]) => match order_id.parse::<OrderId>() {
+                Ok(order_id) => match self.instruments[symbol].orderbooks.lookup(&order_id) {
+                    Some((side, price, order)) => {
+                        let order_status = OrderStatus {
+                            id: order.id,
+                            side,
+                            price,
+                            remaining_volume: order.remaining_volume,
+                        };
+                        json_response(200, &order_status)
+                    }
+                    None => error_response(404, &OrderRejection::UnknownOrder.to_string()),
+                },
+                Err(_) => error_response(400, "Malformed order id"),
+            },
+            ("DELETE", ["orders", order_id]) => match order_id.parse::<OrderId>() {
+                Ok(order_id) => {
+                    let cancel_message = OrderMessage {
+                        target_id: Some(order_id),
+                        r#type: OrderType::Cancel,
+                        ..Default::default()
+                    };
+                    self.execute(symbol, &cancel_message, 200)
+                }
+                Err(_) => error_response(400, "Malformed order id"),
+            },
+            ("GET", ["risk"]) => {
+                let account = query_param(query, "account").and_then(|account| account.parse().ok());
+                json_response(200, &self.risk_status(symbol, account))
+            }
+            ("POST", ["risk"]) => match serde_json::from_str::<RiskRequest>(body) {
+                Ok(risk_request) => {
+                    let risk = &mut self.instruments.get_mut(symbol).unwrap().risk;
+
+                    match risk_request.account {
+                        Some(account) => risk.set_limits(account, risk_request.limits),
+                        None => risk.set_default_limits(risk_request.limits),
+                    }
+
+                    json_response(200, &self.risk_status(symbol, risk_request.account))
+                }
+                Err(error) => error_response(400, &format!("Malformed risk limits: {}", error)),
+            },
+            ("POST", ["halt"]) => {
+                let result = self.instruments.get_mut(symbol).unwrap().orderbooks.halt();
+                self.session_result(symbol, result)
+            }
+            ("POST", ["resume"]) => {
+                let result = self.instruments.get_mut(symbol).unwrap().orderbooks.resume();
+                self.session_result(symbol, result)
+            }
+            ("POST", ["session"]) => match serde_json::from_str::<SessionRequest>(body) {
+                Ok(session_request) => {
+                    let result = self
+                        .instruments
+                        .get_mut(symbol)
+                        .unwrap()
+                        .orderbooks
+                        .transition(session_request.state);
+                    self.session_result(symbol, result)
+                }
+                Err(error) => error_response(400, &format!("Malformed session request: {}", error)),
+            },
+            (_, [])
+            | (_, ["depth"])
+            | (_, ["trades"])
+            | (_, ["orders"])
+            | (_, ["orders", _])
+            | (_, ["risk"])
+            | (_, ["halt"])
+            | (_, ["resume"])
+            | (_, ["session"]) => error_response(405, "Method not allowed"),
+            _ => error_response(404, "Not found"),
+        }
+    }
+
+    fn instrument_status(&self, symbol: &str) -> InstrumentStatus {
+        let the_orderbooks = &self.instruments[symbol].orderbooks;
+
+        InstrumentStatus {
+            symbol: symbol.to_string(),
+            halted: the_orderbooks.is_halted(),
+            session: the_orderbooks.session(),
+            ticker: the_orderbooks.ticker(),
+        }
+    }
+
+    fn session_result(
+        &mut self,
+        symbol: &str,
+        result: Result<HashMap<OrderId, Vec<OrderEvent>>, SessionTransitionError>,
+    ) -> (HttpStatus, String) {
+        match result {
+            Ok(events) => {
+                self.instruments.get_mut(symbol).unwrap().record_trades();
+                let session_result = SessionResult {
+                    status: self.instrument_status(symbol),
+                    events,
+                };
+                json_response(200, &session_result)
+            }
+            Err(error) => error_response(409, &error.to_string()),
+        }
+    }
+
+    fn risk_status(&mut self, symbol: &str, account: Option<AccountId>) -> RiskStatus {
+        let instrument = self.instruments.get_mut(symbol).unwrap();
+
+        RiskStatus {
+            account,
+            limits: instrument.risk.limits(account),
+            position: instrument.orderbooks.position(account),
+            open_orders: instrument.risk.open_orders(&instrument.orderbooks, account),
+        }
+    }
+
+    fn execute(&mut self, symbol: &str, order_message: &OrderMessage, status: HttpStatus) -> (HttpStatus, String) {
+        let instrument = self.instruments.get_mut(symbol).unwrap();
+
+        match instrument
+            .risk
+            .try_execute_order(&mut instrument.orderbooks, order_message)
+        {
+            Ok(events) => {
+                instrument.record_trades();
+                let order_result = OrderResult {
+                    order_id: order_message.id,
+                    events,
+                };
+                json_response(status, &order_result)
+            }
+            Err(rejection) => error_response(pre_trade_rejection_status(rejection), &rejection.to_string()),
+        }
+    }
+}
@@ -0,0 +1,391 @@
+use crate::orderbooks::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use uuid::Uuid;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[repr(u8)]
+pub enum RiskLimit {
+    MaxOrderSize = 0,
+    MaxNotional = 1,
+    MaxOpenOrders = 2,
+    MaxPosition = 3,
+    MaxMessageRate = 4,
+}
+
+// At most `count` messages in the last `window` nanoseconds, cancels included.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct MessageRate {
+    pub count: u64,
+    pub window: UTCNanoSeconds,
+}
+
+// Every limit left out is not checked.
+#[derive(Copy, Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RiskLimits {
+    pub max_order_size: Option<OrderVolume>,
+    pub max_notional: Option<OrderQuote>,
+    pub max_open_orders: Option<u64>,
+    pub max_position: Option<u64>,
+    pub max_messages: Option<MessageRate>,
+}
+
+// The limit an order breached, its value and the value the order ran into:
+// the order's size or notional, the open orders or messages so far, or the
+// position it would reach.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct RiskRejection {
+    pub limit: RiskLimit,
+    pub limit_value: u64,
+    pub current_value: u64,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PreTradeRejection {
+    Risk(RiskRejection),
+    Order(OrderRejection),
+}
+
+// Pre-trade checks for one instrument, run before `Orderbooks::try_execute_order`.
+// Orders are grouped by their `account`, orders without one share a group.
+// Time comes from the book's clock, so a manual clock makes every check
+// reproducible. Positions come from the book, which counts every trade.
+#[derive(Clone, Debug, Default)]
+pub struct RiskEngine {
+    pub(crate) default_limits: RiskLimits,
+    pub(crate) account_limits: HashMap<AccountId, RiskLimits>,
+    pub(crate) open_orders: HashMap<Option<AccountId>, HashSet<Uuid>>,
+    pub(crate) messages: HashMap<Option<AccountId>, VecDeque<UTCNanoSeconds>>,
+}
+
+impl fmt::Display for RiskLimit {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            RiskLimit::MaxOrderSize => "Max order size",
+            RiskLimit::MaxNotional => "Max notional",
+            RiskLimit::MaxOpenOrders => "Max open orders",
+            RiskLimit::MaxPosition => "Max position",
+            RiskLimit::MaxMessageRate => "Max message rate",
+        };
+
+        write!(formatter, "{}", name)
+    }
+}
+
+impl fmt::Display for RiskRejection {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "{} of {} breached at {}",
+            self.limit, self.limit_value, self.current_value
+        )
+    }
+}
+
+impl fmt::Display for PreTradeRejection {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PreTradeRejection::Risk(rejection) => rejection.fmt(formatter),
+            PreTradeRejection::Order(rejection) => rejection.fmt(formatter),
+        }
+    }
+}
+
+impl From<RiskRejection> for PreTradeRejection {
+    fn from(rejection: RiskRejection) -> PreTradeRejection {
+        PreTradeRejection::Risk(rejection)
+    }
+}
+
+impl From<OrderRejection> for PreTradeRejection {
+    fn from(rejection: OrderRejection) -> PreTradeRejection {
+        PreTradeRejection::Order(rejection)
+    }
+}
+
+fn breach(limit: RiskLimit, limit_value: Option<u64>, current_value: u64) -> Result<(), RiskRejection> {
+    match limit_value {
+        Some(limit_value) if current_value > limit_value => Err(RiskRejection {
+            limit,
+            limit_value,
+            current_value,
+        }),
+        _ => Ok(()),
+    }
+}
+
+impl RiskEngine {
+    pub fn new(default_limits: RiskLimits) -> RiskEngine {
+        RiskEngine {
+            default_limits,
+            ..Default::default()
+        }
+    }
+
+    // Applies to every account without limits of its own.
+    pub fn set_default_limits(&mut self, limits: RiskLimits) {
+        self.default_limits = limits;
+    }
+
+    pub fn set_limits(&mut self, account: AccountId, limits: RiskLimits) {
+        self.account_limits.insert(account, limits);
+    }
+
+    pub fn limits(&self, account: Option<AccountId>) -> RiskLimits {
+        account
+            .and_then(|account| self.account_limits.get(&account))
+            .copied()
+            .unwrap_or(self.default_limits)
+    }
+
+    pub fn open_orders(&mut self, the_orderbooks: &Orderbooks, account: Option<AccountId>) -> usize {
+        self.forget_closed(the_orderbooks, account);
+        self.open_orders.get(&account).map_or(0, HashSet::len)
+    }
+
+    // The message counts towards the rate even if a later check rejects it.
+    pub fn check(&mut self, the_orderbooks: &Orderbooks, order_message: &OrderMessage) -> Result<(), RiskRejection> {
+        let account = order_message.account;
+        let limits = self.limits(account);
+
+        if let Some(max_messages) = limits.max_messages {
+            let now = the_orderbooks.now();
+            let messages = self.messages.entry(account).or_default();
+
+            while messages
+                .front()
+                .is_some_and(|sent_at| *sent_at <= now.saturating_sub(max_messages.window))
+            {
+                messages.pop_front();
+            }
+
+            breach(
+                RiskLimit::MaxMessageRate,
+                Some(max_messages.count),
+                messages.len() as u64 + 1,
+            )?;
+            messages.push_back(now);
+        }
+
+        if order_message.r#type == OrderType::Cancel {
+            return Ok(());
+        }
+
+        let volume = order_message.volume.unwrap_or(0);
+        // Market orders are valued at what they would trade for now, bids no
+        // higher than their max quote.
+        let notional = match order_message.r#type {
+            OrderType::Market => the_orderbooks
+                .sweep_quote(order_message.side, volume)
+                .min(order_message.max_quote.unwrap_or(OrderQuote::MAX)),
+            _ => volume.saturating_mul(order_message.price.unwrap_or(0)),
+        };
+
+        breach(RiskLimit::MaxOrderSize, limits.max_order_size, volume)?;
+        breach(RiskLimit::MaxNotional, limits.max_notional, notional)?;
+
+        if order_message.r#type == OrderType::Limit {
+            let open_orders = self.open_orders(the_orderbooks, account) as u64;
+            breach(RiskLimit::MaxOpenOrders, limits.max_open_orders, open_orders + 1)?;
+        }
+
+        let position = match order_message.side {
+            OrderSide::Bid => the_orderbooks.position(account).saturating_add(volume as Position),
+            _ => the_orderbooks.position(account).saturating_sub(volume as Position),
+        };
+
+        breach(RiskLimit::MaxPosition, limits.max_position, position.unsigned_abs())
+    }
+
+    // Books the order, if it rests.
+    pub fn record(&mut self, the_orderbooks: &Orderbooks, order_message: &OrderMessage) {
+        if the_orderbooks.contains(&order_message.id) {
+            self.open_orders
+                .entry(order_message.account)
+                .or_default()
+                .insert(order_message.id);
+        }
+
+        self.forget_closed(the_orderbooks, order_message.account);
+    }
+
+    pub fn try_execute_order(
+        &mut self,
+        the_orderbooks: &mut Orderbooks,
+        order_message: &OrderMessage,
+    ) -> Result<HashMap<Uuid, Vec<OrderEvent>>, PreTradeRejection> {
+        self.check(the_orderbooks, order_message)?;
+        let order_events = the_orderbooks.try_execute_order(order_message)?;
+        self.record(the_orderbooks, order_message);
+
+        Ok(order_events)
+    }
+
+    // Orders also leave the book through expiry, mass cancels and auctions,
+    // so open orders are checked against the book before they are counted.
+    fn forget_closed(&mut self, the_orderbooks: &Orderbooks, account: Option<AccountId>) {
+        if let Some(open_orders) = self.open_orders.get_mut(&account) {
+            open_orders.retain(|order_id| the_orderbooks.contains(order_id));
+        }
+    }
+}
+
+#[cfg(test)]
+mod unit_test {
+    use super::*;
+    use crate::orderbooks::test_support::*;
+
+    fn order(side: OrderSide, volume: OrderVolume, price: OrderPrice, account: AccountId) -> OrderMessage {
+        OrderMessage {
+            account: Some(account),
+            ..limit_order(side, volume, price)
+        }
+    }
+
+    fn rejected(limit: RiskLimit, limit_value: u64, current_value: u64) -> Result<(), RiskRejection> {
+        Err(RiskRejection {
+            limit,
+            limit_value,
+            current_value,
+        })
+    }
+
+    #[test]
+    fn test_risk_limits() {
+        let mut the_orderbooks = Orderbooks::default();
+        the_orderbooks.set_clock(EngineClock::Manual(1_000));
+        let mut risk = RiskEngine::new(RiskLimits {
+            max_order_size: Some(10),
+            max_notional: Some(1_000),
+            max_open_orders: Some(2),
+            max_position: Some(15),
+            max_messages: Some(MessageRate { count: 4, window: 100 }),
+        });
+
+        assert_eq!(
+            risk.check(&the_orderbooks, &order(OrderSide::Bid, 11, 10, 1)),
+            rejected(RiskLimit::MaxOrderSize, 10, 11)
+        );
+        assert_eq!(
+            risk.check(&the_orderbooks, &order(OrderSide::Bid, 10, 101, 1)),
+            rejected(RiskLimit::MaxNotional, 1_000, 1_010)
+        );
+
+        for _ in 0..2 {
+            risk.try_execute_order(&mut the_orderbooks, &order(OrderSide::Ask, 10, 90, 1))
+                .unwrap();
+        }
+
+        assert_eq!(
+            risk.check(&the_orderbooks, &order(OrderSide::Ask, 1, 90, 1)),
+            rejected(RiskLimit::MaxMessageRate, 4, 5)
+        );
+
+        the_orderbooks.set_clock(EngineClock::Manual(1_100));
+
+        assert_eq!(
+            risk.try_execute_order(&mut the_orderbooks, &order(OrderSide::Ask, 1, 90, 1))
+                .unwrap_err(),
+            PreTradeRejection::Risk(RiskRejection {
+                limit: RiskLimit::MaxOpenOrders,
+                limit_value: 2,
+                current_value: 3,
+            })
+        );
+
+        risk.try_execute_order(&mut the_orderbooks, &order(OrderSide::Bid, 10, 90, 2))
+            .unwrap();
+
+        assert_eq!(the_orderbooks.position(Some(1)), -10);
+        assert_eq!(the_orderbooks.position(Some(2)), 10);
+        assert_eq!(risk.open_orders(&the_orderbooks, Some(1)), 1);
+        assert_eq!(
+            risk.check(&the_orderbooks, &order(OrderSide::Bid, 6, 90, 2)),
+            rejected(RiskLimit::MaxPosition, 15, 16)
+        );
+
+        risk.set_limits(2, RiskLimits::default());
+
+        assert!(risk.check(&the_orderbooks, &order(OrderSide::Bid, 6, 90, 2)).is_ok());
+        assert_eq!(
+            risk.try_execute_order(&mut the_orderbooks, &order(OrderSide::Bid, 0, 90, 2))
+                .unwrap_err(),
+            PreTradeRejection::Order(OrderRejection::InvalidVolume)
+        );
+    }
+
+    #[test]
+    fn test_market_order_notional() {
+        let mut the_orderbooks = Orderbooks::default();
+        let mut risk = RiskEngine::new(RiskLimits {
+            max_notional: Some(1_000),
+            ..RiskLimits::default()
+        });
+
+        risk.try_execute_order(&mut the_orderbooks, &order(OrderSide::Ask, 5, 100, 1))
+            .unwrap();
+        risk.try_execute_order(&mut the_orderbooks, &order(OrderSide::Ask, 5, 110, 1))
+            .unwrap();
+
+        let market_bid = |max_quote| OrderMessage {
+            account: Some(2),
+            max_quote: Some(max_quote),
+            ..market_order(OrderSide::Bid, 10)
+        };
+
+        assert_eq!(
+            risk.check(&the_orderbooks, &market_bid(OrderQuote::MAX)),
+            rejected(RiskLimit::MaxNotional, 1_000, 1_050)
+        );
+        assert!(risk.check(&the_orderbooks, &market_bid(1_000)).is_ok());
+    }
+
+    #[test]
+    fn test_auction_trades_count_towards_position() {
+        let mut the_orderbooks = Orderbooks::default();
+        let mut risk = RiskEngine::new(RiskLimits {
+            max_position: Some(10),
+            ..RiskLimits::default()
+        });
+
+        the_orderbooks.start_auction(None);
+        risk.try_execute_order(&mut the_orderbooks, &order(OrderSide::Ask, 10, 100, 1))
+            .unwrap();
+        risk.try_execute_order(&mut the_orderbooks, &order(OrderSide::Bid, 10, 100, 2))
+            .unwrap();
+        the_orderbooks.uncross();
+
+        assert_eq!(the_orderbooks.position(Some(1)), -10);
+        assert_eq!(the_orderbooks.position(Some(2)), 10);
+        assert_eq!(
+            risk.check(&the_orderbooks, &order(OrderSide::Bid, 1, 100, 2)),
+            rejected(RiskLimit::MaxPosition, 10, 11)
+        );
+    }
+
+    #[test]
+    fn test_message_rate_with_default_limits() {
+        let mut the_orderbooks = Orderbooks::default();
+        the_orderbooks.set_clock(EngineClock::Manual(1_000));
+        let mut risk = RiskEngine::new(RiskLimits {
+            max_messages: Some(MessageRate { count: 2, window: 100 }),
+            ..RiskLimits::default()
+        });
+
+        for _ in 0..2 {
+            risk.check(&the_orderbooks, &order(OrderSide::Bid, 1, 90, 1)).unwrap();
+        }
+
+        assert_eq!(
+            risk.check(&the_orderbooks, &order(OrderSide::Bid, 1, 90, 1)),
+            rejected(RiskLimit::MaxMessageRate, 2, 3)
+        );
+
+        the_orderbooks.set_clock(EngineClock::Manual(1_100));
+
+        assert!(risk.check(&the_orderbooks, &order(OrderSide::Bid, 1, 90, 1)).is_ok());
+    }
+}
@@ -0,0 +1,150 @@
+use crate::orderbooks::*;
+
+pub const WORKLOAD_USAGE: &str = "\
+Usage: bench [OPTIONS]
+
+  --orders N               orders to execute (default 1000000)
+  --mix L:M:C              limit, market and cancel ratio (default 70:20:10)
+  --distribution NAME      uniform or normal prices around the mid (default uniform)
+  --mid PRICE              mid price (default 100000)
+  --range TICKS            largest distance from the mid (default 1000)
+  --depth LEVELS           levels resting on each side before the run (default 1000)
+  --volume MIN:MAX         order volume range (default 1:1000)
+  --seed N                 random seed (default 42)";
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PriceDistribution {
+    Uniform,
+    Normal,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WorkloadConfig {
+    pub orders: usize,
+    pub limit_ratio: u32,
+    pub market_ratio: u32,
+    pub cancel_ratio: u32,
+    pub distribution: PriceDistribution,
+    pub mid_price: OrderPrice,
+    pub price_range: OrderPrice,
+    pub depth: usize,
+    pub min_volume: OrderVolume,
+    pub max_volume: OrderVolume,
+    pub seed: u64,
+}
+
+impl Default for WorkloadConfig {
+    fn default() -> WorkloadConfig {
+        WorkloadConfig {
+            orders: 1_000_000,
+            limit_ratio: 70,
+            market_ratio: 20,
+            cancel_ratio: 10,
+            distribution: PriceDistribution::Uniform,
+            mid_price: 100_000,
+            price_range: 1_000,
+            depth: 1_000,
+            min_volume: 1,
+            max_volume: 1_000,
+            seed: 42,
+        }
+    }
+}
+
+fn parse_value<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("Invalid {}: {}", name, value))
+}
+
+impl WorkloadConfig {
+    pub fn from_args<I: Iterator<Item = String>>(mut args: I) -> Result<WorkloadConfig, String> {
+        let mut config = WorkloadConfig::default();
+
+        while let Some(arg) = args.next() {
+            let value = args.next().ok_or_else(|| format!("Missing value for {}", arg))?;
+
+            match arg.as_str() {
+                "--orders" => config.orders = parse_value(&arg, &value)?,
+                "--mix" => match value.split(':').collect::<Vec<_>>().as_slice() {
+                    [limit, market, cancel] => {
+                        config.limit_ratio = parse_value(&arg, limit)?;
+                        config.market_ratio = parse_value(&arg, market)?;
+                        config.cancel_ratio = parse_value(&arg, cancel)?;
+                    }
+                    _ => return Err(format!("Invalid {}: {}", arg, value)),
+                },
+                "--distribution" => {
+                    config.distribution = match value.as_str() {
+                        "uniform" => PriceDistribution::Uniform,
+                        "normal" => PriceDistribution::Normal,
+                        _ => return Err(format!("Invalid {}: {}", arg, value)),
+                    }
+                }
+                "--mid" => config.mid_price = parse_value(&arg, &value)?,
+                "--range" => config.price_range = parse_value(&arg, &value)?,
+                "--depth" => config.depth = parse_value(&arg, &value)?,
+                "--volume" => match value.split(':').collect::<Vec<_>>().as_slice() {
+                    [min_volume, max_volume] => {
+                        config.min_volume = parse_value(&arg, min_volume)?;
+                        config.max_volume = parse_value(&arg, max_volume)?;
+                    }
+                    _ => return Err(format!("Invalid {}: {}", arg, value)),
+                },
+                "--seed" => config.seed = parse_value(&arg, &value)?,
+                _ => return Err(format!("Unknown option: {}", arg)),
+            }
+        }
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.limit_ratio + self.market_ratio + self.cancel_ratio == 0 {
+            return Err("The order mix needs at least one non-zero ratio".to_string());
+        }
+
+        if self.min_volume == 0 || self.min_volume > self.max_volume {
+            return Err("The volume range must be positive and ordered".to_string());
+        }
+
+        // Every generated and prefilled price has to stay positive.
+        if self.mid_price <= self.price_range.max(self.depth as OrderPrice) {
+            return Err("The mid price must be larger than the range and depth".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod unit_test {
+    use super::*;
+
+    fn args(line: &str) -> impl Iterator<Item = String> + '_ {
+        line.split_whitespace().map(|arg| arg.to_string())
+    }
+
+    #[test]
+    fn test_workload_config_from_args() {
+        assert_eq!(WorkloadConfig::from_args(args("")), Ok(WorkloadConfig::default()));
+
+        let config = WorkloadConfig::from_args(args(
+            "--orders 10 --mix 1:0:3 --distribution normal --volume 5:5 --seed 7",
+        ))
+        .unwrap();
+
+        assert_eq!(config.orders, 10);
+        assert_eq!(
+            (config.limit_ratio, config.market_ratio, config.cancel_ratio),
+            (1, 0, 3)
+        );
+        assert_eq!(config.distribution, PriceDistribution::Normal);
+        assert_eq!((config.min_volume, config.max_volume), (5, 5));
+        assert_eq!(config.seed, 7);
+        assert!(WorkloadConfig::from_args(args("--mix 0:0:0")).is_err());
+        assert!(WorkloadConfig::from_args(args("--volume 9:1")).is_err());
+        assert!(WorkloadConfig::from_args(args("--mid 10 --range 10")).is_err());
+        assert!(WorkloadConfig::from_args(args("--orders")).is_err());
+        assert!(WorkloadConfig::from_args(args("--speed 1")).is_err());
+    }
+}
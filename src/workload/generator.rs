@@ -0,0 +1,180 @@
+use super::config::*;
+use crate::orderbooks::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::f64::consts::PI;
+use uuid::Uuid;
+
+// Generates a reproducible order flow for a seed. Cancels target orders the
+// generator entered earlier that still rest in the book.
+#[derive(Clone, Debug)]
+pub struct WorkloadGenerator {
+    pub config: WorkloadConfig,
+    rng: StdRng,
+    entered_ids: Vec<OrderId>,
+}
+
+impl WorkloadGenerator {
+    pub fn new(config: WorkloadConfig) -> WorkloadGenerator {
+        WorkloadGenerator {
+            rng: StdRng::seed_from_u64(config.seed),
+            config,
+            entered_ids: Vec::new(),
+        }
+    }
+
+    // One order per level on each side, the best levels one tick off the mid.
+    pub fn prefill(&mut self) -> Vec<OrderMessage> {
+        let mut orders = Vec::with_capacity(self.config.depth * 2);
+
+        for level in 1..=self.config.depth as OrderPrice {
+            for (side, price) in [
+                (OrderSide::Bid, self.config.mid_price - level),
+                (OrderSide::Ask, self.config.mid_price + level),
+            ]
+            .iter()
+            {
+                let order = self.limit_order(*side, *price);
+                orders.push(order);
+            }
+        }
+
+        orders
+    }
+
+    pub fn next_order(&mut self, the_orderbooks: &Orderbooks) -> OrderMessage {
+        let config = &self.config;
+        let roll = self
+            .rng
+            .gen_range(0..config.limit_ratio + config.market_ratio + config.cancel_ratio);
+        let side = if self.rng.gen() { OrderSide::Bid } else { OrderSide::Ask };
+
+        if roll >= config.limit_ratio + config.market_ratio {
+            if let Some(target_id) = self.cancel_target(the_orderbooks) {
+                return OrderMessage {
+                    id: self.order_id(),
+                    target_id: Some(target_id),
+                    r#type: OrderType::Cancel,
+                    ..Default::default()
+                };
+            }
+        } else if roll >= config.limit_ratio {
+            return OrderMessage {
+                id: self.order_id(),
+                side,
+                r#type: OrderType::Market,
+                volume: Some(self.volume()),
+                max_quote: Some(OrderQuote::MAX).filter(|_| side == OrderSide::Bid),
+                ..Default::default()
+            };
+        }
+
+        // Cancels fall back to limit orders while nothing rests.
+        let price = self.price();
+        self.limit_order(side, price)
+    }
+
+    // Ids come from the seeded generator too, so cancels pick the same targets on every run.
+    fn order_id(&mut self) -> OrderId {
+        Uuid::from_u128(self.rng.gen())
+    }
+
+    fn limit_order(&mut self, side: OrderSide, price: OrderPrice) -> OrderMessage {
+        let order = OrderMessage {
+            id: self.order_id(),
+            side,
+            r#type: OrderType::Limit,
+            volume: Some(self.volume()),
+            price: Some(price),
+            ..Default::default()
+        };
+        self.entered_ids.push(order.id);
+        order
+    }
+
+    // Filled orders are only dropped from `entered_ids` once they are drawn.
+    fn cancel_target(&mut self, the_orderbooks: &Orderbooks) -> Option<OrderId> {
+        while !self.entered_ids.is_empty() {
+            let index = self.rng.gen_range(0..self.entered_ids.len());
+            let order_id = self.entered_ids.swap_remove(index);
+
+            if the_orderbooks.lookup(&order_id).is_some() {
+                return Some(order_id);
+            }
+        }
+
+        None
+    }
+
+    fn volume(&mut self) -> OrderVolume {
+        self.rng.gen_range(self.config.min_volume..=self.config.max_volume)
+    }
+
+    // The normal distribution puts three standard deviations at the range and
+    // clamps the rest to it.
+    fn price(&mut self) -> OrderPrice {
+        let range = self.config.price_range as f64;
+        let offset = match self.config.distribution {
+            PriceDistribution::Uniform => self.rng.gen_range(-range..=range),
+            PriceDistribution::Normal => {
+                let (u1, u2): (f64, f64) = (1.0 - self.rng.gen::<f64>(), self.rng.gen());
+                let standard_normal = (-2.0 * u1.ln()).sqrt() * (2.0 * PI * u2).cos();
+                (standard_normal * range / 3.0).max(-range).min(range)
+            }
+        };
+
+        (self.config.mid_price as f64 + offset.round()).max(1.0) as OrderPrice
+    }
+}
+
+#[cfg(test)]
+mod unit_test {
+    use super::*;
+
+    #[test]
+    fn test_generator_is_reproducible_and_follows_the_mix() {
+        let config = WorkloadConfig {
+            depth: 10,
+            distribution: PriceDistribution::Normal,
+            ..Default::default()
+        };
+        let mut first = WorkloadGenerator::new(config.clone());
+        let mut second = WorkloadGenerator::new(config.clone());
+        let mut first_orderbooks = Orderbooks::default();
+        let mut second_orderbooks = Orderbooks::default();
+
+        for (order, other) in first.prefill().iter().zip(second.prefill().iter()) {
+            first_orderbooks.execute_order(order);
+            second_orderbooks.execute_order(other);
+        }
+
+        assert_eq!(first_orderbooks.count(), (10, 10, 20));
+
+        let mut type_counts = [0; 4];
+
+        for _ in 0..10_000 {
+            let order = first.next_order(&first_orderbooks);
+            let other = second.next_order(&second_orderbooks);
+
+            assert_eq!((order.id, order.target_id), (other.id, other.target_id));
+            assert_eq!(
+                (order.side as u8, order.r#type as u8),
+                (other.side as u8, other.r#type as u8)
+            );
+            assert_eq!((order.volume, order.price), (other.volume, other.price));
+            assert!(order
+                .price
+                .is_none_or(|price| price.abs_diff(config.mid_price) <= config.price_range));
+            assert!(first_orderbooks.validate(&order).is_ok());
+
+            type_counts[order.r#type as usize] += 1;
+            first_orderbooks.execute_order(&order);
+            second_orderbooks.execute_order(&other);
+        }
+
+        // Limit, market and cancel roughly follow 70:20:10.
+        assert!(type_counts[OrderType::Limit as usize] > 6_500);
+        assert!((1_800..2_200).contains(&type_counts[OrderType::Market as usize]));
+        assert!(type_counts[OrderType::Cancel as usize] > 800);
+    }
+}
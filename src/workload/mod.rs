@@ -0,0 +1,5 @@
+mod config;
+mod generator;
+
+pub use config::*;
+pub use generator::*;
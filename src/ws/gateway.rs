@@ -0,0 +1,261 @@
+use super::protocol::*;
+use crate::orderbooks::*;
+use crate::risk::*;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::mpsc::Sender;
+
+pub type ClientId = u64;
+
+// Every order goes through the instrument's risk engine before the book.
+#[derive(Debug, Default)]
+pub struct WsGateway {
+    pub instruments: HashMap<String, Orderbooks>,
+    pub risk_engines: HashMap<String, RiskEngine>,
+    pub owners: HashMap<OrderId, ClientId>,
+    clients: HashMap<ClientId, Sender<String>>,
+    depth_subscribers: HashMap<String, BTreeSet<ClientId>>,
+    trade_subscribers: HashMap<String, BTreeSet<ClientId>>,
+    last_client_id: ClientId,
+}
+
+impl WsGateway {
+    pub fn new(symbols: &[String]) -> WsGateway {
+        let mut instruments = HashMap::new();
+
+        for symbol in symbols {
+            let mut the_orderbooks = Orderbooks::default();
+            the_orderbooks.attach_depth_publisher();
+            instruments.insert(symbol.clone(), the_orderbooks);
+        }
+
+        WsGateway {
+            instruments,
+            risk_engines: symbols
+                .iter()
+                .map(|symbol| (symbol.clone(), RiskEngine::default()))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    pub fn connect(&mut self, outbox: Sender<String>) -> ClientId {
+        self.last_client_id += 1;
+        self.clients.insert(self.last_client_id, outbox);
+        self.last_client_id
+    }
+
+    // Each connection is its own engine session, so its resting orders are
+    // cancelled unless it opted out. Nobody else can cancel them afterwards.
+    pub fn disconnect(&mut self, client_id: ClientId) {
+        self.clients.remove(&client_id);
+
+        for subscribers in self
+            .depth_subscribers
+            .values_mut()
+            .chain(self.trade_subscribers.values_mut())
+        {
+            subscribers.remove(&client_id);
+        }
+
+        let mut symbols: Vec<String> = self.instruments.keys().cloned().collect();
+        symbols.sort();
+
+        for symbol in symbols {
+            let the_orderbooks = self.instruments.get_mut(&symbol).unwrap();
+
+            for order_id in the_orderbooks.disconnect_session(client_id).keys() {
+                self.owners.remove(order_id);
+            }
+
+            let depth_updates = the_orderbooks.drain_depth_updates();
+
+            if !depth_updates.is_empty() {
+                let response = WsResponse::DepthUpdates {
+                    symbol: symbol.clone(),
+                    updates: depth_updates,
+                };
+                self.broadcast(self.depth_subscribers.get(&symbol), &response);
+            }
+        }
+    }
+
+    fn send(&self, client_id: ClientId, response: &WsResponse) {
+        if let Some(outbox) = self.clients.get(&client_id) {
+            let _ = outbox.send(serde_json::to_string(response).unwrap());
+        }
+    }
+
+    fn broadcast(&self, subscribers: Option<&BTreeSet<ClientId>>, response: &WsResponse) {
+        for client_id in subscribers.into_iter().flatten() {
+            self.send(*client_id, response);
+        }
+    }
+
+    fn reject(&self, client_id: ClientId, request_id: Option<RequestId>, reason: &str) {
+        let response = WsResponse::Rejected {
+            request_id,
+            reason: reason.to_string(),
+        };
+        self.send(client_id, &response);
+    }
+
+    pub fn handle(&mut self, client_id: ClientId, text: &str) {
+        let request = match serde_json::from_str::<WsRequest>(text) {
+            Ok(request) => request,
+            Err(error) => return self.reject(client_id, None, &format!("Malformed request: {}", error)),
+        };
+        let request_id = request.request_id();
+
+        match request {
+            WsRequest::Submit { symbol, order, .. } => self.submit(client_id, request_id, symbol, order),
+            WsRequest::Cancel { symbol, order_id, .. } => {
+                let cancel_order = OrderMessage {
+                    target_id: Some(order_id),
+                    r#type: OrderType::Cancel,
+                    ..Default::default()
+                };
+                self.submit(client_id, request_id, symbol, cancel_order);
+            }
+            WsRequest::Subscribe { symbol, channel, .. } => self.subscribe(client_id, request_id, symbol, channel),
+            WsRequest::CancelOnDisconnect { enabled, .. } => {
+                for the_orderbooks in self.instruments.values_mut() {
+                    the_orderbooks.set_cancel_on_disconnect(client_id, enabled);
+                }
+
+                self.send(client_id, &WsResponse::CancelOnDisconnect { request_id, enabled });
+            }
+            WsRequest::Unsubscribe { symbol, channel, .. } => {
+                let subscribers = match channel {
+                    WsChannel::Depth => self.depth_subscribers.get_mut(&symbol),
+                    WsChannel::Trades => self.trade_subscribers.get_mut(&symbol),
+                };
+
+                if let Some(subscribers) = subscribers {
+                    subscribers.remove(&client_id);
+                }
+
+                let response = WsResponse::Unsubscribed {
+                    request_id,
+                    symbol,
+                    channel,
+                };
+                self.send(client_id, &response);
+            }
+        }
+    }
+
+    fn subscribe(&mut self, client_id: ClientId, request_id: Option<RequestId>, symbol: String, channel: WsChannel) {
+        let the_orderbooks = match self.instruments.get(&symbol) {
+            Some(the_orderbooks) => the_orderbooks,
+            None => return self.reject(client_id, request_id, "Unknown symbol"),
+        };
+        let snapshot = the_orderbooks.depth_snapshot(usize::MAX);
+        let subscribers = match channel {
+            WsChannel::Depth => &mut self.depth_subscribers,
+            WsChannel::Trades => &mut self.trade_subscribers,
+        };
+        subscribers.entry(symbol.clone()).or_default().insert(client_id);
+        let response = WsResponse::Subscribed {
+            request_id,
+            symbol: symbol.clone(),
+            channel,
+        };
+        self.send(client_id, &response);
+
+        // The snapshot carries the depth sequence, later updates continue from it.
+        if channel == WsChannel::Depth {
+            self.send(client_id, &WsResponse::DepthSnapshot { symbol, snapshot });
+        }
+    }
+
+    fn validate(&self, client_id: ClientId, symbol: &str, order: &OrderMessage) -> Result<(), String> {
+        let the_orderbooks = self.instruments.get(symbol).ok_or("Unknown symbol")?;
+        let foreign_target = order
+            .target_id
+            .or_else(|| {
+                let client_order_id = order.target_client_order_id.as_deref()?;
+                the_orderbooks.lookup_client_order_id(order.account, client_order_id)
+            })
+            .filter(|target_id| self.owners.get(target_id) != Some(&client_id));
+
+        // Orders entered on other connections are reported as unknown.
+        if foreign_target.is_some() && order.r#type != OrderType::Limit && order.r#type != OrderType::Market {
+            return Err(OrderRejection::UnknownOrder.to_string());
+        }
+
+        if self.owners.contains_key(&order.id) {
+            return Err(OrderRejection::DuplicateOrderId.to_string());
+        }
+
+        Ok(())
+    }
+
+    fn submit(&mut self, client_id: ClientId, request_id: Option<RequestId>, symbol: String, mut order: OrderMessage) {
+        order.session_id = Some(client_id);
+
+        if order.r#type == OrderType::Market && order.side == OrderSide::Bid {
+            order.max_quote = order.max_quote.or(Some(OrderQuote::MAX));
+        }
+
+        if let Err(reason) = self.validate(client_id, &symbol, &order) {
+            return self.reject(client_id, request_id, &reason);
+        }
+
+        let the_orderbooks = self.instruments.get_mut(&symbol).unwrap();
+        let risk = self.risk_engines.get_mut(&symbol).unwrap();
+        let mut order_events: Vec<_> = match risk.try_execute_order(the_orderbooks, &order) {
+            Ok(order_events) => order_events.into_iter().collect(),
+            Err(rejection) => return self.reject(client_id, request_id, &rejection.to_string()),
+        };
+        let depth_updates = the_orderbooks.drain_depth_updates();
+        let trades = the_orderbooks.trades().to_vec();
+        let resting_ids: Vec<OrderId> = order_events
+            .iter()
+            .map(|(order_id, _)| *order_id)
+            .filter(|order_id| the_orderbooks.contains(order_id))
+            .collect();
+        order_events.sort_by_key(|(order_id, _)| *order_id != order.id);
+
+        if order.r#type != OrderType::Cancel {
+            self.owners.insert(order.id, client_id);
+        }
+
+        let response = WsResponse::Accepted {
+            request_id,
+            symbol: symbol.clone(),
+            order_id: order.id,
+        };
+        self.send(client_id, &response);
+
+        for (order_id, events) in order_events {
+            if let Some(owner_id) = self.owners.get(&order_id) {
+                let response = WsResponse::OrderEvents {
+                    symbol: symbol.clone(),
+                    order_id,
+                    events,
+                };
+                self.send(*owner_id, &response);
+            }
+
+            if !resting_ids.contains(&order_id) {
+                self.owners.remove(&order_id);
+            }
+        }
+
+        if !depth_updates.is_empty() {
+            let response = WsResponse::DepthUpdates {
+                symbol: symbol.clone(),
+                updates: depth_updates,
+            };
+            self.broadcast(self.depth_subscribers.get(&symbol), &response);
+        }
+
+        if !trades.is_empty() {
+            let response = WsResponse::Trades {
+                symbol: symbol.clone(),
+                trades,
+            };
+            self.broadcast(self.trade_subscribers.get(&symbol), &response);
+        }
+    }
+}
@@ -0,0 +1,7 @@
+mod gateway;
+mod protocol;
+mod server;
+
+pub use gateway::*;
+pub use protocol::*;
+pub use server::*;
@@ -0,0 +1,136 @@
+use crate::orderbooks::*;
+use serde::{Deserialize, Serialize};
+use std::mem;
+
+pub type RequestId = u64;
+
+#[derive(Copy, Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WsChannel {
+    Depth,
+    Trades,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum WsRequest {
+    Submit {
+        request_id: Option<RequestId>,
+        symbol: String,
+        order: OrderMessage,
+    },
+    Cancel {
+        request_id: Option<RequestId>,
+        symbol: String,
+        order_id: OrderId,
+    },
+    Subscribe {
+        request_id: Option<RequestId>,
+        symbol: String,
+        channel: WsChannel,
+    },
+    Unsubscribe {
+        request_id: Option<RequestId>,
+        symbol: String,
+        channel: WsChannel,
+    },
+    CancelOnDisconnect {
+        request_id: Option<RequestId>,
+        enabled: bool,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WsResponse {
+    Accepted {
+        request_id: Option<RequestId>,
+        symbol: String,
+        order_id: OrderId,
+    },
+    Rejected {
+        request_id: Option<RequestId>,
+        reason: String,
+    },
+    OrderEvents {
+        symbol: String,
+        order_id: OrderId,
+        events: Vec<OrderEvent>,
+    },
+    Subscribed {
+        request_id: Option<RequestId>,
+        symbol: String,
+        channel: WsChannel,
+    },
+    Unsubscribed {
+        request_id: Option<RequestId>,
+        symbol: String,
+        channel: WsChannel,
+    },
+    CancelOnDisconnect {
+        request_id: Option<RequestId>,
+        enabled: bool,
+    },
+    DepthSnapshot {
+        symbol: String,
+        snapshot: DepthSnapshot,
+    },
+    DepthUpdates {
+        symbol: String,
+        updates: Vec<DepthUpdate>,
+    },
+    Trades {
+        symbol: String,
+        trades: Vec<Trade>,
+    },
+}
+
+impl WsRequest {
+    pub fn request_id(&self) -> Option<RequestId> {
+        match self {
+            WsRequest::Submit { request_id, .. }
+            | WsRequest::Cancel { request_id, .. }
+            | WsRequest::Subscribe { request_id, .. }
+            | WsRequest::Unsubscribe { request_id, .. }
+            | WsRequest::CancelOnDisconnect { request_id, .. } => *request_id,
+        }
+    }
+}
+
+impl PartialEq for WsChannel {
+    fn eq(&self, other: &Self) -> bool {
+        mem::discriminant(self) == mem::discriminant(other)
+    }
+}
+
+impl Eq for WsChannel {}
+
+#[cfg(test)]
+mod unit_test {
+    use super::*;
+
+    #[test]
+    fn test_submit_request_from_json() {
+        let request: WsRequest = serde_json::from_str(
+            r#"{"op":"submit","request_id":7,"symbol":"BTC-IDR","order":{"side":"bid","type":"limit","volume":10,"price":95}}"#,
+        )
+        .unwrap();
+
+        match request {
+            WsRequest::Submit {
+                request_id,
+                symbol,
+                order,
+            } => {
+                assert_eq!(request_id, Some(7));
+                assert_eq!(symbol, "BTC-IDR");
+                assert_eq!(order.side, OrderSide::Bid);
+                assert_eq!(order.r#type, OrderType::Limit);
+                assert_eq!(order.volume, Some(10));
+                assert_eq!(order.price, Some(95));
+                assert_eq!(order.max_quote, None);
+            }
+            _ => panic!("Unexpected request: {:?}", request),
+        }
+    }
+}
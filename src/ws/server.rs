@@ -0,0 +1,97 @@
+use super::gateway::*;
+use std::io::{self, ErrorKind};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use tungstenite::{Error, HandshakeError, Message, WebSocket};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+pub struct WsServer {
+    listener: TcpListener,
+    gateway: Arc<Mutex<WsGateway>>,
+}
+
+impl WsServer {
+    pub fn bind<A: ToSocketAddrs>(addr: A, symbols: &[String]) -> io::Result<WsServer> {
+        Ok(WsServer {
+            listener: TcpListener::bind(addr)?,
+            gateway: Arc::new(Mutex::new(WsGateway::new(symbols))),
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    pub fn gateway(&self) -> Arc<Mutex<WsGateway>> {
+        self.gateway.clone()
+    }
+
+    pub fn run(&self) -> io::Result<()> {
+        for stream in self.listener.incoming() {
+            let stream = stream?;
+            let gateway = self.gateway.clone();
+
+            thread::spawn(move || {
+                let peer_addr = stream.peer_addr();
+
+                if let Err(error) = serve_client(stream, gateway) {
+                    eprintln!("WebSocket connection {:?} closed: {}", peer_addr, error);
+                }
+            });
+        }
+
+        Ok(())
+    }
+}
+
+fn io_error(error: Error) -> io::Error {
+    match error {
+        Error::Io(error) => error,
+        error => io::Error::other(error),
+    }
+}
+
+fn serve_client(stream: TcpStream, gateway: Arc<Mutex<WsGateway>>) -> io::Result<()> {
+    let mut websocket = tungstenite::accept(stream).map_err(|error| match error {
+        HandshakeError::Failure(error) => io_error(error),
+        HandshakeError::Interrupted(_) => ErrorKind::WouldBlock.into(),
+    })?;
+    websocket.get_ref().set_read_timeout(Some(POLL_INTERVAL))?;
+    let (outbox, inbox) = mpsc::channel();
+    let client_id = gateway.lock().unwrap().connect(outbox);
+    let result = poll_loop(&mut websocket, &gateway, client_id, &inbox);
+    gateway.lock().unwrap().disconnect(client_id);
+
+    match result {
+        Err(Error::ConnectionClosed) => Ok(()),
+        result => result.map_err(io_error),
+    }
+}
+
+#[allow(clippy::result_large_err)]
+fn poll_loop(
+    websocket: &mut WebSocket<TcpStream>,
+    gateway: &Mutex<WsGateway>,
+    client_id: ClientId,
+    inbox: &Receiver<String>,
+) -> Result<(), Error> {
+    loop {
+        match websocket.read() {
+            Ok(Message::Text(text)) => gateway.lock().unwrap().handle(client_id, &text),
+            Ok(_) => {}
+            Err(Error::Io(error)) if error.kind() == ErrorKind::WouldBlock || error.kind() == ErrorKind::TimedOut => {}
+            Err(error) => return Err(error),
+        }
+
+        while let Ok(text) = inbox.try_recv() {
+            websocket.write(Message::Text(text))?;
+        }
+
+        // Also flushes the pongs and close frames queued by `read`.
+        websocket.flush()?;
+    }
+}
@@ -0,0 +1,45 @@
+use std::env;
+use std::path::PathBuf;
+use std::process::Command;
+
+// The test binary lives in target/<profile>/deps, next to the static library's directory.
+fn profile_dir() -> PathBuf {
+    let mut dir = env::current_exe().unwrap();
+    dir.pop();
+
+    if dir.ends_with("deps") {
+        dir.pop();
+    }
+
+    dir
+}
+
+#[test]
+fn test_c_program_against_the_static_library() {
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let profile_dir = profile_dir();
+    let library = profile_dir.join("libcc_matching_engine.a");
+    let program = profile_dir.join("ffi_test");
+
+    assert!(library.exists(), "{} is missing", library.display());
+
+    let compiled = Command::new(env::var("CC").unwrap_or_else(|_| "cc".to_string()))
+        .arg("-std=c99")
+        .arg("-Wall")
+        .arg("-Werror")
+        .arg("-I")
+        .arg(manifest_dir.join("include"))
+        .arg(manifest_dir.join("tests/ffi/ffi_test.c"))
+        .arg(&library)
+        .args(["-lpthread", "-ldl", "-lm", "-o"])
+        .arg(&program)
+        .status()
+        .unwrap();
+
+    assert!(compiled.success());
+
+    let output = Command::new(&program).output().unwrap();
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "ok\n");
+}
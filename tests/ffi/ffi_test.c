@@ -0,0 +1,85 @@
+#include <stdio.h>
+#include <string.h>
+#include "cc_matching_engine.h"
+
+#define CHECK(condition)                                                   \
+    do {                                                                   \
+        if (!(condition)) {                                                \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__,         \
+                    __LINE__, #condition);                                 \
+            return 1;                                                      \
+        }                                                                  \
+    } while (0)
+
+typedef struct {
+    CcmeOrderEvent events[16];
+    size_t count;
+} Events;
+
+static void collect_event(const CcmeOrderEvent *event, void *user_data) {
+    Events *events = (Events *)user_data;
+
+    if (events->count < 16) {
+        events->events[events->count++] = *event;
+    }
+}
+
+int main(void) {
+    CcmeOrderbooks *book = ccme_orderbooks_new();
+    Events events = {0};
+    CcmeOrderMessage ask = {0};
+    CcmeOrderMessage bid = {0};
+    CcmeOrderMessage session_ask;
+    CcmeDepthLevel levels[4];
+    CcmeOrderId none = {0};
+
+    ask.struct_size = sizeof(ask);
+    ask.side = CCME_SIDE_ASK;
+    ask.order_type = CCME_TYPE_LIMIT;
+    ask.volume = 10;
+    ask.price = 100;
+    ask.has_account = true;
+    ask.account = 0;
+    bid = ask;
+    bid.side = CCME_SIDE_BID;
+    bid.volume = 4;
+
+    CHECK(book != NULL);
+    CHECK(ccme_orderbooks_submit(book, &ask, NULL, NULL) == CCME_OK);
+    CHECK(memcmp(&ask.id, &none, sizeof(none)) != 0);
+    CHECK(ccme_orderbooks_submit(book, &bid, collect_event, &events) == CCME_OK);
+    CHECK(events.count > 0);
+    CHECK(memcmp(&events.events[0].order_id, &bid.id, sizeof(bid.id)) == 0);
+    CHECK(events.events[0].event_type == CCME_EVENT_HAS_MATCH);
+    CHECK(events.events[0].traded_price == 100);
+    CHECK(memcmp(&events.events[0].crossed_id, &ask.id, sizeof(ask.id)) == 0);
+    CHECK(memcmp(&events.events[events.count - 1].order_id, &ask.id, sizeof(ask.id)) == 0);
+    CHECK(events.events[events.count - 1].remaining_volume == 6);
+
+    CHECK(ccme_orderbooks_depth(book, CCME_SIDE_ASK, levels, 4) == 1);
+    CHECK(levels[0].price == 100 && levels[0].volume == 6);
+    CHECK(ccme_orderbooks_depth(book, CCME_SIDE_BID, levels, 4) == 0);
+    CHECK(ccme_orderbooks_count(book, CCME_SIDE_ASK) == 1);
+    CHECK(ccme_orderbooks_count(book, CCME_SIDE_NONE) == 1);
+
+    CHECK(ccme_orderbooks_submit(book, &ask, NULL, NULL) == CCME_REJECTED_DUPLICATE_ORDER_ID);
+    session_ask = ask;
+    session_ask.id = none;
+    session_ask.price = 101;
+    session_ask.session_id = 5;
+    CHECK(ccme_orderbooks_submit(book, &session_ask, NULL, NULL) == CCME_OK);
+    CHECK(ccme_orderbooks_disconnect_session(book, 5, NULL, NULL) == 1);
+    CHECK(ccme_orderbooks_count(book, CCME_SIDE_ASK) == 1);
+    bid.id = none;
+    bid.volume = 0;
+    CHECK(ccme_orderbooks_submit(book, &bid, NULL, NULL) == CCME_REJECTED_INVALID_VOLUME);
+    bid.order_type = 42;
+    CHECK(ccme_orderbooks_submit(book, &bid, NULL, NULL) == CCME_ERROR_INVALID_ENUM);
+    CHECK(ccme_orderbooks_submit(NULL, &bid, NULL, NULL) == CCME_ERROR_NULL_POINTER);
+    bid.struct_size = 0;
+    CHECK(ccme_orderbooks_submit(book, &bid, NULL, NULL) == CCME_ERROR_INVALID_SIZE);
+
+    ccme_orderbooks_free(book);
+    printf("ok\n");
+    return 0;
+}
@@ -0,0 +1,344 @@
+use cc_matching_engine::fix::*;
+use cc_matching_engine::RiskLimits;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+struct FixInitiator {
+    stream: TcpStream,
+    comp_id: String,
+    next_seq_num: SeqNum,
+    buffer: Vec<u8>,
+}
+
+impl FixInitiator {
+    fn connect(addr: SocketAddr, comp_id: &str) -> FixInitiator {
+        let stream = TcpStream::connect(addr).unwrap();
+        stream.set_read_timeout(Some(Duration::from_millis(20))).unwrap();
+
+        FixInitiator {
+            stream,
+            comp_id: comp_id.to_string(),
+            next_seq_num: 1,
+            buffer: Vec::new(),
+        }
+    }
+
+    fn logon(addr: SocketAddr, comp_id: &str) -> FixInitiator {
+        let mut initiator = FixInitiator::connect(addr, comp_id);
+        let mut logon = FixMessage::new(tags::MSG_TYPE_LOGON);
+        logon.set(tags::ENCRYPT_METHOD, 0).set(tags::HEART_BT_INT, 30);
+        initiator.send(logon);
+
+        assert_eq!(initiator.receive().msg_type(), tags::MSG_TYPE_LOGON);
+
+        initiator
+    }
+
+    fn send_with_seq_num(&mut self, mut message: FixMessage, seq_num: SeqNum) {
+        let body = message.fields.split_off(1);
+        message
+            .set(tags::SENDER_COMP_ID, &self.comp_id)
+            .set(tags::TARGET_COMP_ID, "CCME")
+            .set(tags::MSG_SEQ_NUM, seq_num)
+            .set(tags::SENDING_TIME, sending_time());
+        message.fields.extend(body);
+        self.stream.write_all(&message.encode()).unwrap();
+    }
+
+    fn send(&mut self, message: FixMessage) {
+        let seq_num = self.next_seq_num;
+        self.next_seq_num += 1;
+        self.send_with_seq_num(message, seq_num);
+    }
+
+    fn try_receive(&mut self, timeout: Duration) -> Option<FixMessage> {
+        let deadline = Instant::now() + timeout;
+        let mut read_buffer = [0u8; 4096];
+
+        loop {
+            if let Some(message) = take_message(&mut self.buffer) {
+                return Some(message.unwrap());
+            }
+
+            if Instant::now() >= deadline {
+                return None;
+            }
+
+            match self.stream.read(&mut read_buffer) {
+                Ok(0) => return None,
+                Ok(read_length) => self.buffer.extend_from_slice(&read_buffer[..read_length]),
+                Err(error) if error.kind() == ErrorKind::WouldBlock || error.kind() == ErrorKind::TimedOut => {}
+                Err(error) => panic!("{}", error),
+            }
+        }
+    }
+
+    fn receive(&mut self) -> FixMessage {
+        self.try_receive(Duration::from_secs(5))
+            .expect("No message from the gateway")
+    }
+
+    fn new_order(&mut self, cl_ord_id: &str, side: &str, qty: u64, price: Option<u64>) {
+        let mut order = FixMessage::new(tags::MSG_TYPE_NEW_ORDER_SINGLE);
+        order
+            .set(tags::CL_ORD_ID, cl_ord_id)
+            .set(tags::SYMBOL, "BTC-IDR")
+            .set(tags::SIDE, side)
+            .set(tags::ORDER_QTY, qty)
+            .set(tags::ORD_TYPE, if price.is_some() { "2" } else { "1" });
+
+        if let Some(price) = price {
+            order.set(tags::PRICE, price);
+        }
+
+        self.send(order);
+    }
+}
+
+fn start_gateway() -> SocketAddr {
+    start_gateway_with_handle().0
+}
+
+fn start_gateway_with_handle() -> (SocketAddr, Arc<Mutex<FixGateway>>) {
+    let config = FixSessionConfig {
+        sender_comp_id: "CCME".to_string(),
+        max_heartbeat_interval: 60,
+    };
+    let acceptor = FixAcceptor::bind("127.0.0.1:0", config, &["BTC-IDR".to_string()]).unwrap();
+    let addr = acceptor.local_addr().unwrap();
+    let gateway = acceptor.gateway();
+    thread::spawn(move || acceptor.run());
+    (addr, gateway)
+}
+
+fn assert_report(report: &FixMessage, cl_ord_id: &str, exec_type: &str, ord_status: &str, leaves_qty: &str) {
+    assert_eq!(report.msg_type(), tags::MSG_TYPE_EXECUTION_REPORT, "{}", report);
+    assert_eq!(report.get(tags::CL_ORD_ID), Some(cl_ord_id), "{}", report);
+    assert_eq!(report.get(tags::EXEC_TYPE), Some(exec_type), "{}", report);
+    assert_eq!(report.get(tags::ORD_STATUS), Some(ord_status), "{}", report);
+    assert_eq!(report.get(tags::LEAVES_QTY), Some(leaves_qty), "{}", report);
+}
+
+#[test]
+fn test_logon_test_request_and_logout() {
+    let addr = start_gateway();
+    let mut initiator = FixInitiator::logon(addr, "CLIENT");
+    let mut test_request = FixMessage::new(tags::MSG_TYPE_TEST_REQUEST);
+    test_request.set(tags::TEST_REQ_ID, "PING");
+    initiator.send(test_request);
+    let heartbeat = initiator.receive();
+
+    assert_eq!(heartbeat.msg_type(), tags::MSG_TYPE_HEARTBEAT);
+    assert_eq!(heartbeat.get(tags::TEST_REQ_ID), Some("PING"));
+    assert_eq!(heartbeat.seq_num(), Some(2));
+
+    let mut duplicate = FixInitiator::connect(addr, "CLIENT");
+    let mut logon = FixMessage::new(tags::MSG_TYPE_LOGON);
+    logon.set(tags::ENCRYPT_METHOD, 0).set(tags::HEART_BT_INT, 30);
+    duplicate.send(logon);
+
+    assert!(duplicate.try_receive(Duration::from_millis(300)).is_none());
+
+    initiator.send(FixMessage::new(tags::MSG_TYPE_LOGOUT));
+
+    assert_eq!(initiator.receive().msg_type(), tags::MSG_TYPE_LOGOUT);
+    assert!(initiator.try_receive(Duration::from_millis(300)).is_none());
+}
+
+#[test]
+fn test_orders_cross_between_sessions() {
+    let addr = start_gateway();
+    let mut seller = FixInitiator::logon(addr, "SELLER");
+    let mut buyer = FixInitiator::logon(addr, "BUYER");
+    seller.new_order("S1", "2", 10, Some(100));
+
+    assert_report(&seller.receive(), "S1", "0", "0", "10");
+
+    buyer.new_order("B1", "1", 4, Some(101));
+    assert_report(&buyer.receive(), "B1", "0", "0", "4");
+    let buyer_fill = buyer.receive();
+    assert_report(&buyer_fill, "B1", "F", "2", "0");
+    assert_eq!(buyer_fill.get(tags::LAST_PX), Some("100"));
+    assert_eq!(buyer_fill.get(tags::LAST_QTY), Some("4"));
+    assert_eq!(buyer_fill.get(tags::CUM_QTY), Some("4"));
+
+    let seller_fill = seller.receive();
+    assert_report(&seller_fill, "S1", "F", "1", "6");
+    assert_eq!(seller_fill.get(tags::LAST_PX), Some("100"));
+
+    buyer.new_order("B2", "1", 20, None);
+    let cash_order_qty_reject = buyer.receive();
+    assert_report(&cash_order_qty_reject, "B2", "8", "8", "0");
+    assert_eq!(
+        cash_order_qty_reject.get(tags::TEXT),
+        Some("Market buy requires CashOrderQty")
+    );
+
+    let mut market_order = FixMessage::new(tags::MSG_TYPE_NEW_ORDER_SINGLE);
+    market_order
+        .set(tags::CL_ORD_ID, "B2")
+        .set(tags::SYMBOL, "BTC-IDR")
+        .set(tags::SIDE, "1")
+        .set(tags::ORDER_QTY, 20)
+        .set(tags::ORD_TYPE, "1")
+        .set(tags::CASH_ORDER_QTY, 10_000);
+    buyer.send(market_order);
+    assert_report(&buyer.receive(), "B2", "0", "0", "20");
+    assert_report(&buyer.receive(), "B2", "F", "1", "14");
+    assert_report(&buyer.receive(), "B2", "4", "4", "0");
+    assert_report(&seller.receive(), "S1", "F", "2", "0");
+
+    buyer.new_order("B2", "1", 1, Some(99));
+    let duplicate_reject = buyer.receive();
+    assert_report(&duplicate_reject, "B2", "8", "8", "0");
+    assert_eq!(duplicate_reject.get(tags::ORD_REJ_REASON), Some("6"));
+}
+
+#[test]
+fn test_cancel_replace_and_cancel_reject() {
+    let addr = start_gateway();
+    let mut initiator = FixInitiator::logon(addr, "CLIENT");
+    initiator.new_order("O1", "1", 10, Some(95));
+    assert_report(&initiator.receive(), "O1", "0", "0", "10");
+
+    let mut replace = FixMessage::new(tags::MSG_TYPE_ORDER_CANCEL_REPLACE_REQUEST);
+    replace
+        .set(tags::ORIG_CL_ORD_ID, "O1")
+        .set(tags::CL_ORD_ID, "O2")
+        .set(tags::SYMBOL, "BTC-IDR")
+        .set(tags::SIDE, "1")
+        .set(tags::ORDER_QTY, 12)
+        .set(tags::ORD_TYPE, "2")
+        .set(tags::PRICE, 96);
+    initiator.send(replace);
+    let replaced = initiator.receive();
+    assert_report(&replaced, "O2", "5", "0", "12");
+    assert_eq!(replaced.get(tags::ORIG_CL_ORD_ID), Some("O1"));
+    assert_eq!(replaced.get(tags::PRICE), Some("96"));
+
+    let mut cancel = FixMessage::new(tags::MSG_TYPE_ORDER_CANCEL_REQUEST);
+    cancel
+        .set(tags::ORIG_CL_ORD_ID, "O1")
+        .set(tags::CL_ORD_ID, "O3")
+        .set(tags::SYMBOL, "BTC-IDR")
+        .set(tags::SIDE, "1");
+    initiator.send(cancel.clone());
+    let cancel_reject = initiator.receive();
+    assert_eq!(cancel_reject.msg_type(), tags::MSG_TYPE_ORDER_CANCEL_REJECT);
+    assert_eq!(cancel_reject.get(tags::CXL_REJ_RESPONSE_TO), Some("1"));
+    assert_eq!(cancel_reject.get(tags::CXL_REJ_REASON), Some("0"));
+
+    cancel.set(tags::ORIG_CL_ORD_ID, "O2").set(tags::CL_ORD_ID, "O4");
+    initiator.send(cancel.clone());
+    let cancelled = initiator.receive();
+    assert_report(&cancelled, "O4", "4", "4", "0");
+    assert_eq!(cancelled.get(tags::ORIG_CL_ORD_ID), Some("O2"));
+    assert_eq!(cancelled.get(tags::ORDER_ID), replaced.get(tags::ORDER_ID));
+
+    cancel.set(tags::ORIG_CL_ORD_ID, "UNKNOWN").set(tags::CL_ORD_ID, "O5");
+    initiator.send(cancel);
+    let cancel_reject = initiator.receive();
+    assert_eq!(cancel_reject.msg_type(), tags::MSG_TYPE_ORDER_CANCEL_REJECT);
+    assert_eq!(cancel_reject.get(tags::CXL_REJ_REASON), Some("1"));
+    assert_eq!(cancel_reject.get(tags::ORD_STATUS), Some("8"));
+}
+
+#[test]
+fn test_engine_and_risk_rejections() {
+    let (addr, gateway) = start_gateway_with_handle();
+    let mut initiator = FixInitiator::logon(addr, "CLIENT");
+    gateway
+        .lock()
+        .unwrap()
+        .risk_engines
+        .get_mut("BTC-IDR")
+        .unwrap()
+        .set_default_limits(RiskLimits {
+            max_order_size: Some(5),
+            ..Default::default()
+        });
+
+    initiator.new_order("O1", "1", 10, Some(95));
+    let risk_reject = initiator.receive();
+    assert_report(&risk_reject, "O1", "8", "8", "0");
+    assert_eq!(risk_reject.get(tags::ORD_REJ_REASON), Some("3"));
+    assert_eq!(risk_reject.get(tags::TEXT), Some("Max order size of 5 breached at 10"));
+
+    initiator.new_order("O1", "1", 5, Some(95));
+    assert_report(&initiator.receive(), "O1", "0", "0", "5");
+
+    let mut replace = FixMessage::new(tags::MSG_TYPE_ORDER_CANCEL_REPLACE_REQUEST);
+    replace
+        .set(tags::ORIG_CL_ORD_ID, "O1")
+        .set(tags::CL_ORD_ID, "O2")
+        .set(tags::SYMBOL, "BTC-IDR")
+        .set(tags::SIDE, "1")
+        .set(tags::ORDER_QTY, 6)
+        .set(tags::ORD_TYPE, "2")
+        .set(tags::PRICE, 96);
+    initiator.send(replace);
+    let replace_reject = initiator.receive();
+    assert_eq!(replace_reject.msg_type(), tags::MSG_TYPE_ORDER_CANCEL_REJECT);
+    assert_eq!(replace_reject.get(tags::CXL_REJ_RESPONSE_TO), Some("2"));
+    assert_eq!(replace_reject.get(tags::ORD_STATUS), Some("0"));
+
+    gateway
+        .lock()
+        .unwrap()
+        .instruments
+        .get_mut("BTC-IDR")
+        .unwrap()
+        .halt()
+        .unwrap();
+    initiator.new_order("O3", "2", 1, Some(100));
+    let halted_reject = initiator.receive();
+    assert_report(&halted_reject, "O3", "8", "8", "0");
+    assert_eq!(halted_reject.get(tags::ORD_REJ_REASON), Some("2"));
+}
+
+#[test]
+fn test_resend_request_and_sequence_gap() {
+    let addr = start_gateway();
+    let mut initiator = FixInitiator::logon(addr, "CLIENT");
+    initiator.new_order("O1", "2", 5, Some(100));
+    assert_report(&initiator.receive(), "O1", "0", "0", "5");
+    initiator.new_order("O2", "2", 5, Some(101));
+    assert_report(&initiator.receive(), "O2", "0", "0", "5");
+
+    let mut resend_request = FixMessage::new(tags::MSG_TYPE_RESEND_REQUEST);
+    resend_request.set(tags::BEGIN_SEQ_NO, 1).set(tags::END_SEQ_NO, 0);
+    initiator.send(resend_request);
+    let gap_fill = initiator.receive();
+
+    assert_eq!(gap_fill.msg_type(), tags::MSG_TYPE_SEQUENCE_RESET);
+    assert_eq!(gap_fill.seq_num(), Some(1));
+    assert_eq!(gap_fill.get(tags::GAP_FILL_FLAG), Some("Y"));
+    assert_eq!(gap_fill.get(tags::NEW_SEQ_NO), Some("2"));
+
+    for (seq_num, cl_ord_id) in [(2, "O1"), (3, "O2")].iter() {
+        let resent = initiator.receive();
+
+        assert_eq!(resent.seq_num(), Some(*seq_num));
+        assert_eq!(resent.get(tags::POSS_DUP_FLAG), Some("Y"));
+        assert!(resent.get(tags::ORIG_SENDING_TIME).is_some());
+        assert_report(&resent, cl_ord_id, "0", "0", "5");
+    }
+
+    initiator.send_with_seq_num(FixMessage::new(tags::MSG_TYPE_HEARTBEAT), 7);
+    let resend_request = initiator.receive();
+
+    assert_eq!(resend_request.msg_type(), tags::MSG_TYPE_RESEND_REQUEST);
+    assert_eq!(resend_request.get(tags::BEGIN_SEQ_NO), Some("5"));
+    assert_eq!(resend_request.get(tags::END_SEQ_NO), Some("0"));
+
+    let mut gap_fill = FixMessage::new(tags::MSG_TYPE_SEQUENCE_RESET);
+    gap_fill.set(tags::GAP_FILL_FLAG, "Y").set(tags::NEW_SEQ_NO, 8);
+    initiator.send_with_seq_num(gap_fill, 5);
+    initiator.next_seq_num = 8;
+    initiator.new_order("O3", "2", 1, Some(102));
+
+    assert_report(&initiator.receive(), "O3", "0", "0", "1");
+}
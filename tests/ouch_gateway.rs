@@ -0,0 +1,294 @@
+use cc_matching_engine::ouch::*;
+use cc_matching_engine::*;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::thread;
+use std::time::{Duration, Instant};
+
+struct OuchClient {
+    stream: TcpStream,
+    buffer: Vec<u8>,
+}
+
+impl OuchClient {
+    fn connect(addr: SocketAddr) -> OuchClient {
+        let stream = TcpStream::connect(addr).unwrap();
+        stream.set_read_timeout(Some(Duration::from_millis(20))).unwrap();
+
+        OuchClient {
+            stream,
+            buffer: Vec::new(),
+        }
+    }
+
+    fn send(&mut self, request: OuchRequest) {
+        let mut buffer = Vec::new();
+        request.encode(&mut buffer);
+        self.stream.write_all(&buffer).unwrap();
+    }
+
+    fn enter(&mut self, token: OrderToken, side: OrderSide, r#type: OrderType, volume: u64, price: u64) {
+        self.send(OuchRequest::EnterOrder {
+            token,
+            symbol: symbol("BTC-IDR"),
+            side,
+            r#type,
+            volume,
+            price,
+            max_quote: 0,
+        });
+    }
+
+    fn try_receive(&mut self, timeout: Duration) -> Option<OuchResponse> {
+        let deadline = Instant::now() + timeout;
+        let mut chunk = [0; 1024];
+
+        while Instant::now() < deadline {
+            if let Some(payload) = take_payload(&mut self.buffer) {
+                return Some(OuchResponse::decode(&payload).expect("Malformed response"));
+            }
+
+            match self.stream.read(&mut chunk) {
+                Ok(0) => panic!("Gateway closed the connection"),
+                Ok(read_size) => self.buffer.extend_from_slice(&chunk[..read_size]),
+                Err(error) if error.kind() == ErrorKind::WouldBlock || error.kind() == ErrorKind::TimedOut => {}
+                Err(error) => panic!("{}", error),
+            }
+        }
+
+        None
+    }
+
+    fn receive(&mut self) -> OuchResponse {
+        self.try_receive(Duration::from_secs(5))
+            .expect("No message from the gateway")
+    }
+}
+
+fn start_gateway() -> SocketAddr {
+    let server = OuchServer::bind("127.0.0.1:0", &["BTC-IDR".to_string()]).unwrap();
+    let addr = server.local_addr().unwrap();
+    thread::spawn(move || server.run());
+    addr
+}
+
+#[test]
+fn test_enter_execute_replace_and_cancel() {
+    let addr = start_gateway();
+    let mut seller = OuchClient::connect(addr);
+    let mut buyer = OuchClient::connect(addr);
+    seller.enter(1, OrderSide::Ask, OrderType::Limit, 10, 100);
+
+    let ask_id = match seller.receive() {
+        OuchResponse::Accepted {
+            token,
+            volume,
+            order_id,
+            ..
+        } => {
+            assert_eq!(token, 1);
+            assert_eq!(volume, 10);
+            order_id
+        }
+        response => panic!("Unexpected response: {:?}", response),
+    };
+
+    buyer.enter(1, OrderSide::Bid, OrderType::Limit, 4, 101);
+
+    match buyer.receive() {
+        OuchResponse::Accepted { token, order_id, .. } => {
+            assert_eq!(token, 1);
+            assert_ne!(order_id, ask_id);
+        }
+        response => panic!("Unexpected response: {:?}", response),
+    }
+
+    match (buyer.receive(), seller.receive()) {
+        (
+            OuchResponse::Executed {
+                executed_volume: bid_volume,
+                price: bid_price,
+                match_number: bid_match_number,
+                remaining_volume: bid_remaining_volume,
+                ..
+            },
+            OuchResponse::Executed {
+                token,
+                executed_volume,
+                price,
+                match_number,
+                remaining_volume,
+                ..
+            },
+        ) => {
+            assert_eq!((bid_volume, bid_price, bid_remaining_volume), (4, 100, 0));
+            assert_eq!((token, executed_volume, price, remaining_volume), (1, 4, 100, 6));
+            assert_eq!(bid_match_number, match_number);
+        }
+        responses => panic!("Unexpected responses: {:?}", responses),
+    }
+
+    seller.send(OuchRequest::ReplaceOrder {
+        existing_token: 1,
+        replacement_token: 2,
+        volume: 8,
+        price: 105,
+    });
+
+    match seller.receive() {
+        OuchResponse::Replaced {
+            replacement_token,
+            previous_token,
+            volume,
+            price,
+            order_id,
+            ..
+        } => {
+            assert_eq!((replacement_token, previous_token, volume, price), (2, 1, 8, 105));
+            assert_ne!(order_id, ask_id);
+        }
+        response => panic!("Unexpected response: {:?}", response),
+    }
+
+    buyer.send(OuchRequest::CancelOrder { token: 2 });
+
+    match buyer.receive() {
+        OuchResponse::Rejected { token, reason, .. } => {
+            assert_eq!(token, 2);
+            assert_eq!(reason, REJECT_REASON_UNKNOWN_ORDER);
+        }
+        response => panic!("Unexpected response: {:?}", response),
+    }
+
+    seller.send(OuchRequest::CancelOrder { token: 2 });
+
+    match seller.receive() {
+        OuchResponse::Canceled {
+            token,
+            canceled_volume,
+            reason,
+            ..
+        } => {
+            assert_eq!((token, canceled_volume), (2, 8));
+            assert_eq!(reason, CANCEL_REASON_USER_REQUESTED);
+        }
+        response => panic!("Unexpected response: {:?}", response),
+    }
+
+    assert!(seller.try_receive(Duration::from_millis(200)).is_none());
+}
+
+#[test]
+fn test_rejections_and_market_remainder() {
+    let addr = start_gateway();
+    let mut client = OuchClient::connect(addr);
+    client.enter(1, OrderSide::Ask, OrderType::Limit, 5, 100);
+    client.receive();
+    client.enter(1, OrderSide::Bid, OrderType::Limit, 5, 90);
+
+    match client.receive() {
+        OuchResponse::Rejected { token, reason, .. } => {
+            assert_eq!(token, 1);
+            assert_eq!(reason, REJECT_REASON_DUPLICATE_TOKEN);
+        }
+        response => panic!("Unexpected response: {:?}", response),
+    }
+
+    client.enter(2, OrderSide::Bid, OrderType::Limit, 5, 0);
+
+    match client.receive() {
+        OuchResponse::Rejected { reason, .. } => assert_eq!(reason, REJECT_REASON_INVALID_PRICE),
+        response => panic!("Unexpected response: {:?}", response),
+    }
+
+    client.send(OuchRequest::EnterOrder {
+        token: 3,
+        symbol: symbol("ETH-IDR"),
+        side: OrderSide::Bid,
+        r#type: OrderType::Limit,
+        volume: 5,
+        price: 90,
+        max_quote: 0,
+    });
+
+    match client.receive() {
+        OuchResponse::Rejected { reason, .. } => assert_eq!(reason, REJECT_REASON_UNKNOWN_SYMBOL),
+        response => panic!("Unexpected response: {:?}", response),
+    }
+
+    client.enter(4, OrderSide::Ask, OrderType::Market, 5, 0);
+    client.receive();
+
+    match client.receive() {
+        OuchResponse::Canceled {
+            token,
+            canceled_volume,
+            reason,
+            ..
+        } => {
+            assert_eq!((token, canceled_volume), (4, 5));
+            assert_eq!(reason, CANCEL_REASON_IMMEDIATE_OR_CANCEL);
+        }
+        response => panic!("Unexpected response: {:?}", response),
+    }
+
+    client.send(OuchRequest::CancelOrder { token: 4 });
+
+    match client.receive() {
+        OuchResponse::Rejected { reason, .. } => assert_eq!(reason, REJECT_REASON_UNKNOWN_ORDER),
+        response => panic!("Unexpected response: {:?}", response),
+    }
+}
+
+#[test]
+fn test_stalled_client_does_not_block_others() {
+    let addr = start_gateway();
+    let stalled_stream = TcpStream::connect(addr).unwrap();
+    let mut writer_stream = stalled_stream.try_clone().unwrap();
+    let mut client = OuchClient::connect(addr);
+
+    // Each empty frame is rejected, far more responses than the socket buffers
+    // hold, and the stalled client never reads them.
+    thread::spawn(move || writer_stream.write_all(&vec![0; 2 * 1_000_000]));
+    thread::sleep(Duration::from_millis(1_000));
+    client.enter(1, OrderSide::Bid, OrderType::Limit, 10, 100);
+
+    match client.receive() {
+        OuchResponse::Accepted { token, .. } => assert_eq!(token, 1),
+        response => panic!("Unexpected response: {:?}", response),
+    }
+
+    drop(stalled_stream);
+}
+
+#[test]
+fn test_risk_limit_breach_is_rejected() {
+    let server = OuchServer::bind("127.0.0.1:0", &["BTC-IDR".to_string()]).unwrap();
+    let addr = server.local_addr().unwrap();
+    server
+        .gateway()
+        .lock()
+        .unwrap()
+        .risk_engines
+        .get_mut(&symbol("BTC-IDR"))
+        .unwrap()
+        .set_default_limits(RiskLimits {
+            max_order_size: Some(5),
+            ..Default::default()
+        });
+    thread::spawn(move || server.run());
+    let mut client = OuchClient::connect(addr);
+    client.enter(1, OrderSide::Bid, OrderType::Limit, 10, 100);
+
+    match client.receive() {
+        OuchResponse::Rejected { token, reason, .. } => assert_eq!((token, reason), (1, REJECT_REASON_RISK_LIMIT)),
+        response => panic!("Unexpected response: {:?}", response),
+    }
+
+    client.enter(2, OrderSide::Bid, OrderType::Limit, 5, 100);
+
+    match client.receive() {
+        OuchResponse::Accepted { token, .. } => assert_eq!(token, 2),
+        response => panic!("Unexpected response: {:?}", response),
+    }
+}
@@ -0,0 +1,219 @@
+use cc_matching_engine::rest::*;
+use cc_matching_engine::*;
+use serde_json::{json, Value};
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::thread;
+
+fn start_gateway() -> SocketAddr {
+    let server = RestServer::bind("127.0.0.1:0", &["BTC-IDR".to_string()]).unwrap();
+    let addr = server.local_addr().unwrap();
+    thread::spawn(move || server.run());
+    addr
+}
+
+fn request(addr: SocketAddr, method: &str, path: &str, body: Option<Value>) -> (u16, Value) {
+    let body = body.map(|body| body.to_string()).unwrap_or_default();
+    let mut stream = TcpStream::connect(addr).unwrap();
+    let request = format!(
+        "{} {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+        method,
+        path,
+        addr,
+        body.len(),
+        body
+    );
+    stream.write_all(request.as_bytes()).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let status = response[9..12].parse().unwrap();
+    let body_start = response.find("\r\n\r\n").unwrap() + 4;
+
+    (status, serde_json::from_str(&response[body_start..]).unwrap())
+}
+
+fn limit_order(side: &str, volume: u64, price: u64) -> Value {
+    json!({ "side": side, "type": "limit", "volume": volume, "price": price })
+}
+
+#[test]
+fn test_order_lifecycle_over_http() {
+    let addr = start_gateway();
+    let (status, placed) = request(
+        addr,
+        "POST",
+        "/instruments/BTC-IDR/orders",
+        Some(limit_order("ask", 10, 100)),
+    );
+    let ask_id = placed["order_id"].as_str().unwrap().to_string();
+    let ask_path = format!("/instruments/BTC-IDR/orders/{}", ask_id);
+
+    assert_eq!(status, 201);
+    assert_eq!(placed["events"][&ask_id][1]["type"], "open");
+
+    let (status, order_status) = request(addr, "GET", &ask_path, None);
+
+    assert_eq!(status, 200);
+    assert_eq!(order_status["side"], "ask");
+    assert_eq!(order_status["price"], 100);
+    assert_eq!(order_status["remaining_volume"], 10);
+
+    let (status, _) = request(
+        addr,
+        "POST",
+        "/instruments/BTC-IDR/orders",
+        Some(limit_order("bid", 4, 100)),
+    );
+    let (_, trades) = request(addr, "GET", "/instruments/BTC-IDR/trades?limit=10", None);
+    let (_, depth) = request(addr, "GET", "/instruments/BTC-IDR/depth?levels=5", None);
+
+    assert_eq!(status, 201);
+    assert_eq!(trades.as_array().unwrap().len(), 1);
+    assert_eq!(trades[0]["resting_id"], ask_id.as_str());
+    assert_eq!(trades[0]["volume"], 4);
+    assert_eq!(depth["asks"], json!([[100, 6]]));
+
+    let (status, cancelled) = request(addr, "DELETE", &ask_path, None);
+
+    assert_eq!(status, 200);
+    assert_eq!(cancelled["events"][&ask_id][0]["type"], "cancelled");
+    assert_eq!(request(addr, "GET", &ask_path, None).0, 404);
+    assert_eq!(request(addr, "DELETE", &ask_path, None).0, 404);
+
+    for price in [90, 91, 92].iter() {
+        request(
+            addr,
+            "POST",
+            "/instruments/BTC-IDR/orders",
+            Some(limit_order("bid", 1, *price)),
+        );
+    }
+
+    let orders_path = "/instruments/BTC-IDR/orders";
+    let (status, mass_cancelled) = request(addr, "DELETE", orders_path, Some(json!({ "min_price": 91 })));
+    let request_id = mass_cancelled["order_id"].as_str().unwrap();
+
+    assert_eq!(status, 200);
+    assert_eq!(mass_cancelled["events"].as_object().unwrap().len(), 3);
+    assert_eq!(mass_cancelled["events"][request_id][0]["type"], "mass_cancelled");
+
+    let (status, mass_cancelled) = request(addr, "DELETE", orders_path, None);
+    let (_, depth) = request(addr, "GET", "/instruments/BTC-IDR/depth", None);
+
+    assert_eq!(status, 200);
+    assert_eq!(mass_cancelled["events"].as_object().unwrap().len(), 2);
+    assert_eq!(depth["bids"], json!([]));
+}
+
+#[test]
+fn test_rejections_map_to_status_codes() {
+    let addr = start_gateway();
+    let orders_path = "/instruments/BTC-IDR/orders";
+    let market_bid = json!({ "side": "bid", "type": "market", "volume": 1 });
+    let (status, error) = request(addr, "POST", orders_path, Some(market_bid));
+
+    assert_eq!(status, 422);
+    assert_eq!(error["error"], OrderRejection::MissingMaxQuote.to_string());
+    assert_eq!(
+        request(addr, "POST", orders_path, Some(json!({ "side": "sideways" }))).0,
+        400
+    );
+    assert_eq!(
+        request(
+            addr,
+            "POST",
+            "/instruments/ETH-IDR/orders",
+            Some(limit_order("bid", 1, 1))
+        )
+        .0,
+        404
+    );
+    assert_eq!(
+        request(addr, "GET", "/instruments/BTC-IDR/orders/not-a-uuid", None).0,
+        400
+    );
+    assert_eq!(request(addr, "PUT", "/instruments/BTC-IDR/depth", None).0, 405);
+
+    let (_, placed) = request(addr, "POST", orders_path, Some(limit_order("bid", 5, 90)));
+    let duplicate = json!({ "id": placed["order_id"], "side": "bid", "type": "limit", "volume": 5, "price": 90 });
+
+    assert_eq!(request(addr, "POST", orders_path, Some(duplicate)).0, 409);
+
+    let (status, halted) = request(addr, "POST", "/instruments/BTC-IDR/halt", None);
+
+    assert_eq!(status, 200);
+    assert_eq!(halted["halted"], true);
+    assert_eq!(halted["events"], json!({}));
+    assert_eq!(request(addr, "POST", "/instruments/BTC-IDR/halt", None).0, 409);
+
+    let (status, error) = request(addr, "POST", orders_path, Some(limit_order("bid", 1, 90)));
+
+    assert_eq!(status, 409);
+    assert_eq!(
+        error["error"],
+        OrderRejection::Session(SessionState::Halted).to_string()
+    );
+
+    let bid_path = format!("{}/{}", orders_path, placed["order_id"].as_str().unwrap());
+
+    assert_eq!(request(addr, "DELETE", &bid_path, None).0, 200);
+
+    request(addr, "POST", "/instruments/BTC-IDR/resume", None);
+    let (_, instruments) = request(addr, "GET", "/instruments", None);
+
+    assert_eq!(instruments[0]["symbol"], "BTC-IDR");
+    assert_eq!(instruments[0]["halted"], false);
+    assert_eq!(
+        request(addr, "POST", orders_path, Some(limit_order("bid", 1, 90))).0,
+        201
+    );
+
+    let session_path = "/instruments/BTC-IDR/session";
+    let (status, closed) = request(addr, "POST", session_path, Some(json!({ "state": "closed" })));
+
+    assert_eq!(status, 200);
+    assert_eq!(closed["session"], "closed");
+
+    let (status, error) = request(addr, "POST", orders_path, Some(limit_order("bid", 1, 90)));
+
+    assert_eq!(status, 409);
+    assert_eq!(
+        error["error"],
+        OrderRejection::Session(SessionState::Closed).to_string()
+    );
+
+    let (status, error) = request(addr, "POST", session_path, Some(json!({ "state": "continuous" })));
+
+    assert_eq!(status, 409);
+    assert_eq!(error["error"], "Cannot move from closed to continuous");
+}
+
+#[test]
+fn test_risk_limits_over_http() {
+    let addr = start_gateway();
+    let orders_path = "/instruments/BTC-IDR/orders";
+    let risk_path = "/instruments/BTC-IDR/risk";
+    let limits = json!({ "account": 7, "limits": { "max_order_size": 10, "max_messages": { "count": 2, "window": 60_000_000_000u64 } } });
+    let (status, risk_status) = request(addr, "POST", risk_path, Some(limits));
+
+    assert_eq!(status, 200);
+    assert_eq!(risk_status["limits"]["max_order_size"], 10);
+
+    let account_order =
+        |volume: u64| json!({ "side": "bid", "type": "limit", "volume": volume, "price": 90, "account": 7 });
+    let (status, error) = request(addr, "POST", orders_path, Some(account_order(11)));
+
+    assert_eq!(status, 422);
+    assert_eq!(error["error"], "Max order size of 10 breached at 11");
+    assert_eq!(request(addr, "POST", orders_path, Some(account_order(5))).0, 201);
+    assert_eq!(request(addr, "POST", orders_path, Some(account_order(5))).0, 429);
+    assert_eq!(
+        request(addr, "POST", orders_path, Some(limit_order("bid", 11, 90))).0,
+        201
+    );
+
+    let (_, risk_status) = request(addr, "GET", &format!("{}?account=7", risk_path), None);
+
+    assert_eq!(risk_status["open_orders"], 1);
+    assert_eq!(risk_status["position"], 0);
+}
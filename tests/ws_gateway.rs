@@ -0,0 +1,251 @@
+use cc_matching_engine::ws::*;
+use cc_matching_engine::*;
+use serde_json::json;
+use std::io::ErrorKind;
+use std::net::{SocketAddr, TcpStream};
+use std::thread;
+use std::time::{Duration, Instant};
+use tungstenite::{Error, Message, WebSocket};
+
+struct WsClient {
+    websocket: WebSocket<TcpStream>,
+}
+
+impl WsClient {
+    fn connect(addr: SocketAddr) -> WsClient {
+        let stream = TcpStream::connect(addr).unwrap();
+        let (websocket, _) = tungstenite::client(format!("ws://{}/", addr), stream).unwrap();
+        websocket
+            .get_ref()
+            .set_read_timeout(Some(Duration::from_millis(20)))
+            .unwrap();
+
+        WsClient { websocket }
+    }
+
+    fn send(&mut self, request: serde_json::Value) {
+        self.websocket.send(Message::Text(request.to_string())).unwrap();
+    }
+
+    fn try_receive(&mut self, timeout: Duration) -> Option<WsResponse> {
+        let deadline = Instant::now() + timeout;
+
+        while Instant::now() < deadline {
+            match self.websocket.read() {
+                Ok(Message::Text(text)) => return Some(serde_json::from_str(&text).unwrap()),
+                Ok(_) => {}
+                Err(Error::Io(error)) if error.kind() == ErrorKind::WouldBlock => {}
+                Err(error) => panic!("{}", error),
+            }
+        }
+
+        None
+    }
+
+    fn receive(&mut self) -> WsResponse {
+        self.try_receive(Duration::from_secs(5))
+            .expect("No message from the gateway")
+    }
+
+    fn submit(&mut self, request_id: u64, side: &str, volume: u64, price: u64) -> OrderId {
+        self.send(json!({
+            "op": "submit",
+            "request_id": request_id,
+            "symbol": "BTC-IDR",
+            "order": { "side": side, "type": "limit", "volume": volume, "price": price }
+        }));
+
+        match self.receive() {
+            WsResponse::Accepted {
+                request_id: Some(accepted_request_id),
+                order_id,
+                ..
+            } if accepted_request_id == request_id => order_id,
+            response => panic!("Unexpected response: {:?}", response),
+        }
+    }
+
+    fn order_events(&mut self, expected_order_id: OrderId) -> Vec<OrderEvent> {
+        match self.receive() {
+            WsResponse::OrderEvents { order_id, events, .. } if order_id == expected_order_id => events,
+            response => panic!("Unexpected response: {:?}", response),
+        }
+    }
+}
+
+fn start_gateway() -> SocketAddr {
+    let server = WsServer::bind("127.0.0.1:0", &["BTC-IDR".to_string()]).unwrap();
+    let addr = server.local_addr().unwrap();
+    thread::spawn(move || server.run());
+    addr
+}
+
+#[test]
+fn test_order_entry_and_own_events() {
+    let addr = start_gateway();
+    let mut seller = WsClient::connect(addr);
+    let mut buyer = WsClient::connect(addr);
+    let ask_id = seller.submit(1, "ask", 10, 100);
+    let ask_events = seller.order_events(ask_id);
+
+    assert_eq!(ask_events[0].r#type, OrderEventType::NoMatch);
+    assert_eq!(ask_events[1].r#type, OrderEventType::Open);
+
+    let bid_id = buyer.submit(2, "bid", 4, 101);
+    let bid_events = buyer.order_events(bid_id);
+    let ask_events = seller.order_events(ask_id);
+
+    assert_eq!(bid_events[0].r#type, OrderEventType::HasMatch);
+    assert_eq!(bid_events[0].traded_price, Some(100));
+    assert_eq!(bid_events[1].r#type, OrderEventType::Closed);
+    assert_eq!(ask_events[0].r#type, OrderEventType::HasMatch);
+    assert_eq!(ask_events[0].remaining_volume, Some(6));
+    assert!(buyer.try_receive(Duration::from_millis(200)).is_none());
+
+    buyer.send(json!({ "op": "cancel", "request_id": 3, "symbol": "BTC-IDR", "order_id": ask_id }));
+
+    match buyer.receive() {
+        WsResponse::Rejected { request_id, reason } => {
+            assert_eq!(request_id, Some(3));
+            assert_eq!(reason, "Unknown order");
+        }
+        response => panic!("Unexpected response: {:?}", response),
+    }
+
+    seller.send(json!({ "op": "cancel", "request_id": 4, "symbol": "BTC-IDR", "order_id": ask_id }));
+    seller.receive();
+    let cancel_events = seller.order_events(ask_id);
+
+    assert_eq!(cancel_events[0].r#type, OrderEventType::Cancelled);
+    assert_eq!(cancel_events[0].remaining_volume, Some(6));
+
+    seller.send(json!({ "op": "submit", "symbol": "ETH-IDR", "order": { "side": "ask", "volume": 1, "price": 1 } }));
+
+    match seller.receive() {
+        WsResponse::Rejected { reason, .. } => assert_eq!(reason, "Unknown symbol"),
+        response => panic!("Unexpected response: {:?}", response),
+    }
+}
+
+#[test]
+fn test_depth_and_trade_subscriptions() {
+    let addr = start_gateway();
+    let mut trader = WsClient::connect(addr);
+    let mut viewer = WsClient::connect(addr);
+    trader.submit(1, "ask", 10, 100);
+    trader.receive();
+    viewer.send(json!({ "op": "subscribe", "symbol": "BTC-IDR", "channel": "depth" }));
+    viewer.receive();
+
+    let snapshot = match viewer.receive() {
+        WsResponse::DepthSnapshot { snapshot, .. } => snapshot,
+        response => panic!("Unexpected response: {:?}", response),
+    };
+
+    assert_eq!(snapshot.asks, vec![(100, 10)]);
+
+    viewer.send(json!({ "op": "subscribe", "symbol": "BTC-IDR", "channel": "trades" }));
+    viewer.receive();
+    let bid_id = trader.submit(2, "bid", 3, 100);
+    trader.receive();
+    trader.receive();
+
+    match viewer.receive() {
+        WsResponse::DepthUpdates { updates, .. } => {
+            assert_eq!(updates.len(), 1);
+            assert_eq!(updates[0].sequence, snapshot.sequence + 1);
+            assert_eq!(updates[0].side, OrderSide::Ask);
+            assert_eq!(updates[0].volume, 7);
+        }
+        response => panic!("Unexpected response: {:?}", response),
+    }
+
+    match viewer.receive() {
+        WsResponse::Trades { trades, .. } => {
+            assert_eq!(trades.len(), 1);
+            assert_eq!(trades[0].aggressor_id, bid_id);
+            assert_eq!(trades[0].price, 100);
+            assert_eq!(trades[0].volume, 3);
+        }
+        response => panic!("Unexpected response: {:?}", response),
+    }
+
+    viewer.send(json!({ "op": "unsubscribe", "symbol": "BTC-IDR", "channel": "depth" }));
+    viewer.receive();
+    trader.submit(3, "bid", 1, 90);
+
+    assert!(viewer.try_receive(Duration::from_millis(200)).is_none());
+}
+
+#[test]
+fn test_cancel_on_disconnect() {
+    let addr = start_gateway();
+    let mut trader = WsClient::connect(addr);
+    let mut market_maker = WsClient::connect(addr);
+    let mut viewer = WsClient::connect(addr);
+    trader.submit(1, "ask", 10, 100);
+    trader.receive();
+    market_maker.send(json!({ "op": "cancel_on_disconnect", "request_id": 2, "enabled": false }));
+
+    match market_maker.receive() {
+        WsResponse::CancelOnDisconnect { request_id, enabled } => {
+            assert_eq!(request_id, Some(2));
+            assert!(!enabled);
+        }
+        response => panic!("Unexpected response: {:?}", response),
+    }
+
+    market_maker.submit(3, "ask", 5, 101);
+    market_maker.receive();
+    viewer.send(json!({ "op": "subscribe", "symbol": "BTC-IDR", "channel": "depth" }));
+    viewer.receive();
+    viewer.receive();
+    drop(trader);
+    drop(market_maker);
+
+    match viewer.receive() {
+        WsResponse::DepthUpdates { updates, .. } => {
+            assert_eq!(updates.len(), 1);
+            assert_eq!(updates[0].price, 100);
+            assert_eq!(updates[0].volume, 0);
+        }
+        response => panic!("Unexpected response: {:?}", response),
+    }
+
+    assert!(viewer.try_receive(Duration::from_millis(200)).is_none());
+}
+
+#[test]
+fn test_risk_limit_breach_is_rejected() {
+    let server = WsServer::bind("127.0.0.1:0", &["BTC-IDR".to_string()]).unwrap();
+    let addr = server.local_addr().unwrap();
+    server
+        .gateway()
+        .lock()
+        .unwrap()
+        .risk_engines
+        .get_mut("BTC-IDR")
+        .unwrap()
+        .set_default_limits(RiskLimits {
+            max_order_size: Some(5),
+            ..Default::default()
+        });
+    thread::spawn(move || server.run());
+    let mut client = WsClient::connect(addr);
+    client.send(json!({
+        "op": "submit",
+        "request_id": 1,
+        "symbol": "BTC-IDR",
+        "order": { "side": "bid", "type": "limit", "volume": 10, "price": 100 }
+    }));
+
+    match client.receive() {
+        WsResponse::Rejected { request_id, reason } => {
+            assert_eq!(request_id, Some(1));
+            assert_eq!(reason, "Max order size of 5 breached at 10");
+        }
+        response => panic!("Unexpected response: {:?}", response),
+    }
+
+    client.submit(2, "bid", 5, 100);
+}